@@ -8,9 +8,11 @@ use solana_sdk::{
 
 use raydium_amm_v3::accounts as raydium_accounts;
 use raydium_amm_v3::instruction as raydium_instruction;
+use raydium_amm_v3::instructions::RouteHop;
 use raydium_amm_v3::states::{
-    AMM_CONFIG_SEED, OBSERVATION_SEED, OPERATION_SEED, POOL_SEED, POOL_VAULT_SEED, POSITION_SEED,
-    TICK_ARRAY_SEED,
+    AMM_CONFIG_SEED, LIMIT_ORDER_SEED, OBSERVATION_SEED, OPERATION_SEED, POOL_FEE_OVERRIDE_SEED,
+    POOL_SEED, POOL_VAULT_SEED, POSITION_SEED, TICK_ARRAY_SEED, TWAMM_ORDER_POOL_SEED,
+    TWAMM_ORDER_SEED,
 };
 use std::rc::Rc;
 
@@ -21,6 +23,8 @@ pub fn create_amm_config_instr(
     config_index: u16,
     tick_spacing: u16,
     trade_fee_flat: u64,
+    trade_fee_rate: u32,
+    trade_fee_mode: u8,
     protocol_fee_rate: u32,
     fund_fee_rate: u32,
 ) -> Result<Vec<Instruction>> {
@@ -44,6 +48,8 @@ pub fn create_amm_config_instr(
             index: config_index,
             tick_spacing,
             trade_fee_flat,
+            trade_fee_rate,
+            trade_fee_mode,
             protocol_fee_rate,
             fund_fee_rate,
         })
@@ -76,6 +82,123 @@ pub fn update_amm_config_instr(
     Ok(instructions)
 }
 
+pub fn set_pool_fee_override_instr(
+    config: &ClientConfig,
+    amm_config: Pubkey,
+    pool_account_key: Pubkey,
+    trade_fee_flat: Option<u64>,
+    protocol_fee_rate: Option<u32>,
+    fund_fee_rate: Option<u32>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.admin_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (pool_fee_override_key, __bump) = Pubkey::find_program_address(
+        &[
+            POOL_FEE_OVERRIDE_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::SetPoolFeeOverride {
+            owner: program.payer(),
+            amm_config,
+            pool_state: pool_account_key,
+            pool_fee_override: pool_fee_override_key,
+            system_program: system_program::id(),
+        })
+        .args(raydium_instruction::SetPoolFeeOverride {
+            trade_fee_flat,
+            protocol_fee_rate,
+            fund_fee_rate,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn update_pool_price_guard_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    max_deviation_ticks: u32,
+    twap_window_seconds: u32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.admin_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::UpdatePoolPriceGuard {
+            authority: program.payer(),
+            pool_state: pool_account_key,
+        })
+        .args(raydium_instruction::UpdatePoolPriceGuard {
+            max_deviation_ticks,
+            twap_window_seconds,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn update_pool_flash_loan_fee_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    flash_loan_fee_flat: u64,
+    flash_loan_fee_rate: u32,
+    flash_loan_fee_mode: u8,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.admin_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::UpdatePoolFlashLoanFee {
+            authority: program.payer(),
+            pool_state: pool_account_key,
+        })
+        .args(raydium_instruction::UpdatePoolFlashLoanFee {
+            flash_loan_fee_flat,
+            flash_loan_fee_rate,
+            flash_loan_fee_mode,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn update_pool_circuit_breaker_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    max_ticks: u32,
+    window_seconds: u32,
+    cooldown_seconds: u32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.admin_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::UpdatePoolCircuitBreaker {
+            authority: program.payer(),
+            pool_state: pool_account_key,
+        })
+        .args(raydium_instruction::UpdatePoolCircuitBreaker {
+            max_ticks,
+            window_seconds,
+            cooldown_seconds,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
 pub fn create_operation_account_instr(config: &ClientConfig) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.admin_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
@@ -120,6 +243,31 @@ pub fn update_operation_account_instr(
     Ok(instructions)
 }
 
+pub fn update_pool_hook_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    hook_program: Pubkey,
+    hook_permissions: u8,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.admin_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::UpdatePoolHook {
+            authority: program.payer(),
+            pool_state: pool_account_key,
+        })
+        .args(raydium_instruction::UpdatePoolHook {
+            hook_program,
+            hook_permissions,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
 pub fn create_pool_instr(
     config: &ClientConfig,
     amm_config: Pubkey,
@@ -128,8 +276,12 @@ pub fn create_pool_instr(
     token_program_0: Pubkey,
     token_program_1: Pubkey,
     tick_array_bitmap: Pubkey,
+    fee_receiver: Option<Pubkey>,
     sqrt_price_x64: u128,
     open_time: u64,
+    launch_fee_flat: u64,
+    launch_fee_duration: u32,
+    launch_fee_decay_mode: u8,
 ) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.payer_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
@@ -184,10 +336,14 @@ pub fn create_pool_instr(
             token_program_1,
             system_program: system_program::id(),
             rent: sysvar::rent::id(),
+            fee_receiver,
         })
         .args(raydium_instruction::CreatePool {
             sqrt_price_x64,
             open_time,
+            launch_fee_flat,
+            launch_fee_duration,
+            launch_fee_decay_mode,
         })
         .instructions()?;
     Ok(instructions)
@@ -420,6 +576,7 @@ pub fn increase_liquidity_instr(
     tick_upper_index: i32,
     tick_array_lower_start_index: i32,
     tick_array_upper_start_index: i32,
+    deadline: Option<u64>,
 ) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.payer_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
@@ -456,7 +613,7 @@ pub fn increase_liquidity_instr(
         &program.id(),
     );
 
-    let instructions = program
+    let request = program
         .request()
         .accounts(raydium_accounts::IncreaseLiquidityV2 {
             nft_owner: program.payer(),
@@ -475,20 +632,33 @@ pub fn increase_liquidity_instr(
             vault_0_mint: token_mint_0,
             vault_1_mint: token_mint_1,
         })
-        .accounts(remaining_accounts)
-        .args(raydium_instruction::IncreaseLiquidityV2 {
+        .accounts(remaining_accounts);
+    // the original instruction is sent if no deadline is given
+    let request = if deadline.is_some() {
+        request.args(raydium_instruction::IncreaseLiquidityV2WithDeadline {
             liquidity,
             amount_0_max,
             amount_1_max,
             base_flag: None,
+            deadline,
         })
-        .instructions()?;
+    } else {
+        request.args(raydium_instruction::IncreaseLiquidityV2 {
+            liquidity,
+            amount_0_max,
+            amount_1_max,
+            base_flag: None,
+        })
+    };
+    let instructions = request.instructions()?;
     Ok(instructions)
 }
 
-pub fn decrease_liquidity_instr(
+pub fn increase_liquidity_zap_instr(
     config: &ClientConfig,
+    amm_config_key: Pubkey,
     pool_account_key: Pubkey,
+    observation_key: Pubkey,
     token_vault_0: Pubkey,
     token_vault_1: Pubkey,
     token_mint_0: Pubkey,
@@ -498,9 +668,9 @@ pub fn decrease_liquidity_instr(
     user_token_account_0: Pubkey,
     user_token_account_1: Pubkey,
     remaining_accounts: Vec<AccountMeta>,
-    liquidity: u128,
-    amount_0_min: u64,
-    amount_1_min: u64,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
     tick_lower_index: i32,
     tick_upper_index: i32,
     tick_array_lower_start_index: i32,
@@ -511,19 +681,6 @@ pub fn decrease_liquidity_instr(
     // Client.
     let client = Client::new(url, Rc::new(payer));
     let program = client.program(config.raydium_v3_program)?;
-    let (personal_position_key, __bump) = Pubkey::find_program_address(
-        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
-        &program.id(),
-    );
-    let (protocol_position_key, __bump) = Pubkey::find_program_address(
-        &[
-            POSITION_SEED.as_bytes(),
-            pool_account_key.to_bytes().as_ref(),
-            &tick_lower_index.to_be_bytes(),
-            &tick_upper_index.to_be_bytes(),
-        ],
-        &program.id(),
-    );
     let (tick_array_lower, __bump) = Pubkey::find_program_address(
         &[
             TICK_ARRAY_SEED.as_bytes(),
@@ -540,128 +697,1189 @@ pub fn decrease_liquidity_instr(
         ],
         &program.id(),
     );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+
     let instructions = program
         .request()
-        .accounts(raydium_accounts::DecreaseLiquidityV2 {
+        .accounts(raydium_accounts::IncreaseLiquidityZap {
             nft_owner: program.payer(),
             nft_account: nft_token_key,
-            personal_position: personal_position_key,
+            amm_config: amm_config_key,
             pool_state: pool_account_key,
+            observation_state: observation_key,
             protocol_position: protocol_position_key,
-            token_vault_0,
-            token_vault_1,
+            personal_position: personal_position_key,
             tick_array_lower,
             tick_array_upper,
-            recipient_token_account_0: user_token_account_0,
-            recipient_token_account_1: user_token_account_1,
+            token_account_0: user_token_account_0,
+            token_account_1: user_token_account_1,
+            token_vault_0,
+            token_vault_1,
             token_program: spl_token::id(),
             token_program_2022: spl_token_2022::id(),
-            memo_program: spl_memo::id(),
             vault_0_mint: token_mint_0,
             vault_1_mint: token_mint_1,
         })
         .accounts(remaining_accounts)
-        .args(raydium_instruction::DecreaseLiquidityV2 {
-            liquidity,
-            amount_0_min,
-            amount_1_min,
+        .args(raydium_instruction::IncreaseLiquidityZap {
+            amount_in,
+            zero_for_one,
+            liquidity_min,
         })
         .instructions()?;
     Ok(instructions)
 }
 
-pub fn close_personal_position_instr(
+pub fn open_position_zap_instr(
     config: &ClientConfig,
+    amm_config_key: Pubkey,
+    pool_account_key: Pubkey,
+    observation_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
     nft_mint_key: Pubkey,
-    nft_token_key: Pubkey,
-    nft_token_program: Pubkey,
+    nft_to_owner: Pubkey,
+    user_token_account_0: Pubkey,
+    user_token_account_1: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    with_metadata: bool,
 ) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.payer_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
     // Client.
     let client = Client::new(url, Rc::new(payer));
     let program = client.program(config.raydium_v3_program)?;
+    let nft_ata_token_account =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &program.payer(),
+            &nft_mint_key,
+            &spl_token_2022::id(),
+        );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
     let (personal_position_key, __bump) = Pubkey::find_program_address(
         &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
         &program.id(),
     );
     let instructions = program
         .request()
-        .accounts(raydium_accounts::ClosePosition {
-            nft_owner: program.payer(),
+        .accounts(raydium_accounts::OpenPositionZap {
+            payer: program.payer(),
+            position_nft_owner: nft_to_owner,
             position_nft_mint: nft_mint_key,
-            position_nft_account: nft_token_key,
+            position_nft_account: nft_ata_token_account,
+            amm_config: amm_config_key,
+            pool_state: pool_account_key,
+            observation_state: observation_key,
+            protocol_position: protocol_position_key,
+            tick_array_lower,
+            tick_array_upper,
             personal_position: personal_position_key,
+            token_account_0: user_token_account_0,
+            token_account_1: user_token_account_1,
+            token_vault_0,
+            token_vault_1,
+            rent: sysvar::rent::id(),
             system_program: system_program::id(),
-            token_program: nft_token_program,
+            token_program: spl_token::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: token_mint_0,
+            vault_1_mint: token_mint_1,
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::OpenPositionZap {
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            amount_in,
+            zero_for_one,
+            liquidity_min,
+            with_metadata,
         })
-        .args(raydium_instruction::ClosePosition)
         .instructions()?;
     Ok(instructions)
 }
 
-pub fn swap_instr(
+pub fn decrease_liquidity_zap_out_instr(
     config: &ClientConfig,
-    amm_config: Pubkey,
+    amm_config_key: Pubkey,
     pool_account_key: Pubkey,
-    input_vault: Pubkey,
-    output_vault: Pubkey,
-    observation_state: Pubkey,
-    user_input_token: Pubkey,
-    user_out_put_token: Pubkey,
-    tick_array: Pubkey,
+    observation_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    user_token_account: Pubkey,
     remaining_accounts: Vec<AccountMeta>,
-    amount: u64,
-    other_amount_threshold: u64,
-    sqrt_price_limit_x64: Option<u128>,
-    is_base_input: bool,
-) -> Result<Vec<Instruction>> {
-    let payer = read_keypair_file(&config.payer_path)?;
+    liquidity: u128,
+    amount_out_min: u64,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::DecreaseLiquidityZapOut {
+            nft_owner: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            amm_config: amm_config_key,
+            pool_state: pool_account_key,
+            observation_state: observation_key,
+            protocol_position: protocol_position_key,
+            token_vault_0,
+            token_vault_1,
+            tick_array_lower,
+            tick_array_upper,
+            recipient_token_account: user_token_account,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: token_mint_0,
+            vault_1_mint: token_mint_1,
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::DecreaseLiquidityZapOut {
+            liquidity,
+            amount_out_min,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn decrease_liquidity_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    user_token_account_0: Pubkey,
+    user_token_account_1: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    liquidity: u128,
+    amount_0_min: u64,
+    amount_1_min: u64,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    deadline: Option<u64>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let request = program
+        .request()
+        .accounts(raydium_accounts::DecreaseLiquidityV2 {
+            nft_owner: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            pool_state: pool_account_key,
+            protocol_position: protocol_position_key,
+            token_vault_0,
+            token_vault_1,
+            tick_array_lower,
+            tick_array_upper,
+            recipient_token_account_0: user_token_account_0,
+            recipient_token_account_1: user_token_account_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            memo_program: spl_memo::id(),
+            vault_0_mint: token_mint_0,
+            vault_1_mint: token_mint_1,
+        })
+        .accounts(remaining_accounts);
+    // the original instruction is sent if no deadline is given
+    let request = if deadline.is_some() {
+        request.args(raydium_instruction::DecreaseLiquidityV2WithDeadline {
+            liquidity,
+            amount_0_min,
+            amount_1_min,
+            deadline,
+        })
+    } else {
+        request.args(raydium_instruction::DecreaseLiquidityV2 {
+            liquidity,
+            amount_0_min,
+            amount_1_min,
+        })
+    };
+    let instructions = request.instructions()?;
+    Ok(instructions)
+}
+
+pub fn collect_fees_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    user_token_account_0: Pubkey,
+    user_token_account_1: Pubkey,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::CollectFees {
+            nft_owner: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            pool_state: pool_account_key,
+            protocol_position: protocol_position_key,
+            token_vault_0,
+            token_vault_1,
+            tick_array_lower,
+            tick_array_upper,
+            recipient_token_account_0: user_token_account_0,
+            recipient_token_account_1: user_token_account_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: token_mint_0,
+            vault_1_mint: token_mint_1,
+        })
+        .args(raydium_instruction::CollectFees {
+            amount_0_requested,
+            amount_1_requested,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn collect_rewards_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    reward_indexes: Vec<u8>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::CollectRewards {
+            nft_owner: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            pool_state: pool_account_key,
+            protocol_position: protocol_position_key,
+            tick_array_lower,
+            tick_array_upper,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::CollectRewards { reward_indexes })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn compound_position_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let (protocol_position_key, __bump) = Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_lower, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array_upper, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::CompoundPosition {
+            authority: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            pool_state: pool_account_key,
+            protocol_position: protocol_position_key,
+            tick_array_lower,
+            tick_array_upper,
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::CompoundPosition)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn rebalance_position_instr(
+    config: &ClientConfig,
+    amm_config_key: Pubkey,
+    pool_account_key: Pubkey,
+    observation_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    user_token_account_0: Pubkey,
+    user_token_account_1: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    tick_lower_index_before: i32,
+    tick_upper_index_before: i32,
+    tick_array_lower_start_index_before: i32,
+    tick_array_upper_start_index_before: i32,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    liquidity_min: u128,
+    swap_surplus: bool,
+    sqrt_price_limit_x64: Option<u128>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let tick_array_key = |tick_array_start_index: i32| {
+        Pubkey::find_program_address(
+            &[
+                TICK_ARRAY_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                &tick_array_start_index.to_be_bytes(),
+            ],
+            &program.id(),
+        )
+        .0
+    };
+    let protocol_position_key = |tick_lower_index: i32, tick_upper_index: i32| {
+        Pubkey::find_program_address(
+            &[
+                POSITION_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                &tick_lower_index.to_be_bytes(),
+                &tick_upper_index.to_be_bytes(),
+            ],
+            &program.id(),
+        )
+        .0
+    };
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::RebalancePosition {
+            nft_owner: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            amm_config: amm_config_key,
+            pool_state: pool_account_key,
+            observation_state: observation_key,
+            protocol_position: protocol_position_key(
+                tick_lower_index_before,
+                tick_upper_index_before,
+            ),
+            tick_array_lower: tick_array_key(tick_array_lower_start_index_before),
+            tick_array_upper: tick_array_key(tick_array_upper_start_index_before),
+            new_protocol_position: protocol_position_key(tick_lower_index, tick_upper_index),
+            new_tick_array_lower: tick_array_key(tick_array_lower_start_index),
+            new_tick_array_upper: tick_array_key(tick_array_upper_start_index),
+            token_vault_0,
+            token_vault_1,
+            recipient_token_account_0: user_token_account_0,
+            recipient_token_account_1: user_token_account_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: token_mint_0,
+            vault_1_mint: token_mint_1,
+            system_program: system_program::id(),
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::RebalancePosition {
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            liquidity_min,
+            swap_surplus,
+            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn lock_position_instr(
+    config: &ClientConfig,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    unlock_timestamp: u64,
+    lock_authority: Option<Pubkey>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::LockPosition {
+            authority: program.payer(),
+            nft_account: nft_token_key,
+            personal_position: personal_position_key,
+        })
+        .args(raydium_instruction::LockPosition {
+            unlock_timestamp,
+            lock_authority,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn close_personal_position_instr(
+    config: &ClientConfig,
+    nft_mint_key: Pubkey,
+    nft_token_key: Pubkey,
+    nft_token_program: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (personal_position_key, __bump) = Pubkey::find_program_address(
+        &[POSITION_SEED.as_bytes(), nft_mint_key.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::ClosePosition {
+            nft_owner: program.payer(),
+            position_nft_mint: nft_mint_key,
+            position_nft_account: nft_token_key,
+            personal_position: personal_position_key,
+            system_program: system_program::id(),
+            token_program: nft_token_program,
+        })
+        .args(raydium_instruction::ClosePosition)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn swap_instr(
+    config: &ClientConfig,
+    amm_config: Pubkey,
+    pool_account_key: Pubkey,
+    input_vault: Pubkey,
+    output_vault: Pubkey,
+    observation_state: Pubkey,
+    user_input_token: Pubkey,
+    user_out_put_token: Pubkey,
+    tick_array: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit_x64: Option<u128>,
+    is_base_input: bool,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::SwapSingle {
+            payer: program.payer(),
+            amm_config,
+            pool_state: pool_account_key,
+            input_token_account: user_input_token,
+            output_token_account: user_out_put_token,
+            input_vault,
+            output_vault,
+            tick_array,
+            observation_state,
+            token_program: spl_token::id(),
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::Swap {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
+            is_base_input,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn swap_v2_instr(
+    config: &ClientConfig,
+    amm_config: Pubkey,
+    pool_account_key: Pubkey,
+    input_vault: Pubkey,
+    output_vault: Pubkey,
+    observation_state: Pubkey,
+    user_input_token: Pubkey,
+    user_out_put_token: Pubkey,
+    input_vault_mint: Pubkey,
+    output_vault_mint: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit_x64: Option<u128>,
+    is_base_input: bool,
+    referral_token_account: Option<Pubkey>,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
+    deadline: Option<u64>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let request = program
+        .request()
+        .accounts(raydium_accounts::SwapSingleV2 {
+            payer: program.payer(),
+            amm_config,
+            pool_state: pool_account_key,
+            input_token_account: user_input_token,
+            output_token_account: user_out_put_token,
+            input_vault,
+            output_vault,
+            observation_state,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            memo_program: spl_memo::id(),
+            input_vault_mint,
+            output_vault_mint,
+            referral_token_account,
+        })
+        .accounts(remaining_accounts);
+    // the original instruction is sent if no deadline is given
+    let request = if deadline.is_some() {
+        request.args(raydium_instruction::SwapV2WithDeadline {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
+            is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+            deadline,
+        })
+    } else {
+        request.args(raydium_instruction::SwapV2 {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
+            is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+        })
+    };
+    let instructions = request.instructions()?;
+    Ok(instructions)
+}
+
+pub fn flash_swap_instr(
+    config: &ClientConfig,
+    amm_config: Pubkey,
+    pool_account_key: Pubkey,
+    receiver_token_account: Pubkey,
+    input_vault: Pubkey,
+    output_vault: Pubkey,
+    observation_state: Pubkey,
+    input_vault_mint: Pubkey,
+    output_vault_mint: Pubkey,
+    callback_program: Pubkey,
+    callback_accounts: Vec<AccountMeta>,
+    remaining_accounts: Vec<AccountMeta>,
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit_x64: Option<u128>,
+    is_base_input: bool,
+    callback_data: Vec<u8>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let callback_account_count = callback_accounts.len() as u8;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::FlashSwap {
+            payer: program.payer(),
+            amm_config,
+            pool_state: pool_account_key,
+            receiver_token_account,
+            input_vault,
+            output_vault,
+            observation_state,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            input_vault_mint,
+            output_vault_mint,
+            callback_program,
+        })
+        // the callback accounts lead the remaining accounts
+        .accounts(callback_accounts)
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::FlashSwap {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
+            is_base_input,
+            callback_account_count,
+            callback_data,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn flash_loan_instr(
+    config: &ClientConfig,
+    amm_config: Pubkey,
+    pool_account_key: Pubkey,
+    receiver_token_account_0: Pubkey,
+    receiver_token_account_1: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    vault_0_mint: Pubkey,
+    vault_1_mint: Pubkey,
+    callback_program: Pubkey,
+    callback_accounts: Vec<AccountMeta>,
+    remaining_accounts: Vec<AccountMeta>,
+    amount_0: u64,
+    amount_1: u64,
+    callback_data: Vec<u8>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
     // Client.
     let client = Client::new(url, Rc::new(payer));
     let program = client.program(config.raydium_v3_program)?;
+    let callback_account_count = callback_accounts.len() as u8;
     let instructions = program
         .request()
-        .accounts(raydium_accounts::SwapSingle {
+        .accounts(raydium_accounts::FlashLoan {
             payer: program.payer(),
             amm_config,
             pool_state: pool_account_key,
-            input_token_account: user_input_token,
-            output_token_account: user_out_put_token,
+            receiver_token_account_0,
+            receiver_token_account_1,
+            token_vault_0,
+            token_vault_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint,
+            vault_1_mint,
+            callback_program,
+        })
+        // the callback accounts lead the remaining accounts
+        .accounts(callback_accounts)
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::FlashLoan {
+            amount_0,
+            amount_1,
+            callback_account_count,
+            callback_data,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn place_limit_order_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    input_token_account: Pubkey,
+    input_vault: Pubkey,
+    input_vault_mint: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    order_nonce: u64,
+    tick_array_start_index: i32,
+    tick_lower_index: i32,
+    zero_for_one: bool,
+    amount: u64,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (limit_order_key, __bump) = Pubkey::find_program_address(
+        &[
+            LIMIT_ORDER_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            program.payer().to_bytes().as_ref(),
+            &order_nonce.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let (tick_array, __bump) = Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &tick_array_start_index.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::PlaceLimitOrder {
+            owner: program.payer(),
+            pool_state: pool_account_key,
+            limit_order: limit_order_key,
+            tick_array,
+            input_token_account,
             input_vault,
-            output_vault,
+            input_vault_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            system_program: system_program::id(),
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::PlaceLimitOrder {
+            order_nonce,
+            tick_array_start_index,
+            tick_lower_index,
+            zero_for_one,
+            amount,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn cancel_limit_order_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    limit_order_key: Pubkey,
+    tick_array: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    recipient_token_account_0: Pubkey,
+    recipient_token_account_1: Pubkey,
+    vault_0_mint: Pubkey,
+    vault_1_mint: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::CancelLimitOrder {
+            owner: program.payer(),
+            pool_state: pool_account_key,
+            limit_order: limit_order_key,
             tick_array,
-            observation_state,
+            token_vault_0,
+            token_vault_1,
+            recipient_token_account_0,
+            recipient_token_account_1,
             token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint,
+            vault_1_mint,
         })
         .accounts(remaining_accounts)
-        .args(raydium_instruction::Swap {
+        .args(raydium_instruction::CancelLimitOrder)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn claim_limit_order_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    limit_order_key: Pubkey,
+    tick_array: Pubkey,
+    output_vault: Pubkey,
+    recipient_token_account: Pubkey,
+    output_vault_mint: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::ClaimLimitOrder {
+            owner: program.payer(),
+            pool_state: pool_account_key,
+            limit_order: limit_order_key,
+            tick_array,
+            output_vault,
+            recipient_token_account,
+            output_vault_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        })
+        .accounts(remaining_accounts)
+        .args(raydium_instruction::ClaimLimitOrder)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn create_twamm_order_pool_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    zero_for_one: bool,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (order_pool_key, __bump) = Pubkey::find_program_address(
+        &[
+            TWAMM_ORDER_POOL_SEED.as_bytes(),
+            pool_account_key.to_bytes().as_ref(),
+            &[zero_for_one as u8],
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::CreateTwammOrderPool {
+            payer: program.payer(),
+            pool_state: pool_account_key,
+            order_pool: order_pool_key,
+            system_program: system_program::id(),
+        })
+        .args(raydium_instruction::CreateTwammOrderPool { zero_for_one })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn place_twamm_order_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    order_pool_key: Pubkey,
+    input_token_account: Pubkey,
+    input_vault: Pubkey,
+    input_vault_mint: Pubkey,
+    order_nonce: u64,
+    amount: u64,
+    intervals: u16,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let (order_key, __bump) = Pubkey::find_program_address(
+        &[
+            TWAMM_ORDER_SEED.as_bytes(),
+            order_pool_key.to_bytes().as_ref(),
+            program.payer().to_bytes().as_ref(),
+            &order_nonce.to_be_bytes(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::PlaceTwammOrder {
+            owner: program.payer(),
+            pool_state: pool_account_key,
+            order_pool: order_pool_key,
+            order: order_key,
+            input_token_account,
+            input_vault,
+            input_vault_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            system_program: system_program::id(),
+        })
+        .args(raydium_instruction::PlaceTwammOrder {
+            order_nonce,
             amount,
-            other_amount_threshold,
-            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
-            is_base_input,
+            intervals,
         })
         .instructions()?;
     Ok(instructions)
 }
 
-pub fn swap_v2_instr(
+pub fn withdraw_twamm_order_instr(
+    config: &ClientConfig,
+    pool_account_key: Pubkey,
+    order_pool_key: Pubkey,
+    order_key: Pubkey,
+    token_vault_0: Pubkey,
+    token_vault_1: Pubkey,
+    recipient_token_account_0: Pubkey,
+    recipient_token_account_1: Pubkey,
+    vault_0_mint: Pubkey,
+    vault_1_mint: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::WithdrawTwammOrder {
+            owner: program.payer(),
+            pool_state: pool_account_key,
+            order_pool: order_pool_key,
+            order: order_key,
+            token_vault_0,
+            token_vault_1,
+            recipient_token_account_0,
+            recipient_token_account_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint,
+            vault_1_mint,
+        })
+        .args(raydium_instruction::WithdrawTwammOrder)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn execute_twamm_instr(
     config: &ClientConfig,
     amm_config: Pubkey,
     pool_account_key: Pubkey,
+    order_pool_key: Pubkey,
     input_vault: Pubkey,
-    output_vault: Pubkey,
-    observation_state: Pubkey,
-    user_input_token: Pubkey,
-    user_out_put_token: Pubkey,
+    keeper_token_account: Pubkey,
     input_vault_mint: Pubkey,
-    output_vault_mint: Pubkey,
+    observation_state: Pubkey,
     remaining_accounts: Vec<AccountMeta>,
-    amount: u64,
-    other_amount_threshold: u64,
-    sqrt_price_limit_x64: Option<u128>,
-    is_base_input: bool,
 ) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.payer_path)?;
     let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
@@ -670,27 +1888,93 @@ pub fn swap_v2_instr(
     let program = client.program(config.raydium_v3_program)?;
     let instructions = program
         .request()
-        .accounts(raydium_accounts::SwapSingleV2 {
-            payer: program.payer(),
+        .accounts(raydium_accounts::ExecuteTwamm {
+            keeper: program.payer(),
             amm_config,
             pool_state: pool_account_key,
-            input_token_account: user_input_token,
-            output_token_account: user_out_put_token,
+            order_pool: order_pool_key,
             input_vault,
-            output_vault,
+            keeper_token_account,
+            input_vault_mint,
             observation_state,
             token_program: spl_token::id(),
             token_program_2022: spl_token_2022::id(),
-            memo_program: spl_memo::id(),
-            input_vault_mint,
-            output_vault_mint,
         })
         .accounts(remaining_accounts)
-        .args(raydium_instruction::SwapV2 {
-            amount,
-            other_amount_threshold,
-            sqrt_price_limit_x64: sqrt_price_limit_x64.unwrap_or(0u128),
-            is_base_input,
+        .args(raydium_instruction::ExecuteTwamm)
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn swap_router_base_in_v2_instr(
+    config: &ClientConfig,
+    input_token_account: Pubkey,
+    input_token_mint: Pubkey,
+    hop_accounts: Vec<AccountMeta>,
+    hops: Vec<RouteHop>,
+    amount_in: u64,
+    amount_out_minimum: u64,
+    referral_token_account: Option<Pubkey>,
+    referral_fee_bps: u16,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::SwapRouterBaseIn {
+            payer: program.payer(),
+            input_token_account,
+            input_token_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            memo_program: spl_memo::id(),
+            referral_token_account,
+        })
+        .accounts(hop_accounts)
+        .args(raydium_instruction::SwapRouterBaseInV2 {
+            amount_in,
+            amount_out_minimum,
+            referral_fee_bps,
+            hops,
+        })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn swap_router_base_out_instr(
+    config: &ClientConfig,
+    input_token_account: Pubkey,
+    input_token_mint: Pubkey,
+    hop_accounts: Vec<AccountMeta>,
+    amount_out: u64,
+    amount_in_maximum: u64,
+    referral_token_account: Option<Pubkey>,
+    referral_fee_bps: u16,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.raydium_v3_program)?;
+    let instructions = program
+        .request()
+        .accounts(raydium_accounts::SwapRouterBaseOut {
+            payer: program.payer(),
+            input_token_account,
+            input_token_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            memo_program: spl_memo::id(),
+            referral_token_account,
+        })
+        .accounts(hop_accounts)
+        .args(raydium_instruction::SwapRouterBaseOut {
+            amount_out,
+            amount_in_maximum,
+            referral_fee_bps,
         })
         .instructions()?;
     Ok(instructions)
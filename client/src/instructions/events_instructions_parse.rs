@@ -143,6 +143,12 @@ pub fn handle_program_log(
             ConfigChangeEvent::DISCRIMINATOR => {
                 println!("{:#?}", decode_event::<ConfigChangeEvent>(&mut slice)?);
             }
+            PoolFeeOverrideChangeEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<PoolFeeOverrideChangeEvent>(&mut slice)?
+                );
+            }
             CollectPersonalFeeEvent::DISCRIMINATOR => {
                 println!(
                     "{:#?}",
@@ -155,6 +161,42 @@ pub fn handle_program_log(
                     decode_event::<CollectProtocolFeeEvent>(&mut slice)?
                 );
             }
+            CollectCreatorFeeEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<CollectCreatorFeeEvent>(&mut slice)?);
+            }
+            CollectPersonalRewardEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<CollectPersonalRewardEvent>(&mut slice)?
+                );
+            }
+            CompoundPositionEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<CompoundPositionEvent>(&mut slice)?);
+            }
+            RebalancePositionEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<RebalancePositionEvent>(&mut slice)?);
+            }
+            IncreaseLiquidityZapEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<IncreaseLiquidityZapEvent>(&mut slice)?
+                );
+            }
+            DecreaseLiquidityZapOutEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<DecreaseLiquidityZapOutEvent>(&mut slice)?
+                );
+            }
+            PositionLockedEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PositionLockedEvent>(&mut slice)?);
+            }
+            CircuitBreakerTrippedEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<CircuitBreakerTrippedEvent>(&mut slice)?
+                );
+            }
             CreatePersonalPositionEvent::DISCRIMINATOR => {
                 println!(
                     "{:#?}",
@@ -179,9 +221,36 @@ pub fn handle_program_log(
             // PriceChangeEvent::DISCRIMINATOR => {
             //     println!("{:#?}", decode_event::<PriceChangeEvent>(&mut slice)?);
             // }
+            ReferralFeeEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<ReferralFeeEvent>(&mut slice)?);
+            }
             SwapEvent::DISCRIMINATOR => {
                 println!("{:#?}", decode_event::<SwapEvent>(&mut slice)?);
             }
+            FlashLoanEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<FlashLoanEvent>(&mut slice)?);
+            }
+            PlaceLimitOrderEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PlaceLimitOrderEvent>(&mut slice)?);
+            }
+            CancelLimitOrderEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<CancelLimitOrderEvent>(&mut slice)?);
+            }
+            ClaimLimitOrderEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<ClaimLimitOrderEvent>(&mut slice)?);
+            }
+            PlaceTwammOrderEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PlaceTwammOrderEvent>(&mut slice)?);
+            }
+            WithdrawTwammOrderEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<WithdrawTwammOrderEvent>(&mut slice)?
+                );
+            }
+            ExecuteTwammEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<ExecuteTwammEvent>(&mut slice)?);
+            }
             PoolCreatedEvent::DISCRIMINATOR => {
                 println!("{:#?}", decode_event::<PoolCreatedEvent>(&mut slice)?);
             }
@@ -359,6 +428,8 @@ pub fn handle_program_instruction(
                 pub index: u16,
                 pub tick_spacing: u16,
                 pub trade_fee_flat: u64,
+                pub trade_fee_rate: u32,
+                pub trade_fee_mode: u8,
                 pub protocol_fee_rate: u32,
                 pub fund_fee_rate: u32,
             }
@@ -368,6 +439,8 @@ pub fn handle_program_instruction(
                         index: instr.index,
                         tick_spacing: instr.tick_spacing,
                         trade_fee_flat: instr.trade_fee_flat,
+                        trade_fee_rate: instr.trade_fee_rate,
+                        trade_fee_mode: instr.trade_fee_mode,
                         protocol_fee_rate: instr.protocol_fee_rate,
                         fund_fee_rate: instr.fund_fee_rate,
                     }
@@ -398,12 +471,18 @@ pub fn handle_program_instruction(
             pub struct CreatePool {
                 pub sqrt_price_x64: u128,
                 pub open_time: u64,
+                pub launch_fee_flat: u64,
+                pub launch_fee_duration: u32,
+                pub launch_fee_decay_mode: u8,
             }
             impl From<instruction::CreatePool> for CreatePool {
                 fn from(instr: instruction::CreatePool) -> CreatePool {
                     CreatePool {
                         sqrt_price_x64: instr.sqrt_price_x64,
                         open_time: instr.open_time,
+                        launch_fee_flat: instr.launch_fee_flat,
+                        launch_fee_duration: instr.launch_fee_duration,
+                        launch_fee_decay_mode: instr.launch_fee_decay_mode,
                     }
                 }
             }
@@ -424,6 +503,99 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", UpdatePoolStatus::from(ix));
         }
+        instruction::UpdatePoolPriceGuard::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::UpdatePoolPriceGuard>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct UpdatePoolPriceGuard {
+                pub max_deviation_ticks: u32,
+                pub twap_window_seconds: u32,
+            }
+            impl From<instruction::UpdatePoolPriceGuard> for UpdatePoolPriceGuard {
+                fn from(instr: instruction::UpdatePoolPriceGuard) -> UpdatePoolPriceGuard {
+                    UpdatePoolPriceGuard {
+                        max_deviation_ticks: instr.max_deviation_ticks,
+                        twap_window_seconds: instr.twap_window_seconds,
+                    }
+                }
+            }
+            println!("{:#?}", UpdatePoolPriceGuard::from(ix));
+        }
+        instruction::UpdatePoolFlashLoanFee::DISCRIMINATOR => {
+            let ix =
+                decode_instruction::<instruction::UpdatePoolFlashLoanFee>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct UpdatePoolFlashLoanFee {
+                pub flash_loan_fee_flat: u64,
+                pub flash_loan_fee_rate: u32,
+                pub flash_loan_fee_mode: u8,
+            }
+            impl From<instruction::UpdatePoolFlashLoanFee> for UpdatePoolFlashLoanFee {
+                fn from(instr: instruction::UpdatePoolFlashLoanFee) -> UpdatePoolFlashLoanFee {
+                    UpdatePoolFlashLoanFee {
+                        flash_loan_fee_flat: instr.flash_loan_fee_flat,
+                        flash_loan_fee_rate: instr.flash_loan_fee_rate,
+                        flash_loan_fee_mode: instr.flash_loan_fee_mode,
+                    }
+                }
+            }
+            println!("{:#?}", UpdatePoolFlashLoanFee::from(ix));
+        }
+        instruction::UpdatePoolCircuitBreaker::DISCRIMINATOR => {
+            let ix =
+                decode_instruction::<instruction::UpdatePoolCircuitBreaker>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct UpdatePoolCircuitBreaker {
+                pub max_ticks: u32,
+                pub window_seconds: u32,
+                pub cooldown_seconds: u32,
+            }
+            impl From<instruction::UpdatePoolCircuitBreaker> for UpdatePoolCircuitBreaker {
+                fn from(instr: instruction::UpdatePoolCircuitBreaker) -> UpdatePoolCircuitBreaker {
+                    UpdatePoolCircuitBreaker {
+                        max_ticks: instr.max_ticks,
+                        window_seconds: instr.window_seconds,
+                        cooldown_seconds: instr.cooldown_seconds,
+                    }
+                }
+            }
+            println!("{:#?}", UpdatePoolCircuitBreaker::from(ix));
+        }
+        instruction::UpdatePoolHook::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::UpdatePoolHook>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct UpdatePoolHook {
+                pub hook_program: Pubkey,
+                pub hook_permissions: u8,
+            }
+            impl From<instruction::UpdatePoolHook> for UpdatePoolHook {
+                fn from(instr: instruction::UpdatePoolHook) -> UpdatePoolHook {
+                    UpdatePoolHook {
+                        hook_program: instr.hook_program,
+                        hook_permissions: instr.hook_permissions,
+                    }
+                }
+            }
+            println!("{:#?}", UpdatePoolHook::from(ix));
+        }
+        instruction::SetPoolFeeOverride::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::SetPoolFeeOverride>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct SetPoolFeeOverride {
+                pub trade_fee_flat: Option<u64>,
+                pub protocol_fee_rate: Option<u32>,
+                pub fund_fee_rate: Option<u32>,
+            }
+            impl From<instruction::SetPoolFeeOverride> for SetPoolFeeOverride {
+                fn from(instr: instruction::SetPoolFeeOverride) -> SetPoolFeeOverride {
+                    SetPoolFeeOverride {
+                        trade_fee_flat: instr.trade_fee_flat,
+                        protocol_fee_rate: instr.protocol_fee_rate,
+                        fund_fee_rate: instr.fund_fee_rate,
+                    }
+                }
+            }
+            println!("{:#?}", SetPoolFeeOverride::from(ix));
+        }
         instruction::CreateOperationAccount::DISCRIMINATOR => {
             let ix =
                 decode_instruction::<instruction::CreateOperationAccount>(&mut ix_data).unwrap();
@@ -564,6 +736,23 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", CollectFundFee::from(ix));
         }
+        instruction::CollectCreatorFee::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CollectCreatorFee>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CollectCreatorFee {
+                pub amount_0_requested: u64,
+                pub amount_1_requested: u64,
+            }
+            impl From<instruction::CollectCreatorFee> for CollectCreatorFee {
+                fn from(instr: instruction::CollectCreatorFee) -> CollectCreatorFee {
+                    CollectCreatorFee {
+                        amount_0_requested: instr.amount_0_requested,
+                        amount_1_requested: instr.amount_1_requested,
+                    }
+                }
+            }
+            println!("{:#?}", CollectCreatorFee::from(ix));
+        }
         instruction::OpenPosition::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::OpenPosition>(&mut ix_data).unwrap();
             #[derive(Debug)]
@@ -673,6 +862,33 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", IncreaseLiquidityV2::from(ix));
         }
+        instruction::IncreaseLiquidityV2WithDeadline::DISCRIMINATOR => {
+            let ix =
+                decode_instruction::<instruction::IncreaseLiquidityV2WithDeadline>(&mut ix_data)
+                    .unwrap();
+            #[derive(Debug)]
+            pub struct IncreaseLiquidityV2WithDeadline {
+                pub liquidity: u128,
+                pub amount_0_max: u64,
+                pub amount_1_max: u64,
+                pub base_flag: Option<bool>,
+                pub deadline: Option<u64>,
+            }
+            impl From<instruction::IncreaseLiquidityV2WithDeadline> for IncreaseLiquidityV2WithDeadline {
+                fn from(
+                    instr: instruction::IncreaseLiquidityV2WithDeadline,
+                ) -> IncreaseLiquidityV2WithDeadline {
+                    IncreaseLiquidityV2WithDeadline {
+                        liquidity: instr.liquidity,
+                        amount_0_max: instr.amount_0_max,
+                        amount_1_max: instr.amount_1_max,
+                        base_flag: instr.base_flag,
+                        deadline: instr.deadline,
+                    }
+                }
+            }
+            println!("{:#?}", IncreaseLiquidityV2WithDeadline::from(ix));
+        }
         instruction::DecreaseLiquidity::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::DecreaseLiquidity>(&mut ix_data).unwrap();
             #[derive(Debug)]
@@ -711,6 +927,184 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", DecreaseLiquidityV2::from(ix));
         }
+        instruction::DecreaseLiquidityV2WithDeadline::DISCRIMINATOR => {
+            let ix =
+                decode_instruction::<instruction::DecreaseLiquidityV2WithDeadline>(&mut ix_data)
+                    .unwrap();
+            #[derive(Debug)]
+            pub struct DecreaseLiquidityV2WithDeadline {
+                pub liquidity: u128,
+                pub amount_0_min: u64,
+                pub amount_1_min: u64,
+                pub deadline: Option<u64>,
+            }
+            impl From<instruction::DecreaseLiquidityV2WithDeadline> for DecreaseLiquidityV2WithDeadline {
+                fn from(
+                    instr: instruction::DecreaseLiquidityV2WithDeadline,
+                ) -> DecreaseLiquidityV2WithDeadline {
+                    DecreaseLiquidityV2WithDeadline {
+                        liquidity: instr.liquidity,
+                        amount_0_min: instr.amount_0_min,
+                        amount_1_min: instr.amount_1_min,
+                        deadline: instr.deadline,
+                    }
+                }
+            }
+            println!("{:#?}", DecreaseLiquidityV2WithDeadline::from(ix));
+        }
+        instruction::CollectFees::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CollectFees>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CollectFees {
+                pub amount_0_requested: u64,
+                pub amount_1_requested: u64,
+            }
+            impl From<instruction::CollectFees> for CollectFees {
+                fn from(instr: instruction::CollectFees) -> CollectFees {
+                    CollectFees {
+                        amount_0_requested: instr.amount_0_requested,
+                        amount_1_requested: instr.amount_1_requested,
+                    }
+                }
+            }
+            println!("{:#?}", CollectFees::from(ix));
+        }
+        instruction::CollectRewards::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CollectRewards>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CollectRewards {
+                pub reward_indexes: Vec<u8>,
+            }
+            impl From<instruction::CollectRewards> for CollectRewards {
+                fn from(instr: instruction::CollectRewards) -> CollectRewards {
+                    CollectRewards {
+                        reward_indexes: instr.reward_indexes,
+                    }
+                }
+            }
+            println!("{:#?}", CollectRewards::from(ix));
+        }
+        instruction::CompoundPosition::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CompoundPosition>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CompoundPosition;
+            impl From<instruction::CompoundPosition> for CompoundPosition {
+                fn from(_instr: instruction::CompoundPosition) -> CompoundPosition {
+                    CompoundPosition
+                }
+            }
+            println!("{:#?}", CompoundPosition::from(ix));
+        }
+        instruction::RebalancePosition::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::RebalancePosition>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct RebalancePosition {
+                pub tick_lower_index: i32,
+                pub tick_upper_index: i32,
+                pub tick_array_lower_start_index: i32,
+                pub tick_array_upper_start_index: i32,
+                pub liquidity_min: u128,
+                pub swap_surplus: bool,
+                pub sqrt_price_limit_x64: u128,
+            }
+            impl From<instruction::RebalancePosition> for RebalancePosition {
+                fn from(instr: instruction::RebalancePosition) -> RebalancePosition {
+                    RebalancePosition {
+                        tick_lower_index: instr.tick_lower_index,
+                        tick_upper_index: instr.tick_upper_index,
+                        tick_array_lower_start_index: instr.tick_array_lower_start_index,
+                        tick_array_upper_start_index: instr.tick_array_upper_start_index,
+                        liquidity_min: instr.liquidity_min,
+                        swap_surplus: instr.swap_surplus,
+                        sqrt_price_limit_x64: instr.sqrt_price_limit_x64,
+                    }
+                }
+            }
+            println!("{:#?}", RebalancePosition::from(ix));
+        }
+        instruction::IncreaseLiquidityZap::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::IncreaseLiquidityZap>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct IncreaseLiquidityZap {
+                pub amount_in: u64,
+                pub zero_for_one: bool,
+                pub liquidity_min: u128,
+            }
+            impl From<instruction::IncreaseLiquidityZap> for IncreaseLiquidityZap {
+                fn from(instr: instruction::IncreaseLiquidityZap) -> IncreaseLiquidityZap {
+                    IncreaseLiquidityZap {
+                        amount_in: instr.amount_in,
+                        zero_for_one: instr.zero_for_one,
+                        liquidity_min: instr.liquidity_min,
+                    }
+                }
+            }
+            println!("{:#?}", IncreaseLiquidityZap::from(ix));
+        }
+        instruction::OpenPositionZap::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::OpenPositionZap>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct OpenPositionZap {
+                pub tick_lower_index: i32,
+                pub tick_upper_index: i32,
+                pub tick_array_lower_start_index: i32,
+                pub tick_array_upper_start_index: i32,
+                pub amount_in: u64,
+                pub zero_for_one: bool,
+                pub liquidity_min: u128,
+                pub with_metadata: bool,
+            }
+            impl From<instruction::OpenPositionZap> for OpenPositionZap {
+                fn from(instr: instruction::OpenPositionZap) -> OpenPositionZap {
+                    OpenPositionZap {
+                        tick_lower_index: instr.tick_lower_index,
+                        tick_upper_index: instr.tick_upper_index,
+                        tick_array_lower_start_index: instr.tick_array_lower_start_index,
+                        tick_array_upper_start_index: instr.tick_array_upper_start_index,
+                        amount_in: instr.amount_in,
+                        zero_for_one: instr.zero_for_one,
+                        liquidity_min: instr.liquidity_min,
+                        with_metadata: instr.with_metadata,
+                    }
+                }
+            }
+            println!("{:#?}", OpenPositionZap::from(ix));
+        }
+        instruction::DecreaseLiquidityZapOut::DISCRIMINATOR => {
+            let ix =
+                decode_instruction::<instruction::DecreaseLiquidityZapOut>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct DecreaseLiquidityZapOut {
+                pub liquidity: u128,
+                pub amount_out_min: u64,
+            }
+            impl From<instruction::DecreaseLiquidityZapOut> for DecreaseLiquidityZapOut {
+                fn from(instr: instruction::DecreaseLiquidityZapOut) -> DecreaseLiquidityZapOut {
+                    DecreaseLiquidityZapOut {
+                        liquidity: instr.liquidity,
+                        amount_out_min: instr.amount_out_min,
+                    }
+                }
+            }
+            println!("{:#?}", DecreaseLiquidityZapOut::from(ix));
+        }
+        instruction::LockPosition::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::LockPosition>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct LockPosition {
+                pub unlock_timestamp: u64,
+                pub lock_authority: Option<Pubkey>,
+            }
+            impl From<instruction::LockPosition> for LockPosition {
+                fn from(instr: instruction::LockPosition) -> LockPosition {
+                    LockPosition {
+                        unlock_timestamp: instr.unlock_timestamp,
+                        lock_authority: instr.lock_authority,
+                    }
+                }
+            }
+            println!("{:#?}", LockPosition::from(ix));
+        }
         instruction::Swap::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::Swap>(&mut ix_data).unwrap();
             #[derive(Debug)]
@@ -740,6 +1134,9 @@ pub fn handle_program_instruction(
                 pub other_amount_threshold: u64,
                 pub sqrt_price_limit_x64: u128,
                 pub is_base_input: bool,
+                pub referral_fee_bps: u16,
+                pub max_deviation_ticks: Option<u32>,
+                pub allow_partial_fill: bool,
             }
             impl From<instruction::SwapV2> for SwapV2 {
                 fn from(instr: instruction::SwapV2) -> SwapV2 {
@@ -748,28 +1145,300 @@ pub fn handle_program_instruction(
                         other_amount_threshold: instr.other_amount_threshold,
                         sqrt_price_limit_x64: instr.sqrt_price_limit_x64,
                         is_base_input: instr.is_base_input,
+                        referral_fee_bps: instr.referral_fee_bps,
+                        max_deviation_ticks: instr.max_deviation_ticks,
+                        allow_partial_fill: instr.allow_partial_fill,
                     }
                 }
             }
             println!("{:#?}", SwapV2::from(ix));
         }
+        instruction::SwapV2WithDeadline::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::SwapV2WithDeadline>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct SwapV2WithDeadline {
+                pub amount: u64,
+                pub other_amount_threshold: u64,
+                pub sqrt_price_limit_x64: u128,
+                pub is_base_input: bool,
+                pub referral_fee_bps: u16,
+                pub max_deviation_ticks: Option<u32>,
+                pub allow_partial_fill: bool,
+                pub deadline: Option<u64>,
+            }
+            impl From<instruction::SwapV2WithDeadline> for SwapV2WithDeadline {
+                fn from(instr: instruction::SwapV2WithDeadline) -> SwapV2WithDeadline {
+                    SwapV2WithDeadline {
+                        amount: instr.amount,
+                        other_amount_threshold: instr.other_amount_threshold,
+                        sqrt_price_limit_x64: instr.sqrt_price_limit_x64,
+                        is_base_input: instr.is_base_input,
+                        referral_fee_bps: instr.referral_fee_bps,
+                        max_deviation_ticks: instr.max_deviation_ticks,
+                        allow_partial_fill: instr.allow_partial_fill,
+                        deadline: instr.deadline,
+                    }
+                }
+            }
+            println!("{:#?}", SwapV2WithDeadline::from(ix));
+        }
+        instruction::QuoteSwap::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::QuoteSwap>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct QuoteSwap {
+                pub amount: u64,
+                pub sqrt_price_limit_x64: u128,
+                pub zero_for_one: bool,
+                pub is_base_input: bool,
+                pub referral_fee_bps: u16,
+                pub max_deviation_ticks: Option<u32>,
+                pub allow_partial_fill: bool,
+            }
+            impl From<instruction::QuoteSwap> for QuoteSwap {
+                fn from(instr: instruction::QuoteSwap) -> QuoteSwap {
+                    QuoteSwap {
+                        amount: instr.amount,
+                        sqrt_price_limit_x64: instr.sqrt_price_limit_x64,
+                        zero_for_one: instr.zero_for_one,
+                        is_base_input: instr.is_base_input,
+                        referral_fee_bps: instr.referral_fee_bps,
+                        max_deviation_ticks: instr.max_deviation_ticks,
+                        allow_partial_fill: instr.allow_partial_fill,
+                    }
+                }
+            }
+            println!("{:#?}", QuoteSwap::from(ix));
+        }
+        instruction::FlashSwap::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::FlashSwap>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct FlashSwap {
+                pub amount: u64,
+                pub other_amount_threshold: u64,
+                pub sqrt_price_limit_x64: u128,
+                pub is_base_input: bool,
+                pub callback_account_count: u8,
+                pub callback_data: String,
+            }
+            impl From<instruction::FlashSwap> for FlashSwap {
+                fn from(instr: instruction::FlashSwap) -> FlashSwap {
+                    FlashSwap {
+                        amount: instr.amount,
+                        other_amount_threshold: instr.other_amount_threshold,
+                        sqrt_price_limit_x64: instr.sqrt_price_limit_x64,
+                        is_base_input: instr.is_base_input,
+                        callback_account_count: instr.callback_account_count,
+                        callback_data: hex::encode(instr.callback_data),
+                    }
+                }
+            }
+            println!("{:#?}", FlashSwap::from(ix));
+        }
+        instruction::FlashLoan::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::FlashLoan>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct FlashLoan {
+                pub amount_0: u64,
+                pub amount_1: u64,
+                pub callback_account_count: u8,
+                pub callback_data: String,
+            }
+            impl From<instruction::FlashLoan> for FlashLoan {
+                fn from(instr: instruction::FlashLoan) -> FlashLoan {
+                    FlashLoan {
+                        amount_0: instr.amount_0,
+                        amount_1: instr.amount_1,
+                        callback_account_count: instr.callback_account_count,
+                        callback_data: hex::encode(instr.callback_data),
+                    }
+                }
+            }
+            println!("{:#?}", FlashLoan::from(ix));
+        }
+        instruction::PlaceLimitOrder::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::PlaceLimitOrder>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct PlaceLimitOrder {
+                pub order_nonce: u64,
+                pub tick_array_start_index: i32,
+                pub tick_lower_index: i32,
+                pub zero_for_one: bool,
+                pub amount: u64,
+            }
+            impl From<instruction::PlaceLimitOrder> for PlaceLimitOrder {
+                fn from(instr: instruction::PlaceLimitOrder) -> PlaceLimitOrder {
+                    PlaceLimitOrder {
+                        order_nonce: instr.order_nonce,
+                        tick_array_start_index: instr.tick_array_start_index,
+                        tick_lower_index: instr.tick_lower_index,
+                        zero_for_one: instr.zero_for_one,
+                        amount: instr.amount,
+                    }
+                }
+            }
+            println!("{:#?}", PlaceLimitOrder::from(ix));
+        }
+        instruction::CancelLimitOrder::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CancelLimitOrder>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CancelLimitOrder;
+            impl From<instruction::CancelLimitOrder> for CancelLimitOrder {
+                fn from(_instr: instruction::CancelLimitOrder) -> CancelLimitOrder {
+                    CancelLimitOrder
+                }
+            }
+            println!("{:#?}", CancelLimitOrder::from(ix));
+        }
+        instruction::ClaimLimitOrder::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::ClaimLimitOrder>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct ClaimLimitOrder;
+            impl From<instruction::ClaimLimitOrder> for ClaimLimitOrder {
+                fn from(_instr: instruction::ClaimLimitOrder) -> ClaimLimitOrder {
+                    ClaimLimitOrder
+                }
+            }
+            println!("{:#?}", ClaimLimitOrder::from(ix));
+        }
+        instruction::CreateTwammOrderPool::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CreateTwammOrderPool>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CreateTwammOrderPool {
+                pub zero_for_one: bool,
+            }
+            impl From<instruction::CreateTwammOrderPool> for CreateTwammOrderPool {
+                fn from(instr: instruction::CreateTwammOrderPool) -> CreateTwammOrderPool {
+                    CreateTwammOrderPool {
+                        zero_for_one: instr.zero_for_one,
+                    }
+                }
+            }
+            println!("{:#?}", CreateTwammOrderPool::from(ix));
+        }
+        instruction::PlaceTwammOrder::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::PlaceTwammOrder>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct PlaceTwammOrder {
+                pub order_nonce: u64,
+                pub amount: u64,
+                pub intervals: u16,
+            }
+            impl From<instruction::PlaceTwammOrder> for PlaceTwammOrder {
+                fn from(instr: instruction::PlaceTwammOrder) -> PlaceTwammOrder {
+                    PlaceTwammOrder {
+                        order_nonce: instr.order_nonce,
+                        amount: instr.amount,
+                        intervals: instr.intervals,
+                    }
+                }
+            }
+            println!("{:#?}", PlaceTwammOrder::from(ix));
+        }
+        instruction::WithdrawTwammOrder::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::WithdrawTwammOrder>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct WithdrawTwammOrder;
+            impl From<instruction::WithdrawTwammOrder> for WithdrawTwammOrder {
+                fn from(_instr: instruction::WithdrawTwammOrder) -> WithdrawTwammOrder {
+                    WithdrawTwammOrder
+                }
+            }
+            println!("{:#?}", WithdrawTwammOrder::from(ix));
+        }
+        instruction::ExecuteTwamm::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::ExecuteTwamm>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct ExecuteTwamm;
+            impl From<instruction::ExecuteTwamm> for ExecuteTwamm {
+                fn from(_instr: instruction::ExecuteTwamm) -> ExecuteTwamm {
+                    ExecuteTwamm
+                }
+            }
+            println!("{:#?}", ExecuteTwamm::from(ix));
+        }
         instruction::SwapRouterBaseIn::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::SwapRouterBaseIn>(&mut ix_data).unwrap();
             #[derive(Debug)]
             pub struct SwapRouterBaseIn {
                 pub amount_in: u64,
                 pub amount_out_minimum: u64,
+                pub referral_fee_bps: u16,
             }
             impl From<instruction::SwapRouterBaseIn> for SwapRouterBaseIn {
                 fn from(instr: instruction::SwapRouterBaseIn) -> SwapRouterBaseIn {
                     SwapRouterBaseIn {
                         amount_in: instr.amount_in,
                         amount_out_minimum: instr.amount_out_minimum,
+                        referral_fee_bps: instr.referral_fee_bps,
                     }
                 }
             }
             println!("{:#?}", SwapRouterBaseIn::from(ix));
         }
+        instruction::SwapRouterBaseInWithDeadline::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::SwapRouterBaseInWithDeadline>(&mut ix_data)
+                .unwrap();
+            #[derive(Debug)]
+            pub struct SwapRouterBaseInWithDeadline {
+                pub amount_in: u64,
+                pub amount_out_minimum: u64,
+                pub referral_fee_bps: u16,
+                pub deadline: Option<u64>,
+            }
+            impl From<instruction::SwapRouterBaseInWithDeadline> for SwapRouterBaseInWithDeadline {
+                fn from(
+                    instr: instruction::SwapRouterBaseInWithDeadline,
+                ) -> SwapRouterBaseInWithDeadline {
+                    SwapRouterBaseInWithDeadline {
+                        amount_in: instr.amount_in,
+                        amount_out_minimum: instr.amount_out_minimum,
+                        referral_fee_bps: instr.referral_fee_bps,
+                        deadline: instr.deadline,
+                    }
+                }
+            }
+            println!("{:#?}", SwapRouterBaseInWithDeadline::from(ix));
+        }
+        instruction::SwapRouterBaseInV2::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::SwapRouterBaseInV2>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct SwapRouterBaseInV2 {
+                pub amount_in: u64,
+                pub amount_out_minimum: u64,
+                pub referral_fee_bps: u16,
+                pub hops: Vec<raydium_amm_v3::instructions::RouteHop>,
+            }
+            impl From<instruction::SwapRouterBaseInV2> for SwapRouterBaseInV2 {
+                fn from(instr: instruction::SwapRouterBaseInV2) -> SwapRouterBaseInV2 {
+                    SwapRouterBaseInV2 {
+                        amount_in: instr.amount_in,
+                        amount_out_minimum: instr.amount_out_minimum,
+                        referral_fee_bps: instr.referral_fee_bps,
+                        hops: instr.hops,
+                    }
+                }
+            }
+            println!("{:#?}", SwapRouterBaseInV2::from(ix));
+        }
+        instruction::SwapRouterBaseOut::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::SwapRouterBaseOut>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct SwapRouterBaseOut {
+                pub amount_out: u64,
+                pub amount_in_maximum: u64,
+                pub referral_fee_bps: u16,
+            }
+            impl From<instruction::SwapRouterBaseOut> for SwapRouterBaseOut {
+                fn from(instr: instruction::SwapRouterBaseOut) -> SwapRouterBaseOut {
+                    SwapRouterBaseOut {
+                        amount_out: instr.amount_out,
+                        amount_in_maximum: instr.amount_in_maximum,
+                        referral_fee_bps: instr.referral_fee_bps,
+                    }
+                }
+            }
+            println!("{:#?}", SwapRouterBaseOut::from(ix));
+        }
         _ => {
             println!("unknow instruction: {}", instr_data);
         }
@@ -1,11 +1,11 @@
 use anchor_lang::AccountDeserialize;
-use anyhow::Result;
+use anyhow::{format_err, Result};
 use raydium_amm_v3::libraries::fixed_point_64;
 use raydium_amm_v3::libraries::*;
 use raydium_amm_v3::states::*;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::program_pack::Pack;
-use solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_sdk::{account::Account, instruction::AccountMeta, pubkey::Pubkey};
 use spl_token_2022::{
     extension::{
         confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
@@ -23,7 +23,8 @@ use spl_token_2022::{
     state::Mint,
 };
 use std::collections::VecDeque;
-use std::ops::{DerefMut, Mul, Neg};
+use std::ops::{Mul, Neg};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn deserialize_anchor_account<T: AccountDeserialize>(account: &Account) -> Result<T> {
     let mut data: &[u8] = &account.data;
@@ -54,6 +55,17 @@ pub struct TransferFeeInfo {
     pub transfer_fee: u64,
 }
 
+/// Returns the unix timestamp `deadline_secs` seconds from now
+pub fn deadline_from_secs(deadline_secs: Option<u64>) -> Option<u64> {
+    deadline_secs.map(|deadline_secs| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + deadline_secs
+    })
+}
+
 pub fn amount_with_slippage(amount: u64, slippage: f64, round_up: bool) -> u64 {
     if round_up {
         (amount as f64).mul(1_f64 + slippage).ceil() as u64
@@ -308,6 +320,92 @@ struct StepComputations {
     fee_amount: u64,
 }
 
+/// Builds the router accounts of one hop from the pool snapshots,
+/// returns the hop accounts, the swap direction and the output mint of the hop
+pub fn get_route_hop_accounts(
+    raydium_v3_program: &Pubkey,
+    pool_id: Pubkey,
+    pool_state: &PoolState,
+    tickarray_bitmap_extension: &TickArrayBitmapExtension,
+    input_mint: Pubkey,
+    output_token: Pubkey,
+) -> Result<(Vec<AccountMeta>, bool, Pubkey)> {
+    let zero_for_one = input_mint == pool_state.token_mint_0;
+    if !zero_for_one && input_mint != pool_state.token_mint_1 {
+        return Err(format_err!(
+            "pool {} does not trade mint {}",
+            pool_id,
+            input_mint
+        ));
+    }
+    let (input_vault, output_vault, output_mint) = if zero_for_one {
+        (
+            pool_state.token_vault_0,
+            pool_state.token_vault_1,
+            pool_state.token_mint_1,
+        )
+    } else {
+        (
+            pool_state.token_vault_1,
+            pool_state.token_vault_0,
+            pool_state.token_mint_0,
+        )
+    };
+    let mut accounts = vec![
+        AccountMeta::new_readonly(pool_state.amm_config, false),
+        AccountMeta::new(pool_id, false),
+        AccountMeta::new(output_token, false),
+        AccountMeta::new(input_vault, false),
+        AccountMeta::new(output_vault, false),
+        AccountMeta::new_readonly(output_mint, false),
+        AccountMeta::new(pool_state.observation_key, false),
+        AccountMeta::new_readonly(TickArrayBitmapExtension::key(pool_id), false),
+    ];
+    if pool_state.is_fee_overridden() {
+        accounts.push(AccountMeta::new_readonly(
+            PoolFeeOverride::key(pool_id),
+            false,
+        ));
+    }
+    // the current and next five initialized tick arrays in the swap direction
+    let (_, mut tick_array_start_index) = pool_state
+        .get_first_initialized_tick_array(&Some(*tickarray_bitmap_extension), zero_for_one)
+        .map_err(|e| format_err!("{}", e))?;
+    let mut max_array_size = 6;
+    loop {
+        accounts.push(AccountMeta::new(
+            Pubkey::find_program_address(
+                &[
+                    TICK_ARRAY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    &tick_array_start_index.to_be_bytes(),
+                ],
+                raydium_v3_program,
+            )
+            .0,
+            false,
+        ));
+        max_array_size -= 1;
+        if max_array_size == 0 {
+            break;
+        }
+        match pool_state
+            .next_initialized_tick_array_start_index(
+                &Some(*tickarray_bitmap_extension),
+                tick_array_start_index,
+                zero_for_one,
+            )
+            .map_err(|e| format_err!("{}", e))?
+        {
+            Some(next_tick_array_start_index) => {
+                tick_array_start_index = next_tick_array_start_index
+            }
+            None => break,
+        }
+    }
+    Ok((accounts, zero_for_one, output_mint))
+}
+
 pub fn get_out_put_amount_and_remaining_accounts(
     input_amount: u64,
     sqrt_price_limit_x64: Option<u128>,
@@ -315,6 +413,8 @@ pub fn get_out_put_amount_and_remaining_accounts(
     is_base_input: bool,
     pool_config: &AmmConfig,
     pool_state: &PoolState,
+    observation_state: &ObservationState,
+    block_timestamp: u32,
     tickarray_bitmap_extension: &TickArrayBitmapExtension,
     tick_arrays: &mut VecDeque<TickArrayState>,
 ) -> Result<(u64, VecDeque<i32>), &'static str> {
@@ -326,7 +426,10 @@ pub fn get_out_put_amount_and_remaining_accounts(
         zero_for_one,
         is_base_input,
         is_pool_current_tick_array,
-        pool_config.trade_fee_flat,
+        pool_state.get_trade_fee_flat(pool_config.trade_fee_flat, block_timestamp.into()),
+        pool_config.get_trade_fee_rate(observation_state, pool_state.tick_current, block_timestamp),
+        pool_config.get_trade_fee_mode().unwrap(),
+        pool_config.get_fee_accounting_mode().unwrap(),
         input_amount,
         current_vaild_tick_array_start_index,
         sqrt_price_limit_x64.unwrap_or(0),
@@ -339,11 +442,54 @@ pub fn get_out_put_amount_and_remaining_accounts(
     Ok((amount_calculated, tick_array_start_index_vec))
 }
 
+/// Splits a single token deposit into a position the same way as the on-chain zap.
+/// Returns the amount swapped, the amount received from the swap and the tick arrays crossed
+pub fn get_zap_swap_amount_and_remaining_accounts(
+    amount_in: u64,
+    zero_for_one: bool,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    pool_config: &AmmConfig,
+    pool_state: &PoolState,
+    observation_state: &ObservationState,
+    block_timestamp: u32,
+    tickarray_bitmap_extension: &TickArrayBitmapExtension,
+    tick_arrays: &mut VecDeque<TickArrayState>,
+) -> Result<(u64, u64, VecDeque<i32>), &'static str> {
+    let swap_amount = liquidity_math::get_zap_swap_amount(
+        pool_state.sqrt_price_x64,
+        tick_math::get_sqrt_price_at_tick(tick_lower_index).unwrap(),
+        tick_math::get_sqrt_price_at_tick(tick_upper_index).unwrap(),
+        amount_in,
+        zero_for_one,
+        pool_config.get_trade_fee_rate(observation_state, pool_state.tick_current, block_timestamp),
+    );
+    if swap_amount == 0 {
+        return Ok((0, 0, VecDeque::new()));
+    }
+    let (swap_amount_out, tick_array_start_index_vec) = get_out_put_amount_and_remaining_accounts(
+        swap_amount,
+        None,
+        zero_for_one,
+        true,
+        pool_config,
+        pool_state,
+        observation_state,
+        block_timestamp,
+        tickarray_bitmap_extension,
+        tick_arrays,
+    )?;
+    Ok((swap_amount, swap_amount_out, tick_array_start_index_vec))
+}
+
 fn swap_compute(
     zero_for_one: bool,
     is_base_input: bool,
     is_pool_current_tick_array: bool,
     flat_fee: u64,
+    fee_rate: u32,
+    fee_mode: TradeFeeMode,
+    fee_accounting_mode: FeeAccountingMode,
     amount_specified: u64,
     current_vaild_tick_array_start_index: i32,
     sqrt_price_limit_x64: u128,
@@ -387,6 +533,18 @@ fn swap_compute(
         tick: pool_state.tick_current,
         liquidity: pool_state.liquidity,
     };
+    // the flat fee is charged only once at swap entry in per swap accounting mode
+    let step_flat_fee = match fee_accounting_mode {
+        FeeAccountingMode::PerStep => flat_fee,
+        FeeAccountingMode::PerSwap => {
+            if is_base_input {
+                state.amount_specified_remaining -= flat_fee.min(amount_specified);
+            } else {
+                state.amount_calculated = flat_fee;
+            }
+            0
+        }
+    };
 
     let mut tick_array_current = tick_arrays.pop_front().unwrap();
     if tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
@@ -441,11 +599,11 @@ fn swap_compute(
                 return Result::Err("tick array start tick index does not match");
             }
             tick_array_start_index_vec.push_back(tick_array_current.start_tick_index);
-            let mut first_initialized_tick = tick_array_current
+            let first_initialized_tick = tick_array_current
                 .first_initialized_tick(zero_for_one)
                 .unwrap();
 
-            next_initialized_tick = Box::new(*first_initialized_tick.deref_mut());
+            next_initialized_tick = Box::new(*first_initialized_tick);
         }
         step.tick_next = next_initialized_tick.tick;
         step.initialized = next_initialized_tick.is_initialized();
@@ -469,7 +627,9 @@ fn swap_compute(
             target_price,
             state.liquidity,
             state.amount_specified_remaining,
-            flat_fee,
+            step_flat_fee,
+            fee_rate,
+            fee_mode,
             is_base_input,
             zero_for_one,
             1,
@@ -18,6 +18,7 @@ use solana_client::{
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
     message::Message,
     program_pack::Pack,
     pubkey::Pubkey,
@@ -255,6 +256,79 @@ fn load_cur_and_next_five_tick_array(
     tick_arrays
 }
 
+fn load_execute_twamm_instr(
+    rpc_client: &RpcClient,
+    pool_config: &ClientConfig,
+    pool_state: &PoolState,
+    tickarray_bitmap_extension: &TickArrayBitmapExtension,
+    zero_for_one: bool,
+) -> Result<Vec<Instruction>> {
+    let pool_id = pool_config.pool_id_account.unwrap();
+    let (order_pool, __bump) = Pubkey::find_program_address(
+        &[
+            raydium_amm_v3::states::TWAMM_ORDER_POOL_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &[zero_for_one as u8],
+        ],
+        &pool_config.raydium_v3_program,
+    );
+    let (input_vault, input_vault_mint) = if zero_for_one {
+        (pool_state.token_vault_0, pool_state.token_mint_0)
+    } else {
+        (pool_state.token_vault_1, pool_state.token_mint_1)
+    };
+    let input_token_program = rpc_client.get_account(&input_vault_mint)?.owner;
+    let payer = read_keypair_file(&pool_config.payer_path)?;
+    // the orders swap through the tick arrays in the sold direction
+    let tick_arrays = load_cur_and_next_five_tick_array(
+        rpc_client,
+        pool_config,
+        pool_state,
+        tickarray_bitmap_extension,
+        zero_for_one,
+    );
+    let mut remaining_accounts = Vec::new();
+    remaining_accounts.push(AccountMeta::new_readonly(
+        pool_config.tickarray_bitmap_extension.unwrap(),
+        false,
+    ));
+    if pool_state.is_fee_overridden() {
+        remaining_accounts.push(AccountMeta::new_readonly(
+            raydium_amm_v3::states::PoolFeeOverride::key(pool_id),
+            false,
+        ));
+    }
+    for tick_array in tick_arrays {
+        remaining_accounts.push(AccountMeta::new(
+            Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    &tick_array.start_tick_index.to_be_bytes(),
+                ],
+                &pool_config.raydium_v3_program,
+            )
+            .0,
+            false,
+        ));
+    }
+    execute_twamm_instr(
+        pool_config,
+        pool_state.amm_config,
+        pool_id,
+        order_pool,
+        input_vault,
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &payer.pubkey(),
+            &input_vault_mint,
+            &input_token_program,
+        ),
+        input_vault_mint,
+        pool_state.observation_key,
+        remaining_accounts,
+    )
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct PositionNftTokenInfo {
     key: Pubkey,
@@ -392,6 +466,8 @@ pub enum CommandsName {
         config_index: u16,
         tick_spacing: u16,
         trade_fee_flat: u64,
+        trade_fee_rate: u32,
+        trade_fee_mode: u8,
         protocol_fee_rate: u32,
         fund_fee_rate: u32,
     },
@@ -400,6 +476,56 @@ pub enum CommandsName {
         param: u8,
         value: u64,
         remaining: Option<Pubkey>,
+        /// The pools of the config whose fees are overridden, checked when a fee rate is updated
+        #[arg(long)]
+        fee_override_pools: Vec<Pubkey>,
+    },
+    SetPoolFeeOverride {
+        pool_id: Option<Pubkey>,
+        #[arg(long)]
+        trade_fee_flat: Option<u64>,
+        #[arg(long)]
+        protocol_fee_rate: Option<u32>,
+        #[arg(long)]
+        fund_fee_rate: Option<u32>,
+    },
+    UpdatePoolPriceGuard {
+        pool_id: Option<Pubkey>,
+        // 0 disables the guard
+        #[arg(long, default_value_t = 0)]
+        max_deviation_ticks: u32,
+        // 0 uses the default window
+        #[arg(long, default_value_t = 0)]
+        twap_window_seconds: u32,
+    },
+    UpdatePoolFlashLoanFee {
+        pool_id: Option<Pubkey>,
+        #[arg(long, default_value_t = 0)]
+        flash_loan_fee_flat: u64,
+        #[arg(long, default_value_t = 0)]
+        flash_loan_fee_rate: u32,
+        // 0: flat only, 1: max, 2: sum
+        #[arg(long, default_value_t = 0)]
+        flash_loan_fee_mode: u8,
+    },
+    UpdatePoolCircuitBreaker {
+        pool_id: Option<Pubkey>,
+        // 0 disables the breaker
+        #[arg(long, default_value_t = 0)]
+        max_ticks: u32,
+        #[arg(long, default_value_t = 0)]
+        window_seconds: u32,
+        #[arg(long, default_value_t = 0)]
+        cooldown_seconds: u32,
+    },
+    UpdatePoolHook {
+        pool_id: Option<Pubkey>,
+        // the program called before and after swaps and liquidity changes
+        #[arg(long)]
+        hook_program: Option<Pubkey>,
+        // before swap(bit0), after swap(bit1), before liquidity changes(bit2), after liquidity changes(bit3)
+        #[arg(long, default_value_t = 0)]
+        hook_permissions: u8,
     },
     CreateOperation,
     UpdateOperation {
@@ -413,6 +539,12 @@ pub enum CommandsName {
         mint1: Pubkey,
         #[arg(short, long, default_value_t = 0)]
         open_time: u64,
+        #[arg(long, default_value_t = 0)]
+        launch_fee_flat: u64,
+        #[arg(long, default_value_t = 0)]
+        launch_fee_duration: u32,
+        #[arg(long, default_value_t = 0)]
+        launch_fee_decay_mode: u8,
     },
     InitReward {
         open_time: u64,
@@ -449,6 +581,25 @@ pub enum CommandsName {
         #[arg(short, long)]
         is_base_0: bool,
         imput_amount: u64,
+        #[arg(long)]
+        deadline_secs: Option<u64>,
+    },
+    IncreaseLiquidityZap {
+        nft_mint: Pubkey,
+        // deposits token_0 if set, token_1 otherwise
+        #[arg(short, long)]
+        is_base_0: bool,
+        amount_in: u64,
+    },
+    OpenPositionZap {
+        tick_lower_price: f64,
+        tick_upper_price: f64,
+        // deposits token_0 if set, token_1 otherwise
+        #[arg(short, long)]
+        is_base_0: bool,
+        amount_in: u64,
+        #[arg(short, long)]
+        with_metadata: bool,
     },
     DecreaseLiquidity {
         tick_lower_index: i32,
@@ -456,6 +607,57 @@ pub enum CommandsName {
         liquidity: Option<u128>,
         #[arg(short, long)]
         simulate: bool,
+        #[arg(long)]
+        deadline_secs: Option<u64>,
+    },
+    DecreaseLiquidityZapOut {
+        nft_mint: Pubkey,
+        // withdraws all the liquidity if not set
+        liquidity: Option<u128>,
+        // receives token_0 if set, token_1 otherwise
+        #[arg(short, long)]
+        is_base_0: bool,
+    },
+    CollectFees {
+        nft_mint: Pubkey,
+        // collects all the owed fees if not set
+        #[arg(long)]
+        amount_0: Option<u64>,
+        #[arg(long)]
+        amount_1: Option<u64>,
+    },
+    CollectRewards {
+        nft_mint: Pubkey,
+        #[arg(long, required = true)]
+        reward_index: Vec<u8>,
+    },
+    LockPosition {
+        nft_mint: Pubkey,
+        unlock_timestamp: u64,
+        // the account allowed to release the lock early
+        #[arg(long)]
+        lock_authority: Option<Pubkey>,
+        // the nft account of the position, the payer must own it or be the lock authority
+        #[arg(long)]
+        nft_account: Option<Pubkey>,
+    },
+    CompoundPosition {
+        nft_mint: Pubkey,
+        // the nft account of the position, the payer must own it or be its delegate
+        #[arg(long)]
+        nft_account: Option<Pubkey>,
+    },
+    RebalancePosition {
+        nft_mint: Pubkey,
+        tick_lower_price: f64,
+        tick_upper_price: f64,
+        // swaps the surplus of the withdrawn tokens over the token ratio of the new range before the deposit
+        #[arg(long)]
+        swap_surplus: bool,
+        #[arg(long)]
+        limit_price: Option<f64>,
+        #[arg(long, default_value_t = 0)]
+        liquidity_min: u128,
     },
     Swap {
         input_token: Pubkey,
@@ -476,6 +678,136 @@ pub enum CommandsName {
         simulate: bool,
         amount: u64,
         limit_price: Option<f64>,
+        #[arg(long)]
+        referral_token: Option<Pubkey>,
+        #[arg(long, default_value_t = 0)]
+        referral_fee_bps: u16,
+        // tightens the oracle price guard of the pool for this swap
+        #[arg(long)]
+        max_deviation_ticks: Option<u32>,
+        // stop at the limit price or where the liquidity runs out instead of failing
+        #[arg(long)]
+        allow_partial_fill: bool,
+        #[arg(long)]
+        deadline_secs: Option<u64>,
+    },
+    FlashSwap {
+        // the token account receiving the output token before the callback
+        receiver_token: Pubkey,
+        // the program paying the input token in the callback
+        callback_program: Pubkey,
+        #[arg(short, long)]
+        base_in: bool,
+        #[arg(short, long)]
+        simulate: bool,
+        amount: u64,
+        other_amount_threshold: u64,
+        limit_price: Option<f64>,
+        // the writable accounts passed to the callback in order
+        #[arg(long)]
+        callback_account: Vec<Pubkey>,
+        // the hex encoded data passed through to the callback
+        #[arg(long, default_value = "")]
+        callback_data: String,
+    },
+    FlashLoan {
+        // the token account receiving the borrowed token_0
+        receiver_token_0: Pubkey,
+        // the token account receiving the borrowed token_1
+        receiver_token_1: Pubkey,
+        // the program repaying the loan in the callback
+        callback_program: Pubkey,
+        amount_0: u64,
+        amount_1: u64,
+        #[arg(short, long)]
+        simulate: bool,
+        // the writable accounts passed to the callback in order
+        #[arg(long)]
+        callback_account: Vec<Pubkey>,
+        // the hex encoded data passed through to the callback
+        #[arg(long, default_value = "")]
+        callback_data: String,
+    },
+    PlaceLimitOrder {
+        // the order range starts at the tick of this price, one tick spacing wide
+        tick_lower_price: f64,
+        // sell token_0 above the current price, otherwise sell token_1 below it
+        #[arg(short, long)]
+        zero_for_one: bool,
+        amount: u64,
+        // distinguishes the orders of the payer on the pool
+        #[arg(long, default_value_t = 0)]
+        order_nonce: u64,
+    },
+    CancelLimitOrder {
+        limit_order: Pubkey,
+    },
+    ClaimLimitOrder {
+        limit_order: Pubkey,
+    },
+    CreateTwammOrderPool {
+        // the order pool sells token_0 if set, otherwise token_1
+        #[arg(short, long)]
+        zero_for_one: bool,
+    },
+    PlaceTwammOrder {
+        // sell token_0 for token_1 if set, otherwise sell token_1 for token_0
+        #[arg(short, long)]
+        zero_for_one: bool,
+        amount: u64,
+        // the number of hourly intervals the amount is sold over
+        intervals: u16,
+        // distinguishes the orders of the payer on the order pool
+        #[arg(long, default_value_t = 0)]
+        order_nonce: u64,
+    },
+    WithdrawTwammOrder {
+        order: Pubkey,
+    },
+    ExecuteTwamm {
+        // execute the order pool selling token_0 if set, otherwise token_1
+        #[arg(short, long)]
+        zero_for_one: bool,
+    },
+    SwapRouterIn {
+        input_token: Pubkey,
+        amount_in: u64,
+        amount_out_minimum: u64,
+        // the pools of the route in swap order
+        #[arg(long, required = true)]
+        pool: Vec<Pubkey>,
+        // the user token account receiving the output of each pool
+        #[arg(long, required = true)]
+        output_token: Vec<Pubkey>,
+        // the price limit of each pool, bounds the price impact of every hop
+        #[arg(long)]
+        hop_limit_price: Vec<f64>,
+        // the minimum output of each pool
+        #[arg(long)]
+        hop_amount_out_minimum: Vec<u64>,
+        #[arg(short, long)]
+        simulate: bool,
+        #[arg(long)]
+        referral_token: Option<Pubkey>,
+        #[arg(long, default_value_t = 0)]
+        referral_fee_bps: u16,
+    },
+    SwapRouterOut {
+        input_token: Pubkey,
+        amount_out: u64,
+        amount_in_maximum: u64,
+        // the pools of the route in swap order
+        #[arg(long, required = true)]
+        pool: Vec<Pubkey>,
+        // the user token account receiving the output of each pool
+        #[arg(long, required = true)]
+        output_token: Vec<Pubkey>,
+        #[arg(short, long)]
+        simulate: bool,
+        #[arg(long)]
+        referral_token: Option<Pubkey>,
+        #[arg(long, default_value_t = 0)]
+        referral_fee_bps: u16,
     },
     PPositionByOwner {
         user_wallet: Pubkey,
@@ -763,6 +1095,8 @@ fn main() -> Result<()> {
             config_index,
             tick_spacing,
             trade_fee_flat,
+            trade_fee_rate,
+            trade_fee_mode,
             protocol_fee_rate,
             fund_fee_rate,
         } => {
@@ -771,6 +1105,8 @@ fn main() -> Result<()> {
                 config_index,
                 tick_spacing,
                 trade_fee_flat,
+                trade_fee_rate,
+                trade_fee_mode,
                 protocol_fee_rate,
                 fund_fee_rate,
             )?;
@@ -791,14 +1127,30 @@ fn main() -> Result<()> {
             param,
             value,
             remaining,
+            fee_override_pools,
         } => {
             let mut remaing_accounts = Vec::new();
             let mut update_value: u64 = 0;
             let match_param = Some(param);
             match match_param {
                 Some(0) => update_value = value,
-                Some(1) => update_value = value,
-                Some(2) => update_value = value,
+                Some(1) | Some(2) | Some(12) => {
+                    update_value = value;
+                    for pool_id in fee_override_pools {
+                        remaing_accounts.push(AccountMeta::new_readonly(
+                            raydium_amm_v3::states::PoolFeeOverride::key(pool_id),
+                            false,
+                        ));
+                    }
+                }
+                Some(5) => update_value = value,
+                Some(6) => update_value = value,
+                Some(7) => update_value = value,
+                Some(8) => update_value = value,
+                Some(9) => update_value = value,
+                Some(10) => update_value = value,
+                Some(11) => update_value = value,
+                Some(13) => update_value = value,
                 Some(3) => {
                     let remaining_key = remaining.unwrap();
                     remaing_accounts.push(AccountMeta::new_readonly(remaining_key, false));
@@ -835,6 +1187,154 @@ fn main() -> Result<()> {
             let signature = send_txn(&rpc_client, &txn, true)?;
             println!("{}", signature);
         }
+        CommandsName::SetPoolFeeOverride {
+            pool_id,
+            trade_fee_flat,
+            protocol_fee_rate,
+            fund_fee_rate,
+        } => {
+            let pool_id = if let Some(pool_id) = pool_id {
+                pool_id
+            } else {
+                pool_config.pool_id_account.unwrap()
+            };
+            let pool_state: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let set_pool_fee_override_instr = set_pool_fee_override_instr(
+                &pool_config.clone(),
+                pool_state.amm_config,
+                pool_id,
+                trade_fee_flat,
+                protocol_fee_rate,
+                fund_fee_rate,
+            )?;
+            // send
+            let signers = vec![&payer, &admin];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &set_pool_fee_override_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::UpdatePoolPriceGuard {
+            pool_id,
+            max_deviation_ticks,
+            twap_window_seconds,
+        } => {
+            let pool_id = if let Some(pool_id) = pool_id {
+                pool_id
+            } else {
+                pool_config.pool_id_account.unwrap()
+            };
+            let update_pool_price_guard_instr = update_pool_price_guard_instr(
+                &pool_config.clone(),
+                pool_id,
+                max_deviation_ticks,
+                twap_window_seconds,
+            )?;
+            // send
+            let signers = vec![&payer, &admin];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &update_pool_price_guard_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::UpdatePoolFlashLoanFee {
+            pool_id,
+            flash_loan_fee_flat,
+            flash_loan_fee_rate,
+            flash_loan_fee_mode,
+        } => {
+            let pool_id = if let Some(pool_id) = pool_id {
+                pool_id
+            } else {
+                pool_config.pool_id_account.unwrap()
+            };
+            let update_pool_flash_loan_fee_instr = update_pool_flash_loan_fee_instr(
+                &pool_config.clone(),
+                pool_id,
+                flash_loan_fee_flat,
+                flash_loan_fee_rate,
+                flash_loan_fee_mode,
+            )?;
+            // send
+            let signers = vec![&payer, &admin];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &update_pool_flash_loan_fee_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::UpdatePoolCircuitBreaker {
+            pool_id,
+            max_ticks,
+            window_seconds,
+            cooldown_seconds,
+        } => {
+            let pool_id = if let Some(pool_id) = pool_id {
+                pool_id
+            } else {
+                pool_config.pool_id_account.unwrap()
+            };
+            let update_pool_circuit_breaker_instr = update_pool_circuit_breaker_instr(
+                &pool_config.clone(),
+                pool_id,
+                max_ticks,
+                window_seconds,
+                cooldown_seconds,
+            )?;
+            // send
+            let signers = vec![&payer, &admin];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &update_pool_circuit_breaker_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::UpdatePoolHook {
+            pool_id,
+            hook_program,
+            hook_permissions,
+        } => {
+            let pool_id = if let Some(pool_id) = pool_id {
+                pool_id
+            } else {
+                pool_config.pool_id_account.unwrap()
+            };
+            let update_pool_hook_instr = update_pool_hook_instr(
+                &pool_config.clone(),
+                pool_id,
+                hook_program.unwrap_or_default(),
+                hook_permissions,
+            )?;
+            // send
+            let signers = vec![&payer, &admin];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &update_pool_hook_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
         CommandsName::CreateOperation => {
             let create_instr = create_operation_account_instr(&pool_config.clone())?;
             // send
@@ -869,6 +1369,9 @@ fn main() -> Result<()> {
             mint0,
             mint1,
             open_time,
+            launch_fee_flat,
+            launch_fee_duration,
+            launch_fee_decay_mode,
         } => {
             let mut price = price;
             let mut mint0 = mint0;
@@ -895,6 +1398,15 @@ fn main() -> Result<()> {
                 ],
                 &pool_config.raydium_v3_program,
             );
+            let amm_config_state = deserialize_anchor_account::<raydium_amm_v3::states::AmmConfig>(
+                &rpc_client.get_account(&amm_config_key)?,
+            )?;
+            // the fee receiver is only passed if the config charges a pool creation fee
+            let fee_receiver = if amm_config_state.pool_creation_fee > 0 {
+                Some(pool_config.fee_receiver)
+            } else {
+                None
+            };
             let tick = tick_math::get_tick_at_sqrt_price(sqrt_price_x64).unwrap();
             println!(
                 "tick:{}, price:{}, sqrt_price_x64:{}, amm_config_key:{}",
@@ -909,8 +1421,12 @@ fn main() -> Result<()> {
                 mint0_owner,
                 mint1_owner,
                 pool_config.tickarray_bitmap_extension.unwrap(),
+                fee_receiver,
                 sqrt_price_x64,
                 open_time,
+                launch_fee_flat,
+                launch_fee_duration,
+                launch_fee_decay_mode,
             )?;
 
             // send
@@ -1255,6 +1771,7 @@ fn main() -> Result<()> {
             tick_upper_price,
             is_base_0,
             imput_amount,
+            deadline_secs,
         } => {
             // load pool to get observation
             let pool: raydium_amm_v3::states::PoolState =
@@ -1418,6 +1935,7 @@ fn main() -> Result<()> {
                     tick_upper_index,
                     tick_array_lower_start_index,
                     tick_array_upper_start_index,
+                    deadline_from_secs(deadline_secs),
                 )?;
                 // send
                 let signers = vec![&payer];
@@ -1440,6 +1958,7 @@ fn main() -> Result<()> {
             tick_upper_index,
             liquidity,
             simulate,
+            deadline_secs,
         } => {
             // load pool to get observation
             let pool: raydium_amm_v3::states::PoolState =
@@ -1575,6 +2094,7 @@ fn main() -> Result<()> {
                     tick_upper_index,
                     tick_array_lower_start_index,
                     tick_array_upper_start_index,
+                    deadline_from_secs(deadline_secs),
                 )?;
                 if liquidity == find_position.liquidity {
                     let close_position_instr = close_personal_position_instr(
@@ -1611,148 +2131,1919 @@ fn main() -> Result<()> {
                 println!("personal position exist:{:?}", find_position);
             }
         }
-        CommandsName::Swap {
-            input_token,
-            output_token,
-            base_in,
-            simulate,
-            amount,
-            limit_price,
+        CommandsName::DecreaseLiquidityZapOut {
+            nft_mint,
+            liquidity,
+            is_base_0,
         } => {
-            // load mult account
-            let load_accounts = vec![
-                input_token,
-                output_token,
-                pool_config.amm_config_key,
-                pool_config.pool_id_account.unwrap(),
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let user_nft_token_info = get_all_nft_and_position_by_owner(
+                &rpc_client,
+                &payer.pubkey(),
+                &pool_config.raydium_v3_program,
+            )
+            .into_iter()
+            .find(|nft_info| nft_info.mint == nft_mint)
+            .unwrap();
+            let liquidity = liquidity.unwrap_or(position.liquidity);
+            // the token received is swapped out of the other token
+            let zero_for_one = !is_base_0;
+
+            let (amount_0, amount_1) = liquidity_math::get_delta_amounts_signed(
+                pool.tick_current,
+                pool.sqrt_price_x64,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                -(liquidity as i128),
+            )?;
+            let amount_0 = amount_0 + position.token_fees_owed_0;
+            let amount_1 = amount_1 + position.token_fees_owed_1;
+            let (swap_amount, amount_out) = if zero_for_one {
+                (amount_0, amount_1)
+            } else {
+                (amount_1, amount_0)
+            };
+
+            let amm_config_state: raydium_amm_v3::states::AmmConfig =
+                program.account(pool.amm_config)?;
+            let pool_fee_override_key = raydium_amm_v3::states::PoolFeeOverride::key(pool_id);
+            let amm_config_state = if pool.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(pool_fee_override_key)?;
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            let mut remaining_accounts = vec![AccountMeta::new(
                 pool_config.tickarray_bitmap_extension.unwrap(),
-            ];
-            let rsps = rpc_client.get_multiple_accounts(&load_accounts)?;
-            let [user_input_account, user_output_account, amm_config_account, pool_account, tickarray_bitmap_extension_account] =
-                array_ref![rsps, 0, 5];
-            let user_input_state =
-                StateWithExtensions::<Account>::unpack(&user_input_account.as_ref().unwrap().data)
-                    .unwrap();
-            let user_output_state =
-                StateWithExtensions::<Account>::unpack(&user_output_account.as_ref().unwrap().data)
+                false,
+            )];
+            if pool.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(pool_fee_override_key, false));
+            }
+            let mut swap_amount_out = 0;
+            if swap_amount > 0 {
+                // the swap is simulated on the pool before the liquidity is withdrawn,
+                // the slippage covers the difference
+                let observation_state: raydium_amm_v3::states::ObservationState =
+                    program.account(pool.observation_key)?;
+                let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                    program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+                let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u32;
+                let mut tick_arrays = load_cur_and_next_five_tick_array(
+                    &rpc_client,
+                    &pool_config,
+                    &pool,
+                    &tickarray_bitmap_extension,
+                    zero_for_one,
+                );
+                let (amount_calculated, tick_array_indexs) =
+                    utils::get_out_put_amount_and_remaining_accounts(
+                        swap_amount,
+                        None,
+                        zero_for_one,
+                        true,
+                        &amm_config_state,
+                        &pool,
+                        &observation_state,
+                        block_timestamp,
+                        &tickarray_bitmap_extension,
+                        &mut tick_arrays,
+                    )
                     .unwrap();
-            let amm_config_state = deserialize_anchor_account::<raydium_amm_v3::states::AmmConfig>(
-                amm_config_account.as_ref().unwrap(),
+                swap_amount_out = amount_calculated;
+                let mut accounts = tick_array_indexs
+                    .into_iter()
+                    .map(|index| {
+                        AccountMeta::new(
+                            Pubkey::find_program_address(
+                                &[
+                                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                    pool_id.to_bytes().as_ref(),
+                                    &index.to_be_bytes(),
+                                ],
+                                &pool_config.raydium_v3_program,
+                            )
+                            .0,
+                            false,
+                        )
+                    })
+                    .collect();
+                remaining_accounts.append(&mut accounts);
+            }
+            let amount_out_with_slippage =
+                amount_with_slippage(amount_out + swap_amount_out, pool_config.slippage, false);
+            let transfer_fee = get_pool_mints_transfer_fee(
+                &rpc_client,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                if zero_for_one {
+                    0
+                } else {
+                    amount_out_with_slippage
+                },
+                if zero_for_one {
+                    amount_out_with_slippage
+                } else {
+                    0
+                },
+            );
+            let (output_transfer_fee, output_mint, output_token_program) = if zero_for_one {
+                (
+                    transfer_fee.1.transfer_fee,
+                    pool.token_mint_1,
+                    transfer_fee.1.owner,
+                )
+            } else {
+                (
+                    transfer_fee.0.transfer_fee,
+                    pool.token_mint_0,
+                    transfer_fee.0.owner,
+                )
+            };
+            let amount_out_min = amount_out_with_slippage
+                .checked_sub(output_transfer_fee)
+                .unwrap();
+            println!(
+                "swap_amount:{}, swap_amount_out:{}, amount_out_min:{}",
+                swap_amount, swap_amount_out, amount_out_min
+            );
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let zap_out_instr = decrease_liquidity_zap_out_instr(
+                &pool_config.clone(),
+                pool.amm_config,
+                pool_id,
+                pool.observation_key,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                nft_mint,
+                user_nft_token_info.key,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &output_mint,
+                    &output_token_program,
+                ),
+                remaining_accounts,
+                liquidity,
+                amount_out_min,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
             )?;
-            let pool_state = deserialize_anchor_account::<raydium_amm_v3::states::PoolState>(
-                pool_account.as_ref().unwrap(),
+            instructions.extend(zap_out_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::CollectFees {
+            nft_mint,
+            amount_0,
+            amount_1,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let user_nft_token_info = get_all_nft_and_position_by_owner(
+                &rpc_client,
+                &payer.pubkey(),
+                &pool_config.raydium_v3_program,
+            )
+            .into_iter()
+            .find(|nft_info| nft_info.mint == nft_mint)
+            .unwrap();
+            let token_program_0 = rpc_client.get_account(&pool.token_mint_0)?.owner;
+            let token_program_1 = rpc_client.get_account(&pool.token_mint_1)?.owner;
+
+            let collect_instr = collect_fees_instr(
+                &pool_config.clone(),
+                pool_id,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                nft_mint,
+                user_nft_token_info.key,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &token_program_0,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &token_program_1,
+                ),
+                amount_0.unwrap_or(u64::MAX),
+                amount_1.unwrap_or(u64::MAX),
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
             )?;
-            let tickarray_bitmap_extension =
-                deserialize_anchor_account::<raydium_amm_v3::states::TickArrayBitmapExtension>(
-                    tickarray_bitmap_extension_account.as_ref().unwrap(),
-                )?;
-            let zero_for_one = user_input_state.base.mint == pool_state.token_mint_0
-                && user_output_state.base.mint == pool_state.token_mint_1;
-            // load tick_arrays
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &collect_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::CollectRewards {
+            nft_mint,
+            reward_index,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let user_nft_token_info = get_all_nft_and_position_by_owner(
+                &rpc_client,
+                &payer.pubkey(),
+                &pool_config.raydium_v3_program,
+            )
+            .into_iter()
+            .find(|nft_info| nft_info.mint == nft_mint)
+            .unwrap();
+            // reward vault, recipient token account and reward mint of each picked reward
+            let mut remaining_accounts = Vec::new();
+            for index in reward_index.iter() {
+                let reward_info = pool.reward_infos[usize::from(*index)];
+                let reward_token_program = rpc_client.get_account(&reward_info.token_mint)?.owner;
+                remaining_accounts.push(AccountMeta::new(reward_info.token_vault, false));
+                remaining_accounts.push(AccountMeta::new(
+                    spl_associated_token_account::get_associated_token_address_with_program_id(
+                        &payer.pubkey(),
+                        &reward_info.token_mint,
+                        &reward_token_program,
+                    ),
+                    false,
+                ));
+                remaining_accounts.push(AccountMeta::new_readonly(reward_info.token_mint, false));
+            }
+
+            let collect_instr = collect_rewards_instr(
+                &pool_config.clone(),
+                pool_id,
+                nft_mint,
+                user_nft_token_info.key,
+                remaining_accounts,
+                reward_index,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &collect_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::LockPosition {
+            nft_mint,
+            unlock_timestamp,
+            lock_authority,
+            nft_account,
+        } => {
+            let nft_account = if let Some(nft_account) = nft_account {
+                nft_account
+            } else {
+                get_all_nft_and_position_by_owner(
+                    &rpc_client,
+                    &payer.pubkey(),
+                    &pool_config.raydium_v3_program,
+                )
+                .into_iter()
+                .find(|nft_info| nft_info.mint == nft_mint)
+                .unwrap()
+                .key
+            };
+            let lock_instr = lock_position_instr(
+                &pool_config.clone(),
+                nft_mint,
+                nft_account,
+                unlock_timestamp,
+                lock_authority,
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &lock_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::CompoundPosition {
+            nft_mint,
+            nft_account,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let nft_account = if let Some(nft_account) = nft_account {
+                nft_account
+            } else {
+                get_all_nft_and_position_by_owner(
+                    &rpc_client,
+                    &payer.pubkey(),
+                    &pool_config.raydium_v3_program,
+                )
+                .into_iter()
+                .find(|nft_info| nft_info.mint == nft_mint)
+                .unwrap()
+                .key
+            };
+            let remaining_accounts = vec![AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            )];
+
+            let compound_instr = compound_position_instr(
+                &pool_config.clone(),
+                pool_id,
+                nft_mint,
+                nft_account,
+                remaining_accounts,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &compound_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::IncreaseLiquidityZap {
+            nft_mint,
+            is_base_0,
+            amount_in,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let user_nft_token_info = get_all_nft_and_position_by_owner(
+                &rpc_client,
+                &payer.pubkey(),
+                &pool_config.raydium_v3_program,
+            )
+            .into_iter()
+            .find(|nft_info| nft_info.mint == nft_mint)
+            .unwrap();
+            let zero_for_one = is_base_0;
+
+            // the zap splits the amount received by the vault
+            let transfer_fee = get_pool_mints_transfer_fee(
+                &rpc_client,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                if zero_for_one { amount_in } else { 0 },
+                if zero_for_one { 0 } else { amount_in },
+            );
+            let amount_received = if zero_for_one {
+                amount_in - transfer_fee.0.transfer_fee
+            } else {
+                amount_in - transfer_fee.1.transfer_fee
+            };
+            let amm_config_state: raydium_amm_v3::states::AmmConfig =
+                program.account(pool.amm_config)?;
+            let pool_fee_override_key = raydium_amm_v3::states::PoolFeeOverride::key(pool_id);
+            let amm_config_state = if pool.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(pool_fee_override_key)?;
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            let observation_state: raydium_amm_v3::states::ObservationState =
+                program.account(pool.observation_key)?;
+            let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+            let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u32;
             let mut tick_arrays = load_cur_and_next_five_tick_array(
                 &rpc_client,
                 &pool_config,
-                &pool_state,
+                &pool,
                 &tickarray_bitmap_extension,
                 zero_for_one,
             );
+            let (swap_amount, swap_amount_out, tick_array_indexs) =
+                utils::get_zap_swap_amount_and_remaining_accounts(
+                    amount_received,
+                    zero_for_one,
+                    position.tick_lower_index,
+                    position.tick_upper_index,
+                    &amm_config_state,
+                    &pool,
+                    &observation_state,
+                    block_timestamp,
+                    &tickarray_bitmap_extension,
+                    &mut tick_arrays,
+                )
+                .unwrap();
+            let (amount_0, amount_1) = if zero_for_one {
+                (amount_received - swap_amount, swap_amount_out)
+            } else {
+                (swap_amount_out, amount_received - swap_amount)
+            };
+            // the swap moves the price, the liquidity is bounded with the slippage
+            let liquidity = liquidity_math::get_liquidity_from_amounts(
+                pool.sqrt_price_x64,
+                tick_math::get_sqrt_price_at_tick(position.tick_lower_index)?,
+                tick_math::get_sqrt_price_at_tick(position.tick_upper_index)?,
+                amount_0,
+                amount_1,
+            );
+            let liquidity_min = (liquidity as f64 * (1.0 - pool_config.slippage)) as u128;
+            println!(
+                "swap_amount:{}, swap_amount_out:{}, liquidity:{}, liquidity_min:{}",
+                swap_amount, swap_amount_out, liquidity, liquidity_min
+            );
 
-            let mut sqrt_price_limit_x64 = None;
-            if limit_price.is_some() {
-                let sqrt_price_x64 = price_to_sqrt_price_x64(
-                    limit_price.unwrap(),
-                    pool_state.mint_decimals_0,
-                    pool_state.mint_decimals_1,
-                );
-                sqrt_price_limit_x64 = Some(sqrt_price_x64);
+            let mut remaining_accounts = vec![AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            )];
+            if pool.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(pool_fee_override_key, false));
+            }
+            let mut accounts = tick_array_indexs
+                .into_iter()
+                .map(|index| {
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[
+                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                pool_id.to_bytes().as_ref(),
+                                &index.to_be_bytes(),
+                            ],
+                            &pool_config.raydium_v3_program,
+                        )
+                        .0,
+                        false,
+                    )
+                })
+                .collect();
+            remaining_accounts.append(&mut accounts);
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let zap_instr = increase_liquidity_zap_instr(
+                &pool_config.clone(),
+                pool.amm_config,
+                pool_id,
+                pool.observation_key,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                nft_mint,
+                user_nft_token_info.key,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &transfer_fee.0.owner,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &transfer_fee.1.owner,
+                ),
+                remaining_accounts,
+                amount_in,
+                zero_for_one,
+                liquidity_min,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+            )?;
+            instructions.extend(zap_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::OpenPositionZap {
+            tick_lower_price,
+            tick_upper_price,
+            is_base_0,
+            amount_in,
+            with_metadata,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let tick_lower_index = tick_with_spacing(
+                tick_math::get_tick_at_sqrt_price(price_to_sqrt_price_x64(
+                    tick_lower_price,
+                    pool.mint_decimals_0,
+                    pool.mint_decimals_1,
+                ))?,
+                pool.tick_spacing.into(),
+            );
+            let tick_upper_index = tick_with_spacing(
+                tick_math::get_tick_at_sqrt_price(price_to_sqrt_price_x64(
+                    tick_upper_price,
+                    pool.mint_decimals_0,
+                    pool.mint_decimals_1,
+                ))?,
+                pool.tick_spacing.into(),
+            );
+            println!(
+                "tick_lower_index:{}, tick_upper_index:{}",
+                tick_lower_index, tick_upper_index
+            );
+            let zero_for_one = is_base_0;
+
+            // the zap splits the amount received by the vault
+            let transfer_fee = get_pool_mints_transfer_fee(
+                &rpc_client,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                if zero_for_one { amount_in } else { 0 },
+                if zero_for_one { 0 } else { amount_in },
+            );
+            let amount_received = if zero_for_one {
+                amount_in - transfer_fee.0.transfer_fee
+            } else {
+                amount_in - transfer_fee.1.transfer_fee
+            };
+            let amm_config_state: raydium_amm_v3::states::AmmConfig =
+                program.account(pool.amm_config)?;
+            let pool_fee_override_key = raydium_amm_v3::states::PoolFeeOverride::key(pool_id);
+            let amm_config_state = if pool.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(pool_fee_override_key)?;
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            let observation_state: raydium_amm_v3::states::ObservationState =
+                program.account(pool.observation_key)?;
+            let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+            let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u32;
+            let mut tick_arrays = load_cur_and_next_five_tick_array(
+                &rpc_client,
+                &pool_config,
+                &pool,
+                &tickarray_bitmap_extension,
+                zero_for_one,
+            );
+            let (swap_amount, swap_amount_out, tick_array_indexs) =
+                utils::get_zap_swap_amount_and_remaining_accounts(
+                    amount_received,
+                    zero_for_one,
+                    tick_lower_index,
+                    tick_upper_index,
+                    &amm_config_state,
+                    &pool,
+                    &observation_state,
+                    block_timestamp,
+                    &tickarray_bitmap_extension,
+                    &mut tick_arrays,
+                )
+                .unwrap();
+            let (amount_0, amount_1) = if zero_for_one {
+                (amount_received - swap_amount, swap_amount_out)
+            } else {
+                (swap_amount_out, amount_received - swap_amount)
+            };
+            // the swap moves the price, the liquidity is bounded with the slippage
+            let liquidity = liquidity_math::get_liquidity_from_amounts(
+                pool.sqrt_price_x64,
+                tick_math::get_sqrt_price_at_tick(tick_lower_index)?,
+                tick_math::get_sqrt_price_at_tick(tick_upper_index)?,
+                amount_0,
+                amount_1,
+            );
+            let liquidity_min = (liquidity as f64 * (1.0 - pool_config.slippage)) as u128;
+            println!(
+                "swap_amount:{}, swap_amount_out:{}, liquidity:{}, liquidity_min:{}",
+                swap_amount, swap_amount_out, liquidity, liquidity_min
+            );
+
+            // the tick array bitmap extension must be the first remaining account
+            let mut remaining_accounts = vec![AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            )];
+            if pool.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(pool_fee_override_key, false));
+            }
+            let mut accounts = tick_array_indexs
+                .into_iter()
+                .map(|index| {
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[
+                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                pool_id.to_bytes().as_ref(),
+                                &index.to_be_bytes(),
+                            ],
+                            &pool_config.raydium_v3_program,
+                        )
+                        .0,
+                        false,
+                    )
+                })
+                .collect();
+            remaining_accounts.append(&mut accounts);
+
+            // new nft mint
+            let nft_mint = Keypair::new();
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let zap_instr = open_position_zap_instr(
+                &pool_config.clone(),
+                pool.amm_config,
+                pool_id,
+                pool.observation_key,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                nft_mint.pubkey(),
+                payer.pubkey(),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &transfer_fee.0.owner,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &transfer_fee.1.owner,
+                ),
+                remaining_accounts,
+                amount_in,
+                zero_for_one,
+                liquidity_min,
+                tick_lower_index,
+                tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+                with_metadata,
+            )?;
+            instructions.extend(zap_instr);
+            // send
+            let signers = vec![&payer, &nft_mint];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::RebalancePosition {
+            nft_mint,
+            tick_lower_price,
+            tick_upper_price,
+            swap_surplus,
+            limit_price,
+            liquidity_min,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let (personal_position_key, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::POSITION_SEED.as_bytes(),
+                    nft_mint.to_bytes().as_ref(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let position: raydium_amm_v3::states::PersonalPositionState =
+                program.account(personal_position_key)?;
+            let tick_lower_index = tick_with_spacing(
+                tick_math::get_tick_at_sqrt_price(price_to_sqrt_price_x64(
+                    tick_lower_price,
+                    pool.mint_decimals_0,
+                    pool.mint_decimals_1,
+                ))?,
+                pool.tick_spacing.into(),
+            );
+            let tick_upper_index = tick_with_spacing(
+                tick_math::get_tick_at_sqrt_price(price_to_sqrt_price_x64(
+                    tick_upper_price,
+                    pool.mint_decimals_0,
+                    pool.mint_decimals_1,
+                ))?,
+                pool.tick_spacing.into(),
+            );
+            println!(
+                "tick_lower_index:{}, tick_upper_index:{}",
+                tick_lower_index, tick_upper_index
+            );
+            let user_nft_token_info = get_all_nft_and_position_by_owner(
+                &rpc_client,
+                &payer.pubkey(),
+                &pool_config.raydium_v3_program,
+            )
+            .into_iter()
+            .find(|nft_info| nft_info.mint == nft_mint)
+            .unwrap();
+            let token_program_0 = rpc_client.get_account(&pool.token_mint_0)?.owner;
+            let token_program_1 = rpc_client.get_account(&pool.token_mint_1)?.owner;
+
+            let mut remaining_accounts = vec![AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            )];
+            if pool.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(
+                    raydium_amm_v3::states::PoolFeeOverride::key(pool_id),
+                    false,
+                ));
+            }
+            // the direction of the swap the program computes from the withdrawn tokens
+            let (amount_0, amount_1) = liquidity_math::get_delta_amounts_signed(
+                pool.tick_current,
+                pool.sqrt_price_x64,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                -(position.liquidity as i128),
+            )?;
+            let amm_config_state: raydium_amm_v3::states::AmmConfig =
+                program.account(pool.amm_config)?;
+            let (swap_amount, zero_for_one) = liquidity_math::get_rebalance_swap_amount(
+                pool.sqrt_price_x64,
+                tick_math::get_sqrt_price_at_tick(tick_lower_index)?,
+                tick_math::get_sqrt_price_at_tick(tick_upper_index)?,
+                amount_0,
+                amount_1,
+                amm_config_state.trade_fee_rate,
+            );
+            if swap_surplus && swap_amount > 0 {
+                // the swap starts from the price left by the withdrawal
+                let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                    program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+                let tick_arrays = load_cur_and_next_five_tick_array(
+                    &rpc_client,
+                    &pool_config,
+                    &pool,
+                    &tickarray_bitmap_extension,
+                    zero_for_one,
+                );
+                for tick_array in tick_arrays {
+                    remaining_accounts.push(AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[
+                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                pool_id.to_bytes().as_ref(),
+                                &identity(tick_array.start_tick_index).to_be_bytes(),
+                            ],
+                            &pool_config.raydium_v3_program,
+                        )
+                        .0,
+                        false,
+                    ));
+                }
+            }
+            let sqrt_price_limit_x64 = limit_price.map(|limit_price| {
+                price_to_sqrt_price_x64(limit_price, pool.mint_decimals_0, pool.mint_decimals_1)
+            });
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let rebalance_instr = rebalance_position_instr(
+                &pool_config.clone(),
+                pool.amm_config,
+                pool_id,
+                pool.observation_key,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                pool.token_mint_0,
+                pool.token_mint_1,
+                nft_mint,
+                user_nft_token_info.key,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &token_program_0,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &token_program_1,
+                ),
+                remaining_accounts,
+                position.tick_lower_index,
+                position.tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    position.tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+                tick_lower_index,
+                tick_upper_index,
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    tick_lower_index,
+                    pool.tick_spacing.into(),
+                ),
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    tick_upper_index,
+                    pool.tick_spacing.into(),
+                ),
+                liquidity_min,
+                swap_surplus,
+                sqrt_price_limit_x64,
+            )?;
+            instructions.extend(rebalance_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::Swap {
+            input_token,
+            output_token,
+            base_in,
+            simulate,
+            amount,
+            limit_price,
+        } => {
+            // load mult account
+            let load_accounts = vec![
+                input_token,
+                output_token,
+                pool_config.amm_config_key,
+                pool_config.pool_id_account.unwrap(),
+                pool_config.tickarray_bitmap_extension.unwrap(),
+            ];
+            let rsps = rpc_client.get_multiple_accounts(&load_accounts)?;
+            let [user_input_account, user_output_account, amm_config_account, pool_account, tickarray_bitmap_extension_account] =
+                array_ref![rsps, 0, 5];
+            let user_input_state =
+                StateWithExtensions::<Account>::unpack(&user_input_account.as_ref().unwrap().data)
+                    .unwrap();
+            let user_output_state =
+                StateWithExtensions::<Account>::unpack(&user_output_account.as_ref().unwrap().data)
+                    .unwrap();
+            let amm_config_state = deserialize_anchor_account::<raydium_amm_v3::states::AmmConfig>(
+                amm_config_account.as_ref().unwrap(),
+            )?;
+            let pool_state = deserialize_anchor_account::<raydium_amm_v3::states::PoolState>(
+                pool_account.as_ref().unwrap(),
+            )?;
+            let tickarray_bitmap_extension =
+                deserialize_anchor_account::<raydium_amm_v3::states::TickArrayBitmapExtension>(
+                    tickarray_bitmap_extension_account.as_ref().unwrap(),
+                )?;
+            // the swap charges the overridden fees if the pool has an override account
+            let pool_fee_override_key =
+                raydium_amm_v3::states::PoolFeeOverride::key(pool_config.pool_id_account.unwrap());
+            let amm_config_state = if pool_state.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(pool_fee_override_key)?;
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            let zero_for_one = user_input_state.base.mint == pool_state.token_mint_0
+                && user_output_state.base.mint == pool_state.token_mint_1;
+            // the dynamic fee depends on the oracle observations and the block time
+            let observation_state: raydium_amm_v3::states::ObservationState =
+                program.account(pool_state.observation_key)?;
+            let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u32;
+            // load tick_arrays
+            let mut tick_arrays = load_cur_and_next_five_tick_array(
+                &rpc_client,
+                &pool_config,
+                &pool_state,
+                &tickarray_bitmap_extension,
+                zero_for_one,
+            );
+
+            let mut sqrt_price_limit_x64 = None;
+            if limit_price.is_some() {
+                let sqrt_price_x64 = price_to_sqrt_price_x64(
+                    limit_price.unwrap(),
+                    pool_state.mint_decimals_0,
+                    pool_state.mint_decimals_1,
+                );
+                sqrt_price_limit_x64 = Some(sqrt_price_x64);
+            }
+
+            let (mut other_amount_threshold, mut tick_array_indexs) =
+                utils::get_out_put_amount_and_remaining_accounts(
+                    amount,
+                    sqrt_price_limit_x64,
+                    zero_for_one,
+                    base_in,
+                    &amm_config_state,
+                    &pool_state,
+                    &observation_state,
+                    block_timestamp,
+                    &tickarray_bitmap_extension,
+                    &mut tick_arrays,
+                )
+                .unwrap();
+            println!(
+                "amount:{}, other_amount_threshold:{}",
+                amount, other_amount_threshold
+            );
+            if base_in {
+                // min out
+                other_amount_threshold =
+                    amount_with_slippage(other_amount_threshold, pool_config.slippage, false);
+            } else {
+                // max in
+                other_amount_threshold =
+                    amount_with_slippage(other_amount_threshold, pool_config.slippage, true);
+            }
+
+            let current_or_next_tick_array_key = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                    pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
+                    &tick_array_indexs.pop_front().unwrap().to_be_bytes(),
+                ],
+                &pool_config.raydium_v3_program,
+            )
+            .0;
+            let mut remaining_accounts = Vec::new();
+            remaining_accounts.push(AccountMeta::new_readonly(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            ));
+            if pool_state.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(pool_fee_override_key, false));
+            }
+            let mut accounts = tick_array_indexs
+                .into_iter()
+                .map(|index| {
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[
+                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
+                                &index.to_be_bytes(),
+                            ],
+                            &pool_config.raydium_v3_program,
+                        )
+                        .0,
+                        false,
+                    )
+                })
+                .collect();
+            remaining_accounts.append(&mut accounts);
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let swap_instr = swap_instr(
+                &pool_config.clone(),
+                pool_state.amm_config,
+                pool_config.pool_id_account.unwrap(),
+                if zero_for_one {
+                    pool_state.token_vault_0
+                } else {
+                    pool_state.token_vault_1
+                },
+                if zero_for_one {
+                    pool_state.token_vault_1
+                } else {
+                    pool_state.token_vault_0
+                },
+                pool_state.observation_key,
+                input_token,
+                output_token,
+                current_or_next_tick_array_key,
+                remaining_accounts,
+                amount,
+                other_amount_threshold,
+                sqrt_price_limit_x64,
+                base_in,
+            )
+            .unwrap();
+            instructions.extend(swap_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            if simulate {
+                let ret =
+                    simulate_transaction(&rpc_client, &txn, true, CommitmentConfig::confirmed())?;
+                println!("{:#?}", ret);
+            } else {
+                let signature = send_txn(&rpc_client, &txn, true)?;
+                println!("{}", signature);
+            }
+        }
+        CommandsName::SwapV2 {
+            input_token,
+            output_token,
+            base_in,
+            simulate,
+            amount,
+            limit_price,
+            referral_token,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+            deadline_secs,
+        } => {
+            // load mult account
+            let load_accounts = vec![
+                input_token,
+                output_token,
+                pool_config.amm_config_key,
+                pool_config.pool_id_account.unwrap(),
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                pool_config.mint0.unwrap(),
+                pool_config.mint1.unwrap(),
+            ];
+            let rsps = rpc_client.get_multiple_accounts(&load_accounts)?;
+            let epoch = rpc_client.get_epoch_info().unwrap().epoch;
+            let [user_input_account, user_output_account, amm_config_account, pool_account, tickarray_bitmap_extension_account, mint0_account, mint1_account] =
+                array_ref![rsps, 0, 7];
+
+            let user_input_token_data = user_input_account.clone().unwrap().data;
+            let user_input_state = StateWithExtensions::<Account>::unpack(&user_input_token_data)?;
+            let user_output_token_data = user_output_account.clone().unwrap().data;
+            let user_output_state =
+                StateWithExtensions::<Account>::unpack(&user_output_token_data)?;
+            let mint0_data = mint0_account.clone().unwrap().data;
+            let mint0_state = StateWithExtensions::<Mint>::unpack(&mint0_data)?;
+            let mint1_data = mint1_account.clone().unwrap().data;
+            let mint1_state = StateWithExtensions::<Mint>::unpack(&mint1_data)?;
+            let amm_config_state = deserialize_anchor_account::<raydium_amm_v3::states::AmmConfig>(
+                amm_config_account.as_ref().unwrap(),
+            )?;
+            let pool_state = deserialize_anchor_account::<raydium_amm_v3::states::PoolState>(
+                pool_account.as_ref().unwrap(),
+            )?;
+            let tickarray_bitmap_extension =
+                deserialize_anchor_account::<raydium_amm_v3::states::TickArrayBitmapExtension>(
+                    tickarray_bitmap_extension_account.as_ref().unwrap(),
+                )?;
+            // the swap charges the overridden fees if the pool has an override account
+            let pool_fee_override_key =
+                raydium_amm_v3::states::PoolFeeOverride::key(pool_config.pool_id_account.unwrap());
+            let amm_config_state = if pool_state.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(pool_fee_override_key)?;
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            let zero_for_one = user_input_state.base.mint == pool_state.token_mint_0
+                && user_output_state.base.mint == pool_state.token_mint_1;
+            // the dynamic fee depends on the oracle observations and the block time
+            let observation_state: raydium_amm_v3::states::ObservationState =
+                program.account(pool_state.observation_key)?;
+            let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u32;
+
+            let transfer_fee = if base_in {
+                if zero_for_one {
+                    get_transfer_fee(&mint0_state, epoch, amount)
+                } else {
+                    get_transfer_fee(&mint1_state, epoch, amount)
+                }
+            } else {
+                0
+            };
+            let amount_specified = amount.checked_sub(transfer_fee).unwrap();
+            // load tick_arrays
+            let mut tick_arrays = load_cur_and_next_five_tick_array(
+                &rpc_client,
+                &pool_config,
+                &pool_state,
+                &tickarray_bitmap_extension,
+                zero_for_one,
+            );
+
+            let mut sqrt_price_limit_x64 = None;
+            if limit_price.is_some() {
+                let sqrt_price_x64 = price_to_sqrt_price_x64(
+                    limit_price.unwrap(),
+                    pool_state.mint_decimals_0,
+                    pool_state.mint_decimals_1,
+                );
+                sqrt_price_limit_x64 = Some(sqrt_price_x64);
+            }
+
+            let (mut other_amount_threshold, tick_array_indexs) =
+                utils::get_out_put_amount_and_remaining_accounts(
+                    amount_specified,
+                    sqrt_price_limit_x64,
+                    zero_for_one,
+                    base_in,
+                    &amm_config_state,
+                    &pool_state,
+                    &observation_state,
+                    block_timestamp,
+                    &tickarray_bitmap_extension,
+                    &mut tick_arrays,
+                )
+                .unwrap();
+            println!(
+                "amount:{}, other_amount_threshold:{}",
+                amount, other_amount_threshold
+            );
+            if base_in {
+                // calc mint out amount with slippage
+                other_amount_threshold =
+                    amount_with_slippage(other_amount_threshold, pool_config.slippage, false);
+            } else {
+                // calc max in with slippage
+                other_amount_threshold =
+                    amount_with_slippage(other_amount_threshold, pool_config.slippage, true);
+                // calc max in with transfer_fee
+                let transfer_fee = if zero_for_one {
+                    get_transfer_inverse_fee(&mint0_state, epoch, other_amount_threshold)
+                } else {
+                    get_transfer_inverse_fee(&mint1_state, epoch, other_amount_threshold)
+                };
+                other_amount_threshold += transfer_fee;
+            }
+
+            let mut remaining_accounts = Vec::new();
+            remaining_accounts.push(AccountMeta::new_readonly(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            ));
+            if pool_state.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(pool_fee_override_key, false));
+            }
+            let mut accounts = tick_array_indexs
+                .into_iter()
+                .map(|index| {
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[
+                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                                pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
+                                &index.to_be_bytes(),
+                            ],
+                            &pool_config.raydium_v3_program,
+                        )
+                        .0,
+                        false,
+                    )
+                })
+                .collect();
+            remaining_accounts.append(&mut accounts);
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let swap_instr = swap_v2_instr(
+                &pool_config.clone(),
+                pool_state.amm_config,
+                pool_config.pool_id_account.unwrap(),
+                if zero_for_one {
+                    pool_state.token_vault_0
+                } else {
+                    pool_state.token_vault_1
+                },
+                if zero_for_one {
+                    pool_state.token_vault_1
+                } else {
+                    pool_state.token_vault_0
+                },
+                pool_state.observation_key,
+                input_token,
+                output_token,
+                if zero_for_one {
+                    pool_state.token_mint_0
+                } else {
+                    pool_state.token_mint_1
+                },
+                if zero_for_one {
+                    pool_state.token_mint_1
+                } else {
+                    pool_state.token_mint_0
+                },
+                remaining_accounts,
+                amount,
+                other_amount_threshold,
+                sqrt_price_limit_x64,
+                base_in,
+                referral_token,
+                referral_fee_bps,
+                max_deviation_ticks,
+                allow_partial_fill,
+                deadline_from_secs(deadline_secs),
+            )
+            .unwrap();
+            instructions.extend(swap_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            if simulate {
+                let ret =
+                    simulate_transaction(&rpc_client, &txn, true, CommitmentConfig::confirmed())?;
+                println!("{:#?}", ret);
+            } else {
+                let signature = send_txn(&rpc_client, &txn, true)?;
+                println!("{}", signature);
+            }
+        }
+        CommandsName::FlashSwap {
+            receiver_token,
+            callback_program,
+            base_in,
+            simulate,
+            amount,
+            other_amount_threshold,
+            limit_price,
+            callback_account,
+            callback_data,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool_state: PoolState = program.account(pool_id)?;
+            let tickarray_bitmap_extension: TickArrayBitmapExtension =
+                program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+            let receiver_account = rpc_client.get_account(&receiver_token)?;
+            let receiver_state = StateWithExtensions::<Account>::unpack(&receiver_account.data)?;
+            // the input token is the pool token the receiver doesn't hold
+            let input_mint = if receiver_state.base.mint == pool_state.token_mint_1 {
+                pool_state.token_mint_0
+            } else {
+                pool_state.token_mint_1
+            };
+            let (hop_accounts, zero_for_one, output_mint) = get_route_hop_accounts(
+                &pool_config.raydium_v3_program,
+                pool_id,
+                &pool_state,
+                &tickarray_bitmap_extension,
+                input_mint,
+                receiver_token,
+            )?;
+            let (input_vault, output_vault) = if zero_for_one {
+                (pool_state.token_vault_0, pool_state.token_vault_1)
+            } else {
+                (pool_state.token_vault_1, pool_state.token_vault_0)
+            };
+            let sqrt_price_limit_x64 = limit_price.map(|price| {
+                price_to_sqrt_price_x64(
+                    price,
+                    pool_state.mint_decimals_0,
+                    pool_state.mint_decimals_1,
+                )
+            });
+            let callback_accounts = callback_account
+                .into_iter()
+                .map(|account| AccountMeta::new(account, false))
+                .collect();
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let flash_swap_instr = flash_swap_instr(
+                &pool_config.clone(),
+                pool_state.amm_config,
+                pool_id,
+                receiver_token,
+                input_vault,
+                output_vault,
+                pool_state.observation_key,
+                input_mint,
+                output_mint,
+                callback_program,
+                callback_accounts,
+                // the bitmap extension, fee override and tick arrays follow the fixed hop accounts
+                hop_accounts[raydium_amm_v3::instructions::ROUTE_HOP_FIXED_ACCOUNTS_LEN..].to_vec(),
+                amount,
+                other_amount_threshold,
+                sqrt_price_limit_x64,
+                base_in,
+                hex::decode(callback_data)?,
+            )?;
+            instructions.extend(flash_swap_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            if simulate {
+                let ret =
+                    simulate_transaction(&rpc_client, &txn, true, CommitmentConfig::confirmed())?;
+                println!("{:#?}", ret);
+            } else {
+                let signature = send_txn(&rpc_client, &txn, true)?;
+                println!("{}", signature);
+            }
+        }
+        CommandsName::FlashLoan {
+            receiver_token_0,
+            receiver_token_1,
+            callback_program,
+            amount_0,
+            amount_1,
+            simulate,
+            callback_account,
+            callback_data,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool_state: PoolState = program.account(pool_id)?;
+            let callback_accounts = callback_account
+                .into_iter()
+                .map(|account| AccountMeta::new(account, false))
+                .collect();
+            let mut remaining_accounts = Vec::new();
+            if pool_state.is_fee_overridden() {
+                remaining_accounts.push(AccountMeta::new_readonly(
+                    raydium_amm_v3::states::PoolFeeOverride::key(pool_id),
+                    false,
+                ));
+            }
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let flash_loan_instr = flash_loan_instr(
+                &pool_config.clone(),
+                pool_state.amm_config,
+                pool_id,
+                receiver_token_0,
+                receiver_token_1,
+                pool_state.token_vault_0,
+                pool_state.token_vault_1,
+                pool_state.token_mint_0,
+                pool_state.token_mint_1,
+                callback_program,
+                callback_accounts,
+                remaining_accounts,
+                amount_0,
+                amount_1,
+                hex::decode(callback_data)?,
+            )?;
+            instructions.extend(flash_loan_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            if simulate {
+                let ret =
+                    simulate_transaction(&rpc_client, &txn, true, CommitmentConfig::confirmed())?;
+                println!("{:#?}", ret);
+            } else {
+                let signature = send_txn(&rpc_client, &txn, true)?;
+                println!("{}", signature);
+            }
+        }
+        CommandsName::PlaceLimitOrder {
+            tick_lower_price,
+            zero_for_one,
+            amount,
+            order_nonce,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let tick_lower_price_x64 = price_to_sqrt_price_x64(
+                tick_lower_price,
+                pool.mint_decimals_0,
+                pool.mint_decimals_1,
+            );
+            let tick_lower_index = tick_with_spacing(
+                tick_math::get_tick_at_sqrt_price(tick_lower_price_x64)?,
+                pool.tick_spacing.into(),
+            );
+            let tick_array_start_index =
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    tick_lower_index,
+                    pool.tick_spacing.into(),
+                );
+            println!(
+                "tick_lower_index:{}, tick_upper_index:{}",
+                tick_lower_index,
+                tick_lower_index + i32::from(pool.tick_spacing)
+            );
+            let (input_vault, input_vault_mint) = if zero_for_one {
+                (pool.token_vault_0, pool.token_mint_0)
+            } else {
+                (pool.token_vault_1, pool.token_mint_1)
+            };
+            let input_token_program = rpc_client.get_account(&input_vault_mint)?.owner;
+            let mut remaining_accounts = Vec::new();
+            remaining_accounts.push(AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            ));
+
+            let place_limit_order_instr = place_limit_order_instr(
+                &pool_config.clone(),
+                pool_id,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &input_vault_mint,
+                    &input_token_program,
+                ),
+                input_vault,
+                input_vault_mint,
+                remaining_accounts,
+                order_nonce,
+                tick_array_start_index,
+                tick_lower_index,
+                zero_for_one,
+                amount,
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &place_limit_order_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::CancelLimitOrder { limit_order } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let order: raydium_amm_v3::states::LimitOrderState = program.account(limit_order)?;
+            let tick_array_start_index =
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    order.tick_lower_index,
+                    pool.tick_spacing.into(),
+                );
+            let (tick_array, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    &tick_array_start_index.to_be_bytes(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let token_program_0 = rpc_client.get_account(&pool.token_mint_0)?.owner;
+            let token_program_1 = rpc_client.get_account(&pool.token_mint_1)?.owner;
+            let mut remaining_accounts = Vec::new();
+            remaining_accounts.push(AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            ));
+
+            let cancel_limit_order_instr = cancel_limit_order_instr(
+                &pool_config.clone(),
+                pool_id,
+                limit_order,
+                tick_array,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &token_program_0,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &token_program_1,
+                ),
+                pool.token_mint_0,
+                pool.token_mint_1,
+                remaining_accounts,
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &cancel_limit_order_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::ClaimLimitOrder { limit_order } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let order: raydium_amm_v3::states::LimitOrderState = program.account(limit_order)?;
+            let tick_array_start_index =
+                raydium_amm_v3::states::TickArrayState::get_array_start_index(
+                    order.tick_lower_index,
+                    pool.tick_spacing.into(),
+                );
+            let (tick_array, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    &tick_array_start_index.to_be_bytes(),
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            // the order bought the other token of the pool
+            let (output_vault, output_vault_mint) = if order.zero_for_one {
+                (pool.token_vault_1, pool.token_mint_1)
+            } else {
+                (pool.token_vault_0, pool.token_mint_0)
+            };
+            let output_token_program = rpc_client.get_account(&output_vault_mint)?.owner;
+            let mut remaining_accounts = Vec::new();
+            remaining_accounts.push(AccountMeta::new(
+                pool_config.tickarray_bitmap_extension.unwrap(),
+                false,
+            ));
+
+            let claim_limit_order_instr = claim_limit_order_instr(
+                &pool_config.clone(),
+                pool_id,
+                limit_order,
+                tick_array,
+                output_vault,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &output_vault_mint,
+                    &output_token_program,
+                ),
+                output_vault_mint,
+                remaining_accounts,
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &claim_limit_order_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::CreateTwammOrderPool { zero_for_one } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let create_instr =
+                create_twamm_order_pool_instr(&pool_config.clone(), pool_id, zero_for_one)?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &create_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::PlaceTwammOrder {
+            zero_for_one,
+            amount,
+            intervals,
+            order_nonce,
+        } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+            let (order_pool, __bump) = Pubkey::find_program_address(
+                &[
+                    raydium_amm_v3::states::TWAMM_ORDER_POOL_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    &[zero_for_one as u8],
+                ],
+                &pool_config.raydium_v3_program,
+            );
+            let (input_vault, input_vault_mint) = if zero_for_one {
+                (pool.token_vault_0, pool.token_mint_0)
+            } else {
+                (pool.token_vault_1, pool.token_mint_1)
+            };
+            let input_token_program = rpc_client.get_account(&input_vault_mint)?.owner;
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            // the order pool must be executed up to the current time before an order is placed
+            let mut execute_instr = load_execute_twamm_instr(
+                &rpc_client,
+                &pool_config,
+                &pool,
+                &tickarray_bitmap_extension,
+                zero_for_one,
+            )?;
+            instructions.append(&mut execute_instr);
+            let mut place_instr = place_twamm_order_instr(
+                &pool_config.clone(),
+                pool_id,
+                order_pool,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &input_vault_mint,
+                    &input_token_program,
+                ),
+                input_vault,
+                input_vault_mint,
+                order_nonce,
+                amount,
+                intervals,
+            )?;
+            instructions.append(&mut place_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::WithdrawTwammOrder { order } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let order_state: raydium_amm_v3::states::TwammOrderState = program.account(order)?;
+            let token_program_0 = rpc_client.get_account(&pool.token_mint_0)?.owner;
+            let token_program_1 = rpc_client.get_account(&pool.token_mint_1)?.owner;
+
+            let withdraw_instr = withdraw_twamm_order_instr(
+                &pool_config.clone(),
+                pool_id,
+                order_state.order_pool,
+                order,
+                pool.token_vault_0,
+                pool.token_vault_1,
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_0,
+                    &token_program_0,
+                ),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &pool.token_mint_1,
+                    &token_program_1,
+                ),
+                pool.token_mint_0,
+                pool.token_mint_1,
+            )?;
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &withdraw_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::ExecuteTwamm { zero_for_one } => {
+            let pool_id = pool_config.pool_id_account.unwrap();
+            let pool: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
+            let tickarray_bitmap_extension: raydium_amm_v3::states::TickArrayBitmapExtension =
+                program.account(pool_config.tickarray_bitmap_extension.unwrap())?;
+
+            let mut instructions = Vec::new();
+            let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
+            instructions.push(request_inits_instr);
+            let mut execute_instr = load_execute_twamm_instr(
+                &rpc_client,
+                &pool_config,
+                &pool,
+                &tickarray_bitmap_extension,
+                zero_for_one,
+            )?;
+            instructions.append(&mut execute_instr);
+            // send
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        CommandsName::SwapRouterIn {
+            input_token,
+            amount_in,
+            amount_out_minimum,
+            pool,
+            output_token,
+            hop_limit_price,
+            hop_amount_out_minimum,
+            simulate,
+            referral_token,
+            referral_fee_bps,
+        } => {
+            if pool.len() != output_token.len()
+                || (!hop_limit_price.is_empty() && hop_limit_price.len() != pool.len())
+                || (!hop_amount_out_minimum.is_empty()
+                    && hop_amount_out_minimum.len() != pool.len())
+            {
+                panic!("the hop arguments must be given for each pool of the route");
             }
+            let user_input_account = rpc_client.get_account(&input_token)?;
+            let user_input_state =
+                StateWithExtensions::<Account>::unpack(&user_input_account.data)?;
+            let input_token_mint = user_input_state.base.mint;
 
-            let (mut other_amount_threshold, mut tick_array_indexs) =
-                utils::get_out_put_amount_and_remaining_accounts(
-                    amount,
-                    sqrt_price_limit_x64,
-                    zero_for_one,
-                    base_in,
-                    &amm_config_state,
+            let mut hop_input_mint = input_token_mint;
+            let mut hop_accounts = Vec::new();
+            let mut hops = Vec::new();
+            for (index, (pool_id, hop_output_token)) in
+                pool.into_iter().zip(output_token.into_iter()).enumerate()
+            {
+                let pool_state: PoolState = program.account(pool_id)?;
+                let tickarray_bitmap_extension: TickArrayBitmapExtension =
+                    program.account(TickArrayBitmapExtension::key(pool_id))?;
+                let (mut accounts, zero_for_one, output_mint) = get_route_hop_accounts(
+                    &pool_config.raydium_v3_program,
+                    pool_id,
                     &pool_state,
                     &tickarray_bitmap_extension,
-                    &mut tick_arrays,
-                )
-                .unwrap();
-            println!(
-                "amount:{}, other_amount_threshold:{}",
-                amount, other_amount_threshold
-            );
-            if base_in {
-                // min out
-                other_amount_threshold =
-                    amount_with_slippage(other_amount_threshold, pool_config.slippage, false);
-            } else {
-                // max in
-                other_amount_threshold =
-                    amount_with_slippage(other_amount_threshold, pool_config.slippage, true);
+                    hop_input_mint,
+                    hop_output_token,
+                )?;
+                hops.push(raydium_amm_v3::instructions::RouteHop {
+                    pool_index: hop_accounts.len() as u8,
+                    account_count: accounts.len() as u8,
+                    zero_for_one,
+                    sqrt_price_limit_x64: hop_limit_price
+                        .get(index)
+                        .map(|price| {
+                            price_to_sqrt_price_x64(
+                                *price,
+                                pool_state.mint_decimals_0,
+                                pool_state.mint_decimals_1,
+                            )
+                        })
+                        .unwrap_or(0),
+                    amount_out_minimum: hop_amount_out_minimum.get(index).copied().unwrap_or(0),
+                });
+                hop_accounts.append(&mut accounts);
+                // output token is the new swap input token
+                hop_input_mint = output_mint;
             }
 
-            let current_or_next_tick_array_key = Pubkey::find_program_address(
-                &[
-                    raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
-                    pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
-                    &tick_array_indexs.pop_front().unwrap().to_be_bytes(),
-                ],
-                &pool_config.raydium_v3_program,
-            )
-            .0;
-            let mut remaining_accounts = Vec::new();
-            remaining_accounts.push(AccountMeta::new_readonly(
-                pool_config.tickarray_bitmap_extension.unwrap(),
-                false,
-            ));
-            let mut accounts = tick_array_indexs
-                .into_iter()
-                .map(|index| {
-                    AccountMeta::new(
-                        Pubkey::find_program_address(
-                            &[
-                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
-                                pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
-                                &index.to_be_bytes(),
-                            ],
-                            &pool_config.raydium_v3_program,
-                        )
-                        .0,
-                        false,
-                    )
-                })
-                .collect();
-            remaining_accounts.append(&mut accounts);
             let mut instructions = Vec::new();
             let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
             instructions.push(request_inits_instr);
-            let swap_instr = swap_instr(
+            let swap_instr = swap_router_base_in_v2_instr(
                 &pool_config.clone(),
-                pool_state.amm_config,
-                pool_config.pool_id_account.unwrap(),
-                if zero_for_one {
-                    pool_state.token_vault_0
-                } else {
-                    pool_state.token_vault_1
-                },
-                if zero_for_one {
-                    pool_state.token_vault_1
-                } else {
-                    pool_state.token_vault_0
-                },
-                pool_state.observation_key,
                 input_token,
-                output_token,
-                current_or_next_tick_array_key,
-                remaining_accounts,
-                amount,
-                other_amount_threshold,
-                sqrt_price_limit_x64,
-                base_in,
-            )
-            .unwrap();
+                input_token_mint,
+                hop_accounts,
+                hops,
+                amount_in,
+                amount_out_minimum,
+                referral_token,
+                referral_fee_bps,
+            )?;
             instructions.extend(swap_instr);
             // send
             let signers = vec![&payer];
@@ -1772,173 +4063,56 @@ fn main() -> Result<()> {
                 println!("{}", signature);
             }
         }
-        CommandsName::SwapV2 {
+        CommandsName::SwapRouterOut {
             input_token,
+            amount_out,
+            amount_in_maximum,
+            pool,
             output_token,
-            base_in,
             simulate,
-            amount,
-            limit_price,
+            referral_token,
+            referral_fee_bps,
         } => {
-            // load mult account
-            let load_accounts = vec![
-                input_token,
-                output_token,
-                pool_config.amm_config_key,
-                pool_config.pool_id_account.unwrap(),
-                pool_config.tickarray_bitmap_extension.unwrap(),
-                pool_config.mint0.unwrap(),
-                pool_config.mint1.unwrap(),
-            ];
-            let rsps = rpc_client.get_multiple_accounts(&load_accounts)?;
-            let epoch = rpc_client.get_epoch_info().unwrap().epoch;
-            let [user_input_account, user_output_account, amm_config_account, pool_account, tickarray_bitmap_extension_account, mint0_account, mint1_account] =
-                array_ref![rsps, 0, 7];
-
-            let user_input_token_data = user_input_account.clone().unwrap().data;
-            let user_input_state = StateWithExtensions::<Account>::unpack(&user_input_token_data)?;
-            let user_output_token_data = user_output_account.clone().unwrap().data;
-            let user_output_state =
-                StateWithExtensions::<Account>::unpack(&user_output_token_data)?;
-            let mint0_data = mint0_account.clone().unwrap().data;
-            let mint0_state = StateWithExtensions::<Mint>::unpack(&mint0_data)?;
-            let mint1_data = mint1_account.clone().unwrap().data;
-            let mint1_state = StateWithExtensions::<Mint>::unpack(&mint1_data)?;
-            let amm_config_state = deserialize_anchor_account::<raydium_amm_v3::states::AmmConfig>(
-                amm_config_account.as_ref().unwrap(),
-            )?;
-            let pool_state = deserialize_anchor_account::<raydium_amm_v3::states::PoolState>(
-                pool_account.as_ref().unwrap(),
-            )?;
-            let tickarray_bitmap_extension =
-                deserialize_anchor_account::<raydium_amm_v3::states::TickArrayBitmapExtension>(
-                    tickarray_bitmap_extension_account.as_ref().unwrap(),
-                )?;
-            let zero_for_one = user_input_state.base.mint == pool_state.token_mint_0
-                && user_output_state.base.mint == pool_state.token_mint_1;
-
-            let transfer_fee = if base_in {
-                if zero_for_one {
-                    get_transfer_fee(&mint0_state, epoch, amount)
-                } else {
-                    get_transfer_fee(&mint1_state, epoch, amount)
-                }
-            } else {
-                0
-            };
-            let amount_specified = amount.checked_sub(transfer_fee).unwrap();
-            // load tick_arrays
-            let mut tick_arrays = load_cur_and_next_five_tick_array(
-                &rpc_client,
-                &pool_config,
-                &pool_state,
-                &tickarray_bitmap_extension,
-                zero_for_one,
-            );
-
-            let mut sqrt_price_limit_x64 = None;
-            if limit_price.is_some() {
-                let sqrt_price_x64 = price_to_sqrt_price_x64(
-                    limit_price.unwrap(),
-                    pool_state.mint_decimals_0,
-                    pool_state.mint_decimals_1,
-                );
-                sqrt_price_limit_x64 = Some(sqrt_price_x64);
+            if pool.len() != output_token.len() {
+                panic!("each pool of the route needs an output token account");
             }
+            let user_input_account = rpc_client.get_account(&input_token)?;
+            let user_input_state =
+                StateWithExtensions::<Account>::unpack(&user_input_account.data)?;
+            let input_token_mint = user_input_state.base.mint;
 
-            let (mut other_amount_threshold, tick_array_indexs) =
-                utils::get_out_put_amount_and_remaining_accounts(
-                    amount_specified,
-                    sqrt_price_limit_x64,
-                    zero_for_one,
-                    base_in,
-                    &amm_config_state,
+            let mut hop_input_mint = input_token_mint;
+            let mut hop_accounts = Vec::new();
+            for (pool_id, hop_output_token) in pool.into_iter().zip(output_token.into_iter()) {
+                let pool_state: PoolState = program.account(pool_id)?;
+                let tickarray_bitmap_extension: TickArrayBitmapExtension =
+                    program.account(TickArrayBitmapExtension::key(pool_id))?;
+                let (mut accounts, _, output_mint) = get_route_hop_accounts(
+                    &pool_config.raydium_v3_program,
+                    pool_id,
                     &pool_state,
                     &tickarray_bitmap_extension,
-                    &mut tick_arrays,
-                )
-                .unwrap();
-            println!(
-                "amount:{}, other_amount_threshold:{}",
-                amount, other_amount_threshold
-            );
-            if base_in {
-                // calc mint out amount with slippage
-                other_amount_threshold =
-                    amount_with_slippage(other_amount_threshold, pool_config.slippage, false);
-            } else {
-                // calc max in with slippage
-                other_amount_threshold =
-                    amount_with_slippage(other_amount_threshold, pool_config.slippage, true);
-                // calc max in with transfer_fee
-                let transfer_fee = if zero_for_one {
-                    get_transfer_inverse_fee(&mint0_state, epoch, other_amount_threshold)
-                } else {
-                    get_transfer_inverse_fee(&mint1_state, epoch, other_amount_threshold)
-                };
-                other_amount_threshold += transfer_fee;
+                    hop_input_mint,
+                    hop_output_token,
+                )?;
+                hop_accounts.append(&mut accounts);
+                // output token is the new swap input token
+                hop_input_mint = output_mint;
             }
 
-            let mut remaining_accounts = Vec::new();
-            remaining_accounts.push(AccountMeta::new_readonly(
-                pool_config.tickarray_bitmap_extension.unwrap(),
-                false,
-            ));
-            let mut accounts = tick_array_indexs
-                .into_iter()
-                .map(|index| {
-                    AccountMeta::new(
-                        Pubkey::find_program_address(
-                            &[
-                                raydium_amm_v3::states::TICK_ARRAY_SEED.as_bytes(),
-                                pool_config.pool_id_account.unwrap().to_bytes().as_ref(),
-                                &index.to_be_bytes(),
-                            ],
-                            &pool_config.raydium_v3_program,
-                        )
-                        .0,
-                        false,
-                    )
-                })
-                .collect();
-            remaining_accounts.append(&mut accounts);
             let mut instructions = Vec::new();
             let request_inits_instr = ComputeBudgetInstruction::set_compute_unit_limit(1400_000u32);
             instructions.push(request_inits_instr);
-            let swap_instr = swap_v2_instr(
+            let swap_instr = swap_router_base_out_instr(
                 &pool_config.clone(),
-                pool_state.amm_config,
-                pool_config.pool_id_account.unwrap(),
-                if zero_for_one {
-                    pool_state.token_vault_0
-                } else {
-                    pool_state.token_vault_1
-                },
-                if zero_for_one {
-                    pool_state.token_vault_1
-                } else {
-                    pool_state.token_vault_0
-                },
-                pool_state.observation_key,
                 input_token,
-                output_token,
-                if zero_for_one {
-                    pool_state.token_mint_0
-                } else {
-                    pool_state.token_mint_1
-                },
-                if zero_for_one {
-                    pool_state.token_mint_1
-                } else {
-                    pool_state.token_mint_0
-                },
-                remaining_accounts,
-                amount,
-                other_amount_threshold,
-                sqrt_price_limit_x64,
-                base_in,
-            )
-            .unwrap();
+                input_token_mint,
+                hop_accounts,
+                amount_out,
+                amount_in_maximum,
+                referral_token,
+                referral_fee_bps,
+            )?;
             instructions.extend(swap_instr);
             // send
             let signers = vec![&payer];
@@ -2265,6 +4439,22 @@ fn main() -> Result<()> {
             println!("pool_id:{}", pool_id);
             let pool_account: raydium_amm_v3::states::PoolState = program.account(pool_id)?;
             println!("{:#?}", pool_account);
+            let amm_config_state: raydium_amm_v3::states::AmmConfig =
+                program.account(pool_account.amm_config)?;
+            let amm_config_state = if pool_account.is_fee_overridden() {
+                let pool_fee_override: raydium_amm_v3::states::PoolFeeOverride =
+                    program.account(raydium_amm_v3::states::PoolFeeOverride::key(pool_id))?;
+                println!("{:#?}", pool_fee_override);
+                pool_fee_override.apply(&amm_config_state)
+            } else {
+                amm_config_state
+            };
+            println!(
+                "effective fees, trade_fee_flat:{}, protocol_fee_rate:{}, fund_fee_rate:{}",
+                amm_config_state.trade_fee_flat,
+                amm_config_state.protocol_fee_rate,
+                amm_config_state.fund_fee_rate
+            );
         }
         CommandsName::PBitmapExtension { bitmap_extension } => {
             let bitmap_extension = if let Some(bitmap_extension) = bitmap_extension {
@@ -2286,6 +4476,15 @@ fn main() -> Result<()> {
             let personal_account: raydium_amm_v3::states::PersonalPositionState =
                 program.account(personal_id)?;
             println!("{:#?}", personal_account);
+            let block_timestamp = rpc_client.get_block_time(rpc_client.get_slot()?)? as u64;
+            if personal_account.is_locked(block_timestamp) {
+                println!(
+                    "locked until:{}, lock_authority:{}",
+                    personal_account.unlock_timestamp, personal_account.lock_authority
+                );
+            } else {
+                println!("unlocked");
+            }
         }
         CommandsName::DecodeInstruction { instr_hex_data } => {
             handle_program_instruction(&instr_hex_data, InstructionDecodeType::BaseHex)?;
@@ -107,4 +107,62 @@ pub enum ErrorCode {
     CalculateOverflow,
     #[msg("TransferFee calculate not match")]
     TransferFeeCalculateNotMatch,
+    #[msg("Invalid trade fee mode")]
+    InvalidTradeFeeMode,
+    #[msg("Invalid fee accounting mode")]
+    InvalidFeeAccountingMode,
+    #[msg("Missing pool fee override account")]
+    MissingPoolFeeOverrideAccount,
+    #[msg("Referral fee exceeds the maximum allowed by the amm config")]
+    ReferralFeeTooHigh,
+    #[msg("Invalid referral token account")]
+    InvalidReferralTokenAccount,
+    #[msg("Invalid launch fee decay mode")]
+    InvalidLaunchFeeDecayMode,
+    #[msg("The pool is not open for swap yet")]
+    PoolNotOpen,
+    #[msg("The router requires at least one swap hop")]
+    InvalidRouterHops,
+    #[msg("The swap price deviates too far from the oracle TWAP price")]
+    PriceDeviationTooHigh,
+    #[msg("The flash swap callback did not pay the input amount owed")]
+    FlashSwapInputNotPaid,
+    #[msg("The flash loan callback did not repay the loan and the fee")]
+    FlashLoanNotRepaid,
+    #[msg("Limit order range must span one tick spacing inside a tick array, on the sold token side of the price")]
+    InvalidLimitOrderRange,
+    #[msg("Limit order was already filled")]
+    LimitOrderFilled,
+    #[msg("Limit order is not filled yet")]
+    LimitOrderNotFilled,
+    #[msg("TWAMM order duration must be between one and the max number of intervals")]
+    InvalidTwammOrderDuration,
+    #[msg("The expiry bucket still holds orders of an earlier expiry")]
+    TwammExpiryInUse,
+    #[msg("The TWAMM order pool must be executed up to the current time first")]
+    TwammOrderPoolNotExecuted,
+    #[msg("Hook program is missing or doesn't match the pool")]
+    InvalidHookProgram,
+    #[msg("Invalid hook permissions")]
+    InvalidHookPermissions,
+    #[msg("Not supported on pools with swap hooks")]
+    HookNotSupported,
+    #[msg("The circuit breaker paused the swaps in this direction")]
+    CircuitBreakerTripped,
+    #[msg("The new range of the position must differ from the current range")]
+    SameTickRange,
+    #[msg("The liquidity of the position is locked")]
+    PositionLocked,
+    #[msg("The unlock time must be later than the current time and the current unlock time")]
+    InvalidUnlockTimestamp,
+    #[msg("The fee receiver account is required when the pool creation fee is not zero")]
+    MissingFeeReceiver,
+    #[msg("The open time is too far after the pool creation")]
+    InvalidOpenTime,
+    #[msg("The launch fee or the launch window exceeds the maximum")]
+    InvalidLaunchFee,
+    #[msg("The callback program can't be this program")]
+    InvalidCallbackProgram,
+    #[msg("The protocol, fund and creator fee rates exceed the trade fee")]
+    InvalidFeeRates,
 }
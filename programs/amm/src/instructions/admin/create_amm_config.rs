@@ -34,14 +34,19 @@ pub fn create_amm_config(
     index: u16,
     tick_spacing: u16,
     trade_fee_flat: u64,
+    trade_fee_rate: u32,
+    trade_fee_mode: u8,
     protocol_fee_rate: u32,
     fund_fee_rate: u32,
 ) -> Result<()> {
+    TradeFeeMode::from_u8(trade_fee_mode)?;
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.owner = ctx.accounts.owner.key();
     amm_config.bump = ctx.bumps.amm_config;
     amm_config.index = index;
     amm_config.trade_fee_flat = trade_fee_flat;
+    amm_config.trade_fee_rate = trade_fee_rate;
+    amm_config.trade_fee_mode = trade_fee_mode;
     amm_config.protocol_fee_rate = protocol_fee_rate;
     amm_config.tick_spacing = tick_spacing;
     amm_config.fund_fee_rate = fund_fee_rate;
@@ -55,6 +60,15 @@ pub fn create_amm_config(
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        trade_fee_rate: amm_config.trade_fee_rate,
+        trade_fee_mode: amm_config.trade_fee_mode,
+        fee_accounting_mode: amm_config.fee_accounting_mode,
+        dynamic_fee_base: amm_config.dynamic_fee_base,
+        dynamic_fee_max: amm_config.dynamic_fee_max,
+        dynamic_fee_sensitivity: amm_config.dynamic_fee_sensitivity,
+        referral_fee_bps_max: amm_config.referral_fee_bps_max,
+        creator_fee_rate: amm_config.creator_fee_rate,
+        pool_creation_fee: amm_config.pool_creation_fee,
     });
 
     Ok(())
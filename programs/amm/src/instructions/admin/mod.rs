@@ -24,3 +24,18 @@ pub use update_pool_status::*;
 
 pub mod create_support_mint_associated;
 pub use create_support_mint_associated::*;
+
+pub mod set_pool_fee_override;
+pub use set_pool_fee_override::*;
+
+pub mod update_pool_price_guard;
+pub use update_pool_price_guard::*;
+
+pub mod update_pool_flash_loan_fee;
+pub use update_pool_flash_loan_fee::*;
+
+pub mod update_pool_circuit_breaker;
+pub use update_pool_circuit_breaker::*;
+
+pub mod update_pool_hook;
+pub use update_pool_hook::*;
@@ -0,0 +1,80 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+pub struct SetPoolFeeOverride<'info> {
+    /// The amm config owner or admin
+    #[account(
+        mut,
+        constraint = (owner.key() == amm_config.owner || owner.key() == crate::admin::ID) @ ErrorCode::NotApproved
+    )]
+    pub owner: Signer<'info>,
+
+    /// The amm config of the pool
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Initialize the override account of the pool on the first call
+    #[account(
+        init_if_needed,
+        seeds = [
+            POOL_FEE_OVERRIDE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = PoolFeeOverride::LEN
+    )]
+    pub pool_fee_override: Account<'info, PoolFeeOverride>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_pool_fee_override(
+    ctx: Context<SetPoolFeeOverride>,
+    trade_fee_flat: Option<u64>,
+    protocol_fee_rate: Option<u32>,
+    fund_fee_rate: Option<u32>,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_fee_override = ctx.accounts.pool_fee_override.deref_mut();
+    pool_fee_override.bump = ctx.bumps.pool_fee_override;
+    pool_fee_override.pool_id = pool_id;
+
+    let mut override_flags = 0u8;
+    if let Some(trade_fee_flat) = trade_fee_flat {
+        override_flags |= 1 << (PoolFeeOverrideBitIndex::TradeFeeFlat as u8);
+        pool_fee_override.trade_fee_flat = trade_fee_flat;
+    }
+    if let Some(protocol_fee_rate) = protocol_fee_rate {
+        assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+        override_flags |= 1 << (PoolFeeOverrideBitIndex::ProtocolFeeRate as u8);
+        pool_fee_override.protocol_fee_rate = protocol_fee_rate;
+    }
+    if let Some(fund_fee_rate) = fund_fee_rate {
+        assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+        override_flags |= 1 << (PoolFeeOverrideBitIndex::FundFeeRate as u8);
+        pool_fee_override.fund_fee_rate = fund_fee_rate;
+    }
+    pool_fee_override.override_flags = override_flags;
+
+    pool_fee_override.check_fee_rates(&ctx.accounts.amm_config)?;
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.fee_override = (override_flags != 0) as u8;
+
+    emit!(PoolFeeOverrideChangeEvent {
+        pool_state: pool_id,
+        override_flags,
+        trade_fee_flat: pool_fee_override.trade_fee_flat,
+        protocol_fee_rate: pool_fee_override.protocol_fee_rate,
+        fund_fee_rate: pool_fee_override.fund_fee_rate,
+    });
+
+    Ok(())
+}
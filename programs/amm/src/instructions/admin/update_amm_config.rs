@@ -11,15 +11,23 @@ pub struct UpdateAmmConfig<'info> {
     /// Amm config account to be changed
     #[account(mut)]
     pub amm_config: Account<'info, AmmConfig>,
+    // remaining accounts
+    // pool_fee_override_1: the fee override accounts of the pools of the config when a fee rate is updated
+    // pool_fee_override_2
+    // pool_fee_override_...
 }
 
-pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
+pub fn update_amm_config<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, UpdateAmmConfig<'info>>,
+    param: u8,
+    value: u64,
+) -> Result<()> {
     let amm_config = &mut ctx.accounts.amm_config;
     let match_param = Some(param);
     match match_param {
         Some(0) => update_trade_fee_flat(amm_config, value),
-        Some(1) => update_protocol_fee_rate(amm_config, value),
-        Some(2) => update_fund_fee_rate(amm_config, value),
+        Some(1) => update_protocol_fee_rate(amm_config, value as u32),
+        Some(2) => update_fund_fee_rate(amm_config, value as u32),
         Some(3) => {
             let new_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
             set_new_owner(amm_config, new_owner);
@@ -28,8 +36,23 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
             let new_fund_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
             set_new_fund_owner(amm_config, new_fund_owner);
         }
+        Some(5) => update_trade_fee_rate(amm_config, value as u32),
+        Some(6) => update_trade_fee_mode(amm_config, value as u8)?,
+        Some(7) => update_fee_accounting_mode(amm_config, value as u8)?,
+        Some(8) => update_dynamic_fee_base(amm_config, value as u32),
+        Some(9) => update_dynamic_fee_max(amm_config, value as u32),
+        Some(10) => update_dynamic_fee_sensitivity(amm_config, value as u32),
+        Some(11) => update_referral_fee_bps_max(amm_config, value as u16),
+        Some(12) => update_creator_fee_rate(amm_config, value as u32),
+        Some(13) => update_pool_creation_fee(amm_config, value),
         _ => return err!(ErrorCode::InvalidUpdateConfigFlag),
     }
+    // the fee rates carved out of the trade fee must stay valid for the pools overriding some of them
+    if matches!(param, 1 | 2 | 12) {
+        for account_info in ctx.remaining_accounts.iter() {
+            Account::<PoolFeeOverride>::try_from(account_info)?.check_fee_rates(amm_config)?;
+        }
+    }
 
     emit!(ConfigChangeEvent {
         index: amm_config.index,
@@ -39,6 +62,15 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        trade_fee_rate: amm_config.trade_fee_rate,
+        trade_fee_mode: amm_config.trade_fee_mode,
+        fee_accounting_mode: amm_config.fee_accounting_mode,
+        dynamic_fee_base: amm_config.dynamic_fee_base,
+        dynamic_fee_max: amm_config.dynamic_fee_max,
+        dynamic_fee_sensitivity: amm_config.dynamic_fee_sensitivity,
+        referral_fee_bps_max: amm_config.referral_fee_bps_max,
+        creator_fee_rate: amm_config.creator_fee_rate,
+        pool_creation_fee: amm_config.pool_creation_fee,
     });
 
     Ok(())
@@ -46,7 +78,10 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
 
 fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u32) {
     assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        protocol_fee_rate + amm_config.fund_fee_rate + amm_config.creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.protocol_fee_rate = protocol_fee_rate;
 }
 
@@ -54,9 +89,49 @@ fn update_trade_fee_flat(amm_config: &mut Account<AmmConfig>, trade_fee_flat: u6
     amm_config.trade_fee_flat = trade_fee_flat;
 }
 
+fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u32) {
+    assert!(trade_fee_rate + amm_config.dynamic_fee_max < FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.trade_fee_rate = trade_fee_rate;
+}
+
+fn update_dynamic_fee_base(amm_config: &mut Account<AmmConfig>, dynamic_fee_base: u32) {
+    amm_config.dynamic_fee_base = dynamic_fee_base;
+}
+
+fn update_dynamic_fee_max(amm_config: &mut Account<AmmConfig>, dynamic_fee_max: u32) {
+    assert!(dynamic_fee_max < FEE_RATE_DENOMINATOR_VALUE);
+    assert!(dynamic_fee_max + amm_config.trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.dynamic_fee_max = dynamic_fee_max;
+}
+
+fn update_trade_fee_mode(amm_config: &mut Account<AmmConfig>, trade_fee_mode: u8) -> Result<()> {
+    TradeFeeMode::from_u8(trade_fee_mode)?;
+    amm_config.trade_fee_mode = trade_fee_mode;
+    Ok(())
+}
+
+fn update_dynamic_fee_sensitivity(
+    amm_config: &mut Account<AmmConfig>,
+    dynamic_fee_sensitivity: u32,
+) {
+    amm_config.dynamic_fee_sensitivity = dynamic_fee_sensitivity;
+}
+
+fn update_fee_accounting_mode(
+    amm_config: &mut Account<AmmConfig>,
+    fee_accounting_mode: u8,
+) -> Result<()> {
+    FeeAccountingMode::from_u8(fee_accounting_mode)?;
+    amm_config.fee_accounting_mode = fee_accounting_mode;
+    Ok(())
+}
+
 fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u32) {
     assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        fund_fee_rate + amm_config.protocol_fee_rate + amm_config.creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.fund_fee_rate = fund_fee_rate;
 }
 
@@ -79,3 +154,31 @@ fn set_new_fund_owner(amm_config: &mut Account<AmmConfig>, new_fund_owner: Pubke
     );
     amm_config.fund_owner = new_fund_owner;
 }
+
+fn update_referral_fee_bps_max(amm_config: &mut Account<AmmConfig>, referral_fee_bps_max: u16) {
+    assert!(referral_fee_bps_max <= REFERRAL_FEE_BPS_DENOMINATOR);
+    // the referral share is carved out of the trade fee alongside the protocol fee and fund fee
+    assert!(
+        u64::from(referral_fee_bps_max) * u64::from(FEE_RATE_DENOMINATOR_VALUE)
+            / u64::from(REFERRAL_FEE_BPS_DENOMINATOR)
+            + u64::from(amm_config.protocol_fee_rate)
+            + u64::from(amm_config.fund_fee_rate)
+            + u64::from(amm_config.creator_fee_rate)
+            <= u64::from(FEE_RATE_DENOMINATOR_VALUE)
+    );
+    amm_config.referral_fee_bps_max = referral_fee_bps_max;
+}
+
+fn update_creator_fee_rate(amm_config: &mut Account<AmmConfig>, creator_fee_rate: u32) {
+    assert!(creator_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        creator_fee_rate + amm_config.protocol_fee_rate + amm_config.fund_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
+    amm_config.creator_fee_rate = creator_fee_rate;
+}
+
+fn update_pool_creation_fee(amm_config: &mut Account<AmmConfig>, pool_creation_fee: u64) {
+    assert!(pool_creation_fee <= u64::from(u32::MAX));
+    amm_config.pool_creation_fee = pool_creation_fee as u32;
+}
@@ -0,0 +1,30 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolCircuitBreaker<'info> {
+    #[account(
+        address = crate::admin::ID
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_circuit_breaker(
+    ctx: Context<UpdatePoolCircuitBreaker>,
+    max_ticks: u32,
+    window_seconds: u32,
+    cooldown_seconds: u32,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    // also resets a tripped breaker
+    pool_state.initialize_circuit_breaker(
+        max_ticks,
+        window_seconds,
+        cooldown_seconds,
+        Clock::get()?.unix_timestamp as u32,
+    );
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolFlashLoanFee<'info> {
+    #[account(
+        address = crate::admin::ID
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_flash_loan_fee(
+    ctx: Context<UpdatePoolFlashLoanFee>,
+    flash_loan_fee_flat: u64,
+    flash_loan_fee_rate: u32,
+    flash_loan_fee_mode: u8,
+) -> Result<()> {
+    assert!(flash_loan_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+    TradeFeeMode::from_u8(flash_loan_fee_mode)?;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.flash_loan_fee_flat = flash_loan_fee_flat;
+    pool_state.flash_loan_fee_rate = flash_loan_fee_rate;
+    pool_state.flash_loan_fee_mode = flash_loan_fee_mode;
+    Ok(())
+}
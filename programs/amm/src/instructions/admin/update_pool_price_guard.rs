@@ -0,0 +1,24 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolPriceGuard<'info> {
+    #[account(
+        address = crate::admin::ID
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_price_guard(
+    ctx: Context<UpdatePoolPriceGuard>,
+    max_deviation_ticks: u32,
+    twap_window_seconds: u32,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.max_deviation_ticks = max_deviation_ticks;
+    pool_state.twap_window_seconds = twap_window_seconds;
+    Ok(())
+}
@@ -47,6 +47,12 @@ pub struct ClosePosition<'info> {
 pub fn close_position<'a, 'b, 'c, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, ClosePosition<'info>>,
 ) -> Result<()> {
+    require!(
+        !ctx.accounts
+            .personal_position
+            .is_locked(u64::try_from(Clock::get()?.unix_timestamp).unwrap()),
+        ErrorCode::PositionLocked
+    );
     if ctx.accounts.personal_position.liquidity != 0
         || ctx.accounts.personal_position.token_fees_owed_0 != 0
         || ctx.accounts.personal_position.token_fees_owed_1 != 0
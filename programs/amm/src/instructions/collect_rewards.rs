@@ -0,0 +1,150 @@
+use super::collect_fees::update_position_fees_and_rewards;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CollectRewards<'info> {
+    /// The position owner or delegated authority
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+        token::authority = nft_owner,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Collect rewards for this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores the lower tick of the position
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores the upper tick of the position
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining accounts, for each of the reward_indexes in order
+    // reward_token_vault, recipient_token_account, reward_vault_mint
+}
+
+pub fn collect_position_rewards<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CollectRewards<'info>>,
+    reward_indexes: Vec<u8>,
+) -> Result<()> {
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        reward_indexes.len() * 3,
+        ErrorCode::InvalidRewardInputAccountNumber
+    );
+    {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectReward) {
+            return err!(ErrorCode::NotApproved);
+        }
+    }
+    update_position_fees_and_rewards(
+        &ctx.accounts.pool_state,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+    )?;
+
+    let personal_position = &mut ctx.accounts.personal_position;
+    let mut reward_amounts: [u64; REWARD_NUM] = [0; REWARD_NUM];
+    let mut collected = [false; REWARD_NUM];
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    for reward_index in reward_indexes {
+        let i = usize::from(reward_index);
+        require_gt!(REWARD_NUM, i, ErrorCode::InvalidRewardIndex);
+        require!(!collected[i], ErrorCode::InvalidRewardIndex);
+        collected[i] = true;
+
+        let reward_token_vault =
+            InterfaceAccount::<TokenAccount>::try_from(remaining_accounts.next().unwrap())?;
+        let recipient_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(remaining_accounts.next().unwrap())?;
+        let reward_vault_mint = Box::new(InterfaceAccount::<Mint>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        {
+            let pool_state = ctx.accounts.pool_state.load()?;
+            require!(
+                pool_state.reward_infos[i].initialized(),
+                ErrorCode::InvalidRewardIndex
+            );
+            require_keys_eq!(
+                reward_token_vault.key(),
+                pool_state.reward_infos[i].token_vault
+            );
+        }
+        require_keys_eq!(reward_token_vault.mint, recipient_token_account.mint);
+        require_keys_eq!(reward_token_vault.mint, reward_vault_mint.key());
+
+        let reward_amount_owed = personal_position.reward_infos[i].reward_amount_owed;
+        if reward_amount_owed == 0 {
+            continue;
+        }
+        ctx.accounts
+            .pool_state
+            .load()?
+            .check_unclaimed_reward(i, reward_amount_owed)?;
+
+        let transfer_amount = reward_amount_owed.min(reward_token_vault.amount);
+        if transfer_amount > 0 {
+            personal_position.reward_infos[i].reward_amount_owed =
+                reward_amount_owed.checked_sub(transfer_amount).unwrap();
+            ctx.accounts
+                .pool_state
+                .load_mut()?
+                .add_reward_clamed(i, transfer_amount)?;
+
+            transfer_from_pool_vault_to_user(
+                &ctx.accounts.pool_state,
+                &reward_token_vault.to_account_info(),
+                &recipient_token_account.to_account_info(),
+                Some(reward_vault_mint),
+                &ctx.accounts.token_program,
+                Some(ctx.accounts.token_program_2022.to_account_info()),
+                transfer_amount,
+            )?;
+        }
+        reward_amounts[i] = transfer_amount;
+    }
+
+    emit!(CollectPersonalRewardEvent {
+        position_nft_mint: personal_position.nft_mint,
+        reward_amounts,
+    });
+
+    Ok(())
+}
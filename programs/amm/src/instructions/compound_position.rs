@@ -0,0 +1,222 @@
+use super::{add_liquidity_to_ticks, update_position_fees_and_rewards};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct CompoundPosition<'info> {
+    /// The position owner, or the delegate of the position nft account
+    pub authority: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+        constraint = nft_account.owner == authority.key()
+            || nft_account.delegate.contains(&authority.key()) @ ErrorCode::NotApproved,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Compound the fees of this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+    // remaining account
+    // #[account(
+    //     seeds = [
+    //         POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+    //         pool_state.key().as_ref(),
+    //     ],
+    //     bump
+    // )]
+    // pub tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+/// Adds the fees owed to the position back to its liquidity, the tokens stay in the vaults.
+/// The part of the fees that doesn't fit the price ratio of the range stays owed
+pub fn compound_position<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CompoundPosition<'info>>,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let protocol_position = &mut ctx.accounts.protocol_position;
+    let personal_position = &mut ctx.accounts.personal_position;
+    let tick_lower = personal_position.tick_lower_index;
+    let tick_upper = personal_position.tick_upper_index;
+    let owner = ctx.accounts.nft_account.owner;
+
+    let (remaining_accounts, hook_accounts) = split_hook_accounts(
+        pool_state_loader,
+        ctx.remaining_accounts,
+        &MODIFY_POSITION_HOOK_PERMISSIONS,
+    )?;
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeModifyPosition,
+        BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &BeforeModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner,
+            tick_lower_index: tick_lower,
+            tick_upper_index: tick_upper,
+            liquidity_delta: 0,
+        },
+    )?;
+    {
+        let pool_state = pool_state_loader.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+    }
+    update_position_fees_and_rewards(
+        pool_state_loader,
+        protocol_position,
+        personal_position,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+    )?;
+
+    let (liquidity, amount_0, amount_1) = {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let liquidity = liquidity_math::get_liquidity_from_amounts(
+            pool_state.sqrt_price_x64,
+            tick_math::get_sqrt_price_at_tick(tick_lower)?,
+            tick_math::get_sqrt_price_at_tick(tick_upper)?,
+            personal_position.token_fees_owed_0,
+            personal_position.token_fees_owed_1,
+        );
+        require_gt!(liquidity, 0, ErrorCode::ForbidBothZeroForSupplyLiquidity);
+
+        let use_tickarray_bitmap_extension =
+            pool_state.is_overflow_default_tickarray_bitmap(vec![tick_lower, tick_upper]);
+        let (amount_0, amount_1) = add_liquidity_to_ticks(
+            &AccountLoad::<TickArrayState>::try_from(
+                &ctx.accounts.tick_array_lower.to_account_info(),
+            )?,
+            &AccountLoad::<TickArrayState>::try_from(
+                &ctx.accounts.tick_array_upper.to_account_info(),
+            )?,
+            protocol_position,
+            if use_tickarray_bitmap_extension {
+                require_keys_eq!(
+                    remaining_accounts[0].key(),
+                    TickArrayBitmapExtension::key(pool_state_loader.key())
+                );
+                Some(&remaining_accounts[0])
+            } else {
+                None
+            },
+            pool_state,
+            liquidity,
+            tick_lower,
+            tick_upper,
+        )?;
+
+        // the fees become the reserves of the added liquidity
+        require_gte!(personal_position.token_fees_owed_0, amount_0);
+        require_gte!(personal_position.token_fees_owed_1, amount_1);
+        personal_position.token_fees_owed_0 -= amount_0;
+        personal_position.token_fees_owed_1 -= amount_1;
+        pool_state.total_fees_claimed_token_0 = pool_state
+            .total_fees_claimed_token_0
+            .checked_add(amount_0)
+            .unwrap();
+        pool_state.total_fees_claimed_token_1 = pool_state
+            .total_fees_claimed_token_1
+            .checked_add(amount_1)
+            .unwrap();
+        (liquidity, amount_0, amount_1)
+    };
+    personal_position.liquidity = personal_position.liquidity.checked_add(liquidity).unwrap();
+
+    emit!(CompoundPositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        amount_0,
+        amount_1,
+    });
+
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::AfterModifyPosition,
+        AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &AfterModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner,
+            tick_lower_index: tick_lower,
+            tick_upper_index: tick_upper,
+            liquidity_delta: i128::try_from(liquidity).unwrap(),
+            amount_0,
+            amount_1,
+        },
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod compound_position_test {
+    use super::*;
+
+    #[test]
+    fn compounded_amounts_within_fees_owed_test() {
+        let tick_lower = -120;
+        let tick_upper = 240;
+        for (tick_current, fees_owed_0, fees_owed_1) in [
+            (0, 1_000_003u64, 999_999u64),
+            (-500, 12_345, 0),
+            (500, 0, 67_891),
+            (100, 7, 1_000_000_000),
+            (-60, u32::MAX as u64, 3),
+        ] {
+            let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(tick_current).unwrap();
+            let liquidity = liquidity_math::get_liquidity_from_amounts(
+                sqrt_price_x64,
+                tick_math::get_sqrt_price_at_tick(tick_lower).unwrap(),
+                tick_math::get_sqrt_price_at_tick(tick_upper).unwrap(),
+                fees_owed_0,
+                fees_owed_1,
+            );
+            let (amount_0, amount_1) = liquidity_math::get_delta_amounts_signed(
+                tick_current,
+                sqrt_price_x64,
+                tick_lower,
+                tick_upper,
+                i128::try_from(liquidity).unwrap(),
+            )
+            .unwrap();
+            assert!(amount_0 <= fees_owed_0);
+            assert!(amount_1 <= fees_owed_1);
+        }
+    }
+}
@@ -1,8 +1,7 @@
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::{libraries::tick_math, util};
-use crate::states::POOL_CREATION_FEE_LAMPORTS;
-use anchor_lang::{prelude::*, solana_program};
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 // use solana_program::{program::invoke_signed, system_instruction};
 #[derive(Accounts)]
@@ -108,6 +107,12 @@ pub struct CreatePool<'info> {
     pub system_program: Program<'info, System>,
     /// Sysvar for program account
     pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Receives the pool creation fee, only required when the fee is not zero
+    #[account(
+        mut,
+        address = crate::fee_receiver::ID
+    )]
+    pub fee_receiver: Option<UncheckedAccount<'info>>,
     // remaining account
     // #[account(
     //     seeds = [
@@ -128,9 +133,14 @@ pub struct CreatePool<'info> {
     // pub support_mint1_associated: Account<'info, SupportMintAssociated>,
 }
 
-pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u64) -> Result<()> {
-    // creation fee is set to zero by default. the constant is kept for compatibility
-    let _creation_fee = POOL_CREATION_FEE_LAMPORTS;
+pub fn create_pool(
+    ctx: Context<CreatePool>,
+    sqrt_price_x64: u128,
+    open_time: u64,
+    launch_fee_flat: u64,
+    launch_fee_duration: u32,
+    launch_fee_decay_mode: u8,
+) -> Result<()> {
     let mint0_associated_is_initialized = util::support_mint_associated_is_initialized(
         &ctx.remaining_accounts,
         &ctx.accounts.token_mint_0,
@@ -146,8 +156,21 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
     {
         return err!(ErrorCode::NotSupportMint);
     }
-    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
-    require_gt!(block_timestamp, open_time);
+    let pool_creation_fee = ctx.accounts.amm_config.pool_creation_fee;
+    if let Some(fee_receiver) =
+        get_fee_receiver(pool_creation_fee, ctx.accounts.fee_receiver.as_ref())?
+    {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.pool_creator.to_account_info(),
+                    to: fee_receiver.to_account_info(),
+                },
+            ),
+            pool_creation_fee.into(),
+        )?;
+    }
     let pool_id = ctx.accounts.pool_state.key();
     let mut pool_state = ctx.accounts.pool_state.load_init()?;
 
@@ -168,7 +191,7 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
     pool_state.initialize(
         bump,
         sqrt_price_x64,
-        0,
+        open_time,
         tick,
         ctx.accounts.pool_creator.key(),
         ctx.accounts.token_vault_0.key(),
@@ -178,6 +201,12 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
         ctx.accounts.token_mint_1.as_ref(),
         ctx.accounts.observation_state.key(),
     )?;
+    pool_state.initialize_launch_fee(
+        launch_fee_flat,
+        launch_fee_duration,
+        launch_fee_decay_mode,
+        u64::try_from(Clock::get()?.unix_timestamp).unwrap(),
+    )?;
 
     ctx.accounts
         .tick_array_bitmap
@@ -196,3 +225,34 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
     });
     Ok(())
 }
+
+/// Returns the account to pay the pool creation fee to, none if there is no fee to charge
+fn get_fee_receiver<T>(pool_creation_fee: u32, fee_receiver: Option<T>) -> Result<Option<T>> {
+    if pool_creation_fee == 0 {
+        return Ok(None);
+    }
+    require!(fee_receiver.is_some(), ErrorCode::MissingFeeReceiver);
+    Ok(fee_receiver)
+}
+
+#[cfg(test)]
+mod create_pool_test {
+    use super::*;
+
+    #[test]
+    fn get_fee_receiver_test() {
+        let fee_receiver = Pubkey::new_unique();
+        // no fee, the account isn't needed and isn't charged even if passed
+        assert_eq!(get_fee_receiver::<Pubkey>(0, None).unwrap(), None);
+        assert_eq!(get_fee_receiver(0, Some(fee_receiver)).unwrap(), None);
+        // a fee requires the account
+        assert_eq!(
+            get_fee_receiver(1_000_000, Some(fee_receiver)).unwrap(),
+            Some(fee_receiver)
+        );
+        assert_eq!(
+            get_fee_receiver::<Pubkey>(1_000_000, None).unwrap_err(),
+            ErrorCode::MissingFeeReceiver.into()
+        );
+    }
+}
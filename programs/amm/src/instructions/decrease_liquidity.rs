@@ -2,7 +2,11 @@ use super::calculate_latest_token_fees;
 use super::modify_position;
 use crate::error::ErrorCode;
 use crate::states::*;
-use crate::util::{self, transfer_from_pool_vault_to_user};
+use crate::util::{
+    self, invoke_hook, split_hook_accounts, transfer_from_pool_vault_to_user,
+    AfterModifyPositionHook, BeforeModifyPositionHook, AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+    BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 use anchor_spl::token_2022::spl_token_2022;
@@ -101,6 +105,7 @@ pub fn decrease_liquidity_v1<'a, 'b, 'c: 'info, 'info>(
     amount_1_min: u64,
 ) -> Result<()> {
     decrease_liquidity(
+        &ctx.accounts.nft_owner,
         &ctx.accounts.pool_state,
         &mut ctx.accounts.protocol_position,
         &mut ctx.accounts.personal_position,
@@ -123,6 +128,7 @@ pub fn decrease_liquidity_v1<'a, 'b, 'c: 'info, 'info>(
 }
 
 pub fn decrease_liquidity<'a, 'b, 'c: 'info, 'info>(
+    nft_owner: &'b Signer<'info>,
     pool_state_loader: &'b AccountLoader<'info, PoolState>,
     protocol_position: &'b mut Box<Account<'info, ProtocolPositionState>>,
     personal_position: &'b mut Box<Account<'info, PersonalPositionState>>,
@@ -147,6 +153,32 @@ pub fn decrease_liquidity<'a, 'b, 'c: 'info, 'info>(
     //     invoke_memo_instruction(DECREASE_MEMO_MSG, memp_program)?;
     // }
     assert!(liquidity <= personal_position.liquidity);
+    // the fees and rewards of a locked position can still be collected
+    if liquidity > 0 {
+        require!(
+            !personal_position.is_locked(u64::try_from(Clock::get()?.unix_timestamp).unwrap()),
+            ErrorCode::PositionLocked
+        );
+    }
+    let (remaining_accounts, hook_accounts) = split_hook_accounts(
+        pool_state_loader,
+        remaining_accounts,
+        &MODIFY_POSITION_HOOK_PERMISSIONS,
+    )?;
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeModifyPosition,
+        BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &BeforeModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: nft_owner.key(),
+            tick_lower_index: personal_position.tick_lower_index,
+            tick_upper_index: personal_position.tick_upper_index,
+            liquidity_delta: -i128::try_from(liquidity).unwrap(),
+        },
+    )?;
+
     let liquidity_before;
     let pool_sqrt_price_x64;
     let pool_tick_current;
@@ -155,6 +187,7 @@ pub fn decrease_liquidity<'a, 'b, 'c: 'info, 'info>(
     let remaining_collect_accounts = &mut Vec::new();
     {
         let pool_state = pool_state_loader.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
         if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity)
             && !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
             && !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectReward)
@@ -296,6 +329,22 @@ pub fn decrease_liquidity<'a, 'b, 'c: 'info, 'info>(
         transfer_fee_1: transfer_fee_1,
     });
 
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::AfterModifyPosition,
+        AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &AfterModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: nft_owner.key(),
+            tick_lower_index: personal_position.tick_lower_index,
+            tick_upper_index: personal_position.tick_upper_index,
+            liquidity_delta: -i128::try_from(liquidity).unwrap(),
+            amount_0: decrease_amount_0,
+            amount_1: decrease_amount_1,
+        },
+    )?;
+
     Ok(())
 }
 
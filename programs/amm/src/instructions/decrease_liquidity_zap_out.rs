@@ -0,0 +1,358 @@
+use super::{
+    check_unclaimed_fees_and_vault, decrease_liquidity_and_update_position,
+    emit_circuit_breaker_tripped, swap_internal_with_state,
+};
+use crate::error::ErrorCode;
+use crate::libraries::tick_math;
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+#[derive(Accounts)]
+pub struct DecreaseLiquidityZapOut<'info> {
+    /// The position owner
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+        token::authority = nft_owner,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Decrease liquidity for this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The destination token account, its mint selects the token received
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == token_vault_0.mint
+            || recipient_token_account.mint == token_vault_1.mint
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+/// Burns liquidity of the position and collects its fees, the token that isn't wanted is
+/// swapped through the pool and the total is sent as a single token to the recipient
+pub fn decrease_liquidity_zap_out<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, DecreaseLiquidityZapOut<'info>>,
+    liquidity: u128,
+    amount_out_min: u64,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let personal_position = &mut ctx.accounts.personal_position;
+    require_gte!(personal_position.liquidity, liquidity);
+    check_position_unlocked(
+        personal_position,
+        liquidity,
+        u64::try_from(Clock::get()?.unix_timestamp).unwrap(),
+    )?;
+    let zero_for_one = is_zero_for_one(
+        &ctx.accounts.recipient_token_account.mint,
+        &ctx.accounts.vault_1_mint.key(),
+    );
+
+    let mut tickarray_bitmap_extension = None;
+    let mut tick_array_accounts = Vec::new();
+    let amm_config = {
+        let pool_state = pool_state_loader.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        // the liquidity change and the swap are done without calling the hooks
+        require!(
+            pool_state.hook_permissions == 0,
+            ErrorCode::HookNotSupported
+        );
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
+        let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
+        for account_info in ctx.remaining_accounts.iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
+            if account_info.key().eq(&tick_array_bitmap_extension_key) {
+                tickarray_bitmap_extension = Some(account_info);
+                continue;
+            }
+            tick_array_accounts.push(account_info);
+        }
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![
+            ctx.accounts.tick_array_lower.load()?.start_tick_index,
+            ctx.accounts.tick_array_upper.load()?.start_tick_index,
+        ]) {
+            require!(
+                tickarray_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+        get_effective_amm_config(&ctx.accounts.amm_config, &pool_state, pool_fee_override)?
+    };
+
+    // the withdrawn tokens and the fees stay in the vaults until the final transfer
+    let (decrease_amount_0, fee_amount_0, decrease_amount_1, fee_amount_1) =
+        decrease_liquidity_and_update_position(
+            pool_state_loader,
+            &mut ctx.accounts.protocol_position,
+            personal_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            tickarray_bitmap_extension,
+            liquidity,
+        )?;
+    let amount_0 = decrease_amount_0.checked_add(fee_amount_0).unwrap();
+    let amount_1 = decrease_amount_1.checked_add(fee_amount_1).unwrap();
+
+    let (swap_amount, mut amount_out) = if zero_for_one {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    };
+    let mut swap_amount_out = 0;
+    if swap_amount > 0 {
+        let tick_array_bitmap_extension = match tickarray_bitmap_extension {
+            Some(account_info) => Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            ),
+            None => None,
+        };
+        let mut tick_array_states = VecDeque::new();
+        for account_info in tick_array_accounts.iter() {
+            tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+        }
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let (swap_amount_0, swap_amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
+            pool_state,
+            &mut tick_array_states,
+            &mut ctx.accounts.observation_state.load_mut()?,
+            &tick_array_bitmap_extension,
+            swap_amount,
+            if zero_for_one {
+                tick_math::MIN_SQRT_PRICE_X64 + 1
+            } else {
+                tick_math::MAX_SQRT_PRICE_X64 - 1
+            },
+            zero_for_one,
+            true,
+            0,
+            None,
+            false,
+            oracle::block_timestamp(),
+        )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        swap_amount_out = if zero_for_one {
+            swap_amount_1
+        } else {
+            swap_amount_0
+        };
+        amount_out = amount_out.checked_add(swap_amount_out).unwrap();
+    }
+
+    let (output_vault, output_vault_mint) = if zero_for_one {
+        (&ctx.accounts.token_vault_1, &ctx.accounts.vault_1_mint)
+    } else {
+        (&ctx.accounts.token_vault_0, &ctx.accounts.vault_0_mint)
+    };
+    let transfer_fee = get_transfer_fee(output_vault_mint.clone(), amount_out)?;
+    check_amount_out_received(amount_out, transfer_fee, amount_out_min)?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &output_vault.to_account_info(),
+        &ctx.accounts.recipient_token_account.to_account_info(),
+        Some(output_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_out,
+    )?;
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.token_vault_1.to_account_info(),
+    )?;
+
+    emit!(DecreaseLiquidityZapOutEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        decrease_amount_0,
+        decrease_amount_1,
+        fee_amount_0,
+        fee_amount_1,
+        zero_for_one,
+        swap_amount_in: swap_amount,
+        swap_amount_out,
+        amount_out,
+        transfer_fee,
+    });
+
+    Ok(())
+}
+
+/// A locked position can't burn liquidity, but its fees can still be collected with liquidity 0
+fn check_position_unlocked(
+    personal_position: &PersonalPositionState,
+    liquidity: u128,
+    block_timestamp: u64,
+) -> Result<()> {
+    if liquidity > 0 {
+        require!(
+            !personal_position.is_locked(block_timestamp),
+            ErrorCode::PositionLocked
+        );
+    }
+    Ok(())
+}
+
+/// token_0 is swapped into token_1 if the recipient holds token_1
+fn is_zero_for_one(recipient_mint: &Pubkey, vault_1_mint: &Pubkey) -> bool {
+    recipient_mint == vault_1_mint
+}
+
+/// The slippage is checked against the amount the recipient receives, net of the transfer fee
+fn check_amount_out_received(
+    amount_out: u64,
+    transfer_fee: u64,
+    amount_out_min: u64,
+) -> Result<()> {
+    require_gte!(
+        amount_out - transfer_fee,
+        amount_out_min,
+        ErrorCode::TooLittleOutputReceived
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod decrease_liquidity_zap_out_test {
+    use super::*;
+
+    #[test]
+    fn zero_for_one_follows_recipient_mint_test() {
+        let vault_0_mint = Pubkey::new_unique();
+        let vault_1_mint = Pubkey::new_unique();
+        // the recipient holds token_1, token_0 is swapped into it
+        assert!(is_zero_for_one(&vault_1_mint, &vault_1_mint));
+        // the recipient holds token_0, token_1 is swapped into it
+        assert!(!is_zero_for_one(&vault_0_mint, &vault_1_mint));
+    }
+
+    #[test]
+    fn amount_out_min_net_of_transfer_fee_test() {
+        assert!(check_amount_out_received(1_000, 0, 1_000).is_ok());
+        assert!(check_amount_out_received(1_000, 10, 990).is_ok());
+        // the amount out before the transfer fee covers the minimum, the amount received doesn't
+        assert_eq!(
+            check_amount_out_received(1_000, 10, 991).unwrap_err(),
+            ErrorCode::TooLittleOutputReceived.into()
+        );
+        assert_eq!(
+            check_amount_out_received(1_000, 0, 1_001).unwrap_err(),
+            ErrorCode::TooLittleOutputReceived.into()
+        );
+    }
+
+    #[test]
+    fn locked_position_collects_fees_only_test() {
+        let personal_position = PersonalPositionState {
+            liquidity: 1_000_000,
+            unlock_timestamp: 1_000,
+            ..Default::default()
+        };
+        // liquidity 0 only collects the fees, it's allowed while the position is locked
+        assert!(check_position_unlocked(&personal_position, 0, 999).is_ok());
+        assert_eq!(
+            check_position_unlocked(&personal_position, 1, 999).unwrap_err(),
+            ErrorCode::PositionLocked.into()
+        );
+        // the liquidity can be burned once the lock expires
+        assert!(check_position_unlocked(&personal_position, 1_000_000, 1_000).is_ok());
+    }
+}
@@ -0,0 +1,349 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::swap::{accrue_swap_fee, SwapState};
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// The instruction discriminator of the callback, the anchor sighash of `flash_loan_callback`
+pub const FLASH_LOAN_CALLBACK_DISCRIMINATOR: [u8; 8] = [43, 84, 214, 124, 50, 211, 59, 73];
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The user performing the flash loan
+    pub payer: Signer<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool to borrow from
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The token account receiving the borrowed token_0
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub receiver_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account receiving the borrowed token_1
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub receiver_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: The program called back to repay the loan into the vaults,
+    /// it can't be this program
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::id() @ ErrorCode::InvalidCallbackProgram
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+    // remaining accounts
+    // callback accounts: the first `callback_account_count` accounts, passed to the callback in order
+    // pool_fee_override: must add account if the pool fees are overridden
+}
+
+/// The instruction data of the callback, following `FLASH_LOAN_CALLBACK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct FlashLoanCallback {
+    /// The pool of the flash loan
+    pub pool_state: Pubkey,
+    /// The amount of token_0 sent out of the vault, token 2022 transfer fee included
+    pub amount_0: u64,
+    /// The amount of token_1 sent out of the vault, token 2022 transfer fee included
+    pub amount_1: u64,
+    /// The amount the token_0 vault must receive back on top of `amount_0`, token 2022 transfer fee excluded
+    pub fee_0: u64,
+    /// The amount the token_1 vault must receive back on top of `amount_1`, token 2022 transfer fee excluded
+    pub fee_1: u64,
+    /// The data passed through from the flash loan caller
+    pub data: Vec<u8>,
+}
+
+/// Lends the vault tokens to the receivers, then calls back the callback program which must
+/// repay the loan and the fee into the vaults.
+/// The pool is locked during the callback, so the pool can't be swapped or changed by the callback
+pub fn flash_loan<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, FlashLoan<'info>>,
+    amount_0: u64,
+    amount_1: u64,
+    callback_account_count: u8,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    require!(
+        amount_0 != 0 || amount_1 != 0,
+        ErrorCode::ZeroAmountSpecified
+    );
+    let callback_account_count = usize::from(callback_account_count);
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        callback_account_count,
+        ErrorCode::AccountLack
+    );
+    let (callback_accounts, remaining_accounts) =
+        ctx.remaining_accounts.split_at(callback_account_count);
+
+    let fee_0;
+    let fee_1;
+    let amm_config;
+    {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        // a flash loan shares the swap permission, a pool that can't swap can't lend
+        require!(
+            pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
+            ErrorCode::NotApproved
+        );
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+
+        let mut pool_fee_override = None;
+        if pool_state.is_fee_overridden() {
+            let pool_fee_override_key = PoolFeeOverride::key(pool_state.key());
+            if let Some(account_info) = remaining_accounts
+                .iter()
+                .find(|account_info| account_info.key() == pool_fee_override_key)
+            {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+            }
+        }
+        amm_config =
+            get_effective_amm_config(&ctx.accounts.amm_config, pool_state, pool_fee_override)?;
+
+        fee_0 = pool_state.get_flash_loan_fee(amount_0)?;
+        fee_1 = pool_state.get_flash_loan_fee(amount_1)?;
+        // lock the pool until the loan is repaid
+        pool_state.locked = 1;
+    }
+
+    let vault_0_balance_before = ctx.accounts.token_vault_0.amount;
+    let vault_1_balance_before = ctx.accounts.token_vault_1.amount;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.receiver_token_account_0.to_account_info(),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.receiver_token_account_1.to_account_info(),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+
+    let mut data = FLASH_LOAN_CALLBACK_DISCRIMINATOR.to_vec();
+    FlashLoanCallback {
+        pool_state: ctx.accounts.pool_state.key(),
+        amount_0,
+        amount_1,
+        fee_0,
+        fee_1,
+        data: callback_data,
+    }
+    .serialize(&mut data)?;
+    let mut callback_account_infos = callback_accounts.to_vec();
+    callback_account_infos.push(ctx.accounts.callback_program.to_account_info());
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.callback_program.key(),
+            accounts: callback_accounts
+                .iter()
+                .map(|account_info| {
+                    if account_info.is_writable {
+                        AccountMeta::new(account_info.key(), account_info.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(account_info.key(), account_info.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        },
+        &callback_account_infos,
+    )?;
+
+    // the vault balances are measured, so the transfer fees of the repayment are paid by the borrower
+    ctx.accounts.token_vault_0.reload()?;
+    ctx.accounts.token_vault_1.reload()?;
+    require_gte!(
+        ctx.accounts.token_vault_0.amount,
+        vault_0_balance_before.checked_add(fee_0).unwrap(),
+        ErrorCode::FlashLoanNotRepaid
+    );
+    require_gte!(
+        ctx.accounts.token_vault_1.amount,
+        vault_1_balance_before.checked_add(fee_1).unwrap(),
+        ErrorCode::FlashLoanNotRepaid
+    );
+
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.locked = 0;
+    accrue_flash_loan_fee(&amm_config, pool_state, true, fee_0);
+    accrue_flash_loan_fee(&amm_config, pool_state, false, fee_1);
+
+    emit!(FlashLoanEvent {
+        pool_state: pool_state.key(),
+        sender: ctx.accounts.payer.key(),
+        amount_0,
+        amount_1,
+        fee_0,
+        fee_1,
+    });
+    Ok(())
+}
+
+/// Splits the flash loan fee of one token the same way as the swap fee, the protocol fee,
+/// fund fee and creator fee are taken out and the rest goes to the liquidity in range
+fn accrue_flash_loan_fee(
+    amm_config: &AmmConfig,
+    pool_state: &mut PoolState,
+    is_token_0: bool,
+    fee_amount: u64,
+) {
+    if fee_amount == 0 {
+        return;
+    }
+    let mut state = SwapState {
+        amount_specified_remaining: 0,
+        amount_calculated: 0,
+        sqrt_price_x64: pool_state.sqrt_price_x64,
+        tick: pool_state.tick_current,
+        fee_growth_global_x64: if is_token_0 {
+            pool_state.fee_growth_global_0_x64
+        } else {
+            pool_state.fee_growth_global_1_x64
+        },
+        fee_amount: 0,
+        protocol_fee: 0,
+        fund_fee: 0,
+        creator_fee: 0,
+        referral_fee: 0,
+        liquidity: pool_state.liquidity,
+        limit_order_liquidity: pool_state.limit_order_liquidity,
+        ticks_crossed: 0,
+        circuit_breaker_tick_start: None,
+    };
+    accrue_swap_fee(amm_config, 0, &mut state, fee_amount);
+
+    if is_token_0 {
+        pool_state.fee_growth_global_0_x64 = state.fee_growth_global_x64;
+        pool_state.total_fees_token_0 = pool_state
+            .total_fees_token_0
+            .checked_add(state.fee_amount)
+            .unwrap();
+        pool_state.protocol_fees_token_0 = pool_state
+            .protocol_fees_token_0
+            .checked_add(state.protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_0 = pool_state
+            .fund_fees_token_0
+            .checked_add(state.fund_fee)
+            .unwrap();
+        pool_state.creator_fees_token_0 = pool_state
+            .creator_fees_token_0
+            .checked_add(state.creator_fee)
+            .unwrap();
+    } else {
+        pool_state.fee_growth_global_1_x64 = state.fee_growth_global_x64;
+        pool_state.total_fees_token_1 = pool_state
+            .total_fees_token_1
+            .checked_add(state.fee_amount)
+            .unwrap();
+        pool_state.protocol_fees_token_1 = pool_state
+            .protocol_fees_token_1
+            .checked_add(state.protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_1 = pool_state
+            .fund_fees_token_1
+            .checked_add(state.fund_fee)
+            .unwrap();
+        pool_state.creator_fees_token_1 = pool_state
+            .creator_fees_token_1
+            .checked_add(state.creator_fee)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod flash_loan_test {
+    use super::*;
+    use crate::libraries::fixed_point_64;
+    use anchor_lang::solana_program::hash::hash;
+    use std::convert::identity;
+
+    #[test]
+    fn flash_loan_callback_discriminator_test() {
+        assert_eq!(
+            FLASH_LOAN_CALLBACK_DISCRIMINATOR,
+            hash(b"global:flash_loan_callback").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn accrue_flash_loan_fee_test() {
+        let amm_config = AmmConfig {
+            protocol_fee_rate: 100_000,
+            fund_fee_rate: 50_000,
+            ..Default::default()
+        };
+        let mut pool_state = PoolState {
+            liquidity: 10,
+            ..Default::default()
+        };
+
+        accrue_flash_loan_fee(&amm_config, &mut pool_state, false, 1000);
+        assert_eq!(identity(pool_state.protocol_fees_token_1), 100);
+        assert_eq!(identity(pool_state.fund_fees_token_1), 50);
+        assert_eq!(identity(pool_state.total_fees_token_1), 850);
+        // 850 fee over 10 units of liquidity
+        assert_eq!(
+            identity(pool_state.fee_growth_global_1_x64),
+            85 * fixed_point_64::Q64
+        );
+        // the other token is untouched
+        assert_eq!(identity(pool_state.fee_growth_global_0_x64), 0);
+        assert_eq!(identity(pool_state.total_fees_token_0), 0);
+    }
+}
@@ -0,0 +1,400 @@
+use crate::error::ErrorCode;
+use crate::libraries::tick_math;
+use crate::swap::{emit_circuit_breaker_tripped, swap_internal_with_state};
+use crate::swap_v2::SwapReturnData;
+use crate::util::*;
+use crate::{states::*, util};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+/// The instruction discriminator of the callback, the anchor sighash of `flash_swap_callback`
+pub const FLASH_SWAP_CALLBACK_DISCRIMINATOR: [u8; 8] = [225, 54, 80, 1, 45, 208, 202, 124];
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    /// The user performing the flash swap
+    pub payer: Signer<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool in which the swap will be performed
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The token account receiving the output token before the callback
+    #[account(mut)]
+    pub receiver_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(mut)]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of input token vault
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token vault
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: The program called back to pay the input token into the input vault,
+    /// it can't be this program
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::id() @ ErrorCode::InvalidCallbackProgram
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+    // remaining accounts
+    // callback accounts: the first `callback_account_count` accounts, passed to the callback in order
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+    // hook accounts: must add the hook program and its accounts at last if the pool has a swap hook
+}
+
+/// The instruction data of the callback, following `FLASH_SWAP_CALLBACK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct FlashSwapCallback {
+    /// The pool of the flash swap
+    pub pool_state: Pubkey,
+    /// The amount the input vault must receive before the callback returns, token 2022 transfer fee excluded
+    pub amount_in_owed: u64,
+    /// The amount of output token transferred to the receiver, token 2022 transfer fee included
+    pub amount_out: u64,
+    /// The data passed through from the flash swap caller
+    pub data: Vec<u8>,
+}
+
+/// Transfers the output tokens to the receiver first, then calls back the callback program
+/// which must pay the input tokens into the input vault.
+/// The pool is locked during the callback, so the pool can't be swapped or changed by the callback
+pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit_x64: u128,
+    is_base_input: bool,
+    callback_account_count: u8,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    let callback_account_count = usize::from(callback_account_count);
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        callback_account_count,
+        ErrorCode::AccountLack
+    );
+    let (callback_accounts, remaining_accounts) =
+        ctx.remaining_accounts.split_at(callback_account_count);
+
+    // the receiver gets exactly the specified amount in base output swaps
+    let amount_calculate_specified = if is_base_input {
+        amount
+    } else {
+        amount
+            + util::get_transfer_inverse_fee(ctx.accounts.output_vault_mint.clone(), amount)
+                .unwrap()
+    };
+
+    let (remaining_accounts, hook_accounts) = split_hook_accounts(
+        &ctx.accounts.pool_state,
+        remaining_accounts,
+        &SWAP_HOOK_PERMISSIONS,
+    )?;
+    invoke_hook(
+        &ctx.accounts.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeSwap,
+        BEFORE_SWAP_HOOK_DISCRIMINATOR,
+        &BeforeSwapHook {
+            pool_state: ctx.accounts.pool_state.key(),
+            sender: ctx.accounts.payer.key(),
+            zero_for_one: ctx.accounts.input_vault.mint
+                == ctx.accounts.pool_state.load()?.token_mint_0,
+            is_base_input,
+            amount_specified: amount_calculate_specified,
+            sqrt_price_limit_x64,
+        },
+    )?;
+
+    let amount_0;
+    let amount_1;
+    let zero_for_one;
+    let swap_price_before;
+    let fee_amount;
+    {
+        swap_price_before = ctx.accounts.pool_state.load()?.sqrt_price_x64;
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        zero_for_one = ctx.accounts.input_vault.mint == pool_state.token_mint_0;
+        let swap_fees_before = pool_state.get_accumulated_swap_fees(zero_for_one);
+
+        require!(
+            if zero_for_one {
+                ctx.accounts.input_vault.key() == pool_state.token_vault_0
+                    && ctx.accounts.output_vault.key() == pool_state.token_vault_1
+            } else {
+                ctx.accounts.input_vault.key() == pool_state.token_vault_1
+                    && ctx.accounts.output_vault.key() == pool_state.token_vault_0
+            },
+            ErrorCode::InvalidInputPoolVault
+        );
+
+        let mut tickarray_bitmap_extension = None;
+        let tick_array_states = &mut VecDeque::new();
+
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
+        let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
+        for account_info in remaining_accounts.iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
+            if account_info.key().eq(&tick_array_bitmap_extension_key) {
+                tickarray_bitmap_extension = Some(
+                    *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                        .load()?
+                        .deref()),
+                );
+                continue;
+            }
+            tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+        }
+
+        let amm_config =
+            get_effective_amm_config(&ctx.accounts.amm_config, pool_state, pool_fee_override)?;
+        let swap_state;
+        (amount_0, amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
+            pool_state,
+            tick_array_states,
+            &mut ctx.accounts.observation_state.load_mut()?,
+            &tickarray_bitmap_extension,
+            amount_calculate_specified,
+            if sqrt_price_limit_x64 == 0 {
+                if zero_for_one {
+                    tick_math::MIN_SQRT_PRICE_X64 + 1
+                } else {
+                    tick_math::MAX_SQRT_PRICE_X64 - 1
+                }
+            } else {
+                sqrt_price_limit_x64
+            },
+            zero_for_one,
+            is_base_input,
+            0,
+            None,
+            false,
+            oracle::block_timestamp(),
+        )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        fee_amount = pool_state.get_accumulated_swap_fees(zero_for_one) - swap_fees_before;
+        require!(
+            amount_0 != 0 && amount_1 != 0,
+            ErrorCode::TooSmallInputOrOutputAmount
+        );
+        if sqrt_price_limit_x64 == 0 {
+            // Does't allow partial filled without specified limit_price.
+            require_eq!(
+                amount_calculate_specified,
+                if zero_for_one == is_base_input {
+                    amount_0
+                } else {
+                    amount_1
+                }
+            );
+        }
+    }
+    let after_swap_hook = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        AfterSwapHook {
+            pool_state: pool_state.key(),
+            sender: ctx.accounts.payer.key(),
+            zero_for_one,
+            is_base_input,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        }
+    };
+    invoke_hook(
+        &ctx.accounts.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::AfterSwap,
+        AFTER_SWAP_HOOK_DISCRIMINATOR,
+        &after_swap_hook,
+    )?;
+    // lock the pool until the input is paid
+    ctx.accounts.pool_state.load_mut()?.locked = 1;
+    let (amount_in_owed, amount_out) = if zero_for_one {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    };
+
+    if ctx.accounts.output_vault.amount <= amount_out {
+        // freeze pool, disable all instructions
+        ctx.accounts.pool_state.load_mut()?.set_status(255);
+    }
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.output_vault.to_account_info(),
+        &ctx.accounts.receiver_token_account.to_account_info(),
+        Some(ctx.accounts.output_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_out,
+    )?;
+
+    let input_vault_balance_before = ctx.accounts.input_vault.amount;
+    let mut data = FLASH_SWAP_CALLBACK_DISCRIMINATOR.to_vec();
+    FlashSwapCallback {
+        pool_state: ctx.accounts.pool_state.key(),
+        amount_in_owed,
+        amount_out,
+        data: callback_data,
+    }
+    .serialize(&mut data)?;
+    let mut callback_account_infos = callback_accounts.to_vec();
+    callback_account_infos.push(ctx.accounts.callback_program.to_account_info());
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.callback_program.key(),
+            accounts: callback_accounts
+                .iter()
+                .map(|account_info| {
+                    if account_info.is_writable {
+                        AccountMeta::new(account_info.key(), account_info.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(account_info.key(), account_info.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        },
+        &callback_account_infos,
+    )?;
+
+    ctx.accounts.input_vault.reload()?;
+    let amount_in_paid = ctx
+        .accounts
+        .input_vault
+        .amount
+        .checked_sub(input_vault_balance_before)
+        .ok_or(ErrorCode::FlashSwapInputNotPaid)?;
+    require_gte!(
+        amount_in_paid,
+        amount_in_owed,
+        ErrorCode::FlashSwapInputNotPaid
+    );
+    ctx.accounts.pool_state.load_mut()?.locked = 0;
+
+    let transfer_fee_out =
+        util::get_transfer_fee(ctx.accounts.output_vault_mint.clone(), amount_out).unwrap();
+    let amount_received = amount_out.checked_sub(transfer_fee_out).unwrap();
+    if is_base_input {
+        require_gte!(
+            amount_received,
+            other_amount_threshold,
+            ErrorCode::TooLittleOutputReceived
+        );
+    } else {
+        require_gte!(
+            other_amount_threshold,
+            amount_in_owed,
+            ErrorCode::TooMuchInputPaid
+        );
+    }
+
+    let filled_amount = if is_base_input {
+        amount_in_owed
+    } else {
+        amount_received
+    };
+    let pool_state = ctx.accounts.pool_state.load()?;
+    // the input is paid by the callback, from a token account unknown to the pool
+    let (token_account_0, token_account_1) = if zero_for_one {
+        (Pubkey::default(), ctx.accounts.receiver_token_account.key())
+    } else {
+        (ctx.accounts.receiver_token_account.key(), Pubkey::default())
+    };
+    let (transfer_fee_0, transfer_fee_1) = if zero_for_one {
+        (0, transfer_fee_out)
+    } else {
+        (transfer_fee_out, 0)
+    };
+    emit!(SwapEvent {
+        pool_state: pool_state.key(),
+        sender: ctx.accounts.payer.key(),
+        token_account_0,
+        token_account_1,
+        amount_0: amount_0 - transfer_fee_0,
+        transfer_fee_0,
+        amount_1: amount_1 - transfer_fee_1,
+        transfer_fee_1,
+        zero_for_one,
+        sqrt_price_x64: pool_state.sqrt_price_x64,
+        liquidity: pool_state.liquidity,
+        tick: pool_state.tick_current,
+        fee_amount,
+        filled_amount,
+        unfilled_amount: amount.saturating_sub(filled_amount),
+    });
+    if zero_for_one {
+        require_gt!(swap_price_before, pool_state.sqrt_price_x64);
+    } else {
+        require_gt!(pool_state.sqrt_price_x64, swap_price_before);
+    }
+
+    SwapReturnData {
+        amount_in: amount_in_paid,
+        amount_out: amount_received,
+        unfilled_amount: amount.saturating_sub(filled_amount),
+    }
+    .set()
+}
+
+#[cfg(test)]
+mod flash_swap_test {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+
+    #[test]
+    fn flash_swap_callback_discriminator_test() {
+        assert_eq!(
+            FLASH_SWAP_CALLBACK_DISCRIMINATOR,
+            hash(b"global:flash_swap_callback").to_bytes()[..8]
+        );
+    }
+}
@@ -142,47 +142,69 @@ pub fn increase_liquidity<'a, 'b, 'c: 'info, 'info>(
     base_flag: Option<bool>,
 ) -> Result<()> {
     let mut liquidity = liquidity;
-    let pool_state = &mut pool_state_loader.load_mut()?;
-    if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
-        return err!(ErrorCode::NotApproved);
-    }
     let tick_lower = personal_position.tick_lower_index;
     let tick_upper = personal_position.tick_upper_index;
-
-    let use_tickarray_bitmap_extension =
-        pool_state.is_overflow_default_tickarray_bitmap(vec![tick_lower, tick_upper]);
-
-    let (amount_0, amount_1, amount_0_transfer_fee, amount_1_transfer_fee) = add_liquidity(
-        &nft_owner,
-        token_account_0,
-        token_account_1,
-        token_vault_0,
-        token_vault_1,
-        &AccountLoad::<TickArrayState>::try_from(&tick_array_lower_loader.to_account_info())?,
-        &AccountLoad::<TickArrayState>::try_from(&tick_array_upper_loader.to_account_info())?,
-        protocol_position,
-        token_program_2022,
-        token_program,
-        vault_0_mint,
-        vault_1_mint,
-        if use_tickarray_bitmap_extension {
-            require_keys_eq!(
-                remaining_accounts[0].key(),
-                TickArrayBitmapExtension::key(pool_state_loader.key())
-            );
-            Some(&remaining_accounts[0])
-        } else {
-            None
+    let (remaining_accounts, hook_accounts) = split_hook_accounts(
+        pool_state_loader,
+        remaining_accounts,
+        &MODIFY_POSITION_HOOK_PERMISSIONS,
+    )?;
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeModifyPosition,
+        BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &BeforeModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: nft_owner.key(),
+            tick_lower_index: tick_lower,
+            tick_upper_index: tick_upper,
+            liquidity_delta: i128::try_from(liquidity).unwrap(),
         },
-        pool_state,
-        &mut liquidity,
-        amount_0_max,
-        amount_1_max,
-        tick_lower,
-        tick_upper,
-        base_flag,
     )?;
 
+    let (amount_0, amount_1, amount_0_transfer_fee, amount_1_transfer_fee) = {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        let use_tickarray_bitmap_extension =
+            pool_state.is_overflow_default_tickarray_bitmap(vec![tick_lower, tick_upper]);
+
+        add_liquidity(
+            &nft_owner,
+            token_account_0,
+            token_account_1,
+            token_vault_0,
+            token_vault_1,
+            &AccountLoad::<TickArrayState>::try_from(&tick_array_lower_loader.to_account_info())?,
+            &AccountLoad::<TickArrayState>::try_from(&tick_array_upper_loader.to_account_info())?,
+            protocol_position,
+            token_program_2022,
+            token_program,
+            vault_0_mint,
+            vault_1_mint,
+            if use_tickarray_bitmap_extension {
+                require_keys_eq!(
+                    remaining_accounts[0].key(),
+                    TickArrayBitmapExtension::key(pool_state_loader.key())
+                );
+                Some(&remaining_accounts[0])
+            } else {
+                None
+            },
+            pool_state,
+            &mut liquidity,
+            amount_0_max,
+            amount_1_max,
+            tick_lower,
+            tick_upper,
+            base_flag,
+        )?
+    };
+
     personal_position.token_fees_owed_0 = calculate_latest_token_fees(
         personal_position.token_fees_owed_0,
         personal_position.fee_growth_inside_0_last_x64,
@@ -212,6 +234,22 @@ pub fn increase_liquidity<'a, 'b, 'c: 'info, 'info>(
         amount_1_transfer_fee
     });
 
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::AfterModifyPosition,
+        AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &AfterModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: nft_owner.key(),
+            tick_lower_index: tick_lower,
+            tick_upper_index: tick_upper,
+            liquidity_delta: i128::try_from(liquidity).unwrap(),
+            amount_0,
+            amount_1,
+        },
+    )?;
+
     Ok(())
 }
 
@@ -0,0 +1,483 @@
+use super::{
+    add_liquidity_to_ticks, calculate_latest_token_fees, check_unclaimed_fees_and_vault,
+    emit_circuit_breaker_tripped, swap_internal_with_state,
+};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+#[derive(Accounts)]
+pub struct IncreaseLiquidityZap<'info> {
+    /// The position owner, pays the deposited token
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for nft
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+        token::authority = nft_owner,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Increase liquidity for this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The payer's token account for token_0, receives the token_0 left over
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account for token_1, receives the token_1 left over
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program to transfer tokens
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+pub fn increase_liquidity_zap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, IncreaseLiquidityZap<'info>>,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+) -> Result<()> {
+    zap_into_position(
+        &ctx.accounts.nft_owner,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.observation_state,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.tick_array_lower.to_account_info(),
+        &ctx.accounts.tick_array_upper.to_account_info(),
+        &ctx.accounts.token_account_0.to_account_info(),
+        &ctx.accounts.token_account_1.to_account_info(),
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        ctx.accounts.vault_0_mint.clone(),
+        ctx.accounts.vault_1_mint.clone(),
+        ctx.remaining_accounts,
+        amount_in,
+        zero_for_one,
+        liquidity_min,
+    )
+}
+
+/// Deposits a single token into the position. The part computed by `get_zap_swap_amount`
+/// is swapped through the pool, the two halves are added as liquidity and the dust is sent back
+pub fn zap_into_position<'c: 'info, 'info>(
+    payer: &Signer<'info>,
+    amm_config: &AmmConfig,
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    observation_state_loader: &AccountLoader<'info, ObservationState>,
+    protocol_position: &mut ProtocolPositionState,
+    personal_position: &mut PersonalPositionState,
+    tick_array_lower: &AccountInfo<'info>,
+    tick_array_upper: &AccountInfo<'info>,
+    token_account_0: &AccountInfo<'info>,
+    token_account_1: &AccountInfo<'info>,
+    token_vault_0: &AccountInfo<'info>,
+    token_vault_1: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    token_program_2022: &Program<'info, Token2022>,
+    vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+) -> Result<()> {
+    let tick_lower = personal_position.tick_lower_index;
+    let tick_upper = personal_position.tick_upper_index;
+    require_gt!(amount_in, 0, ErrorCode::ForbidBothZeroForSupplyLiquidity);
+
+    let mut tickarray_bitmap_extension = None;
+    let mut tick_array_accounts = Vec::new();
+    let amm_config = {
+        let pool_state = pool_state_loader.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        // the liquidity change and the swap are done without calling the hooks
+        require!(
+            pool_state.hook_permissions == 0,
+            ErrorCode::HookNotSupported
+        );
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
+        let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
+        for account_info in remaining_accounts.iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
+            if account_info.key().eq(&tick_array_bitmap_extension_key) {
+                tickarray_bitmap_extension = Some(account_info);
+                continue;
+            }
+            tick_array_accounts.push(account_info);
+        }
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![tick_lower, tick_upper]) {
+            require!(
+                tickarray_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+        get_effective_amm_config(amm_config, &pool_state, pool_fee_override)?
+    };
+
+    let (input_token_account, input_vault, input_vault_mint) = if zero_for_one {
+        (token_account_0, token_vault_0, &vault_0_mint)
+    } else {
+        (token_account_1, token_vault_1, &vault_1_mint)
+    };
+    // must deduct transfer fee before splitting the deposit
+    let amount_in_transfer_fee = get_transfer_fee(input_vault_mint.clone(), amount_in)?;
+    transfer_from_user_to_pool_vault(
+        payer,
+        input_token_account,
+        input_vault,
+        Some(input_vault_mint.clone()),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        amount_in,
+    )?;
+    let amount_received = amount_in.checked_sub(amount_in_transfer_fee).unwrap();
+
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper)?;
+    let block_timestamp = oracle::block_timestamp();
+    let swap_amount = {
+        let pool_state = pool_state_loader.load()?;
+        liquidity_math::get_zap_swap_amount(
+            pool_state.sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_received,
+            zero_for_one,
+            amm_config.get_trade_fee_rate(
+                observation_state_loader.load()?.deref(),
+                pool_state.tick_current,
+                block_timestamp,
+            ),
+        )
+    };
+
+    let (mut amount_0, mut amount_1) = if zero_for_one {
+        (amount_received, 0)
+    } else {
+        (0, amount_received)
+    };
+    let mut swap_amount_in = 0;
+    let mut swap_amount_out = 0;
+    if swap_amount > 0 {
+        let tick_array_bitmap_extension = match tickarray_bitmap_extension {
+            Some(account_info) => Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            ),
+            None => None,
+        };
+        let mut tick_array_states = VecDeque::new();
+        for account_info in tick_array_accounts.iter() {
+            tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+        }
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let (swap_amount_0, swap_amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
+            pool_state,
+            &mut tick_array_states,
+            &mut observation_state_loader.load_mut()?,
+            &tick_array_bitmap_extension,
+            swap_amount,
+            if zero_for_one {
+                tick_math::MIN_SQRT_PRICE_X64 + 1
+            } else {
+                tick_math::MAX_SQRT_PRICE_X64 - 1
+            },
+            zero_for_one,
+            true,
+            0,
+            None,
+            false,
+            block_timestamp,
+        )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        // the swapped tokens don't leave the vaults
+        if zero_for_one {
+            (swap_amount_in, swap_amount_out) = (swap_amount_0, swap_amount_1);
+            amount_0 -= swap_amount_0;
+            amount_1 = swap_amount_1;
+        } else {
+            (swap_amount_in, swap_amount_out) = (swap_amount_1, swap_amount_0);
+            amount_1 -= swap_amount_1;
+            amount_0 = swap_amount_0;
+        }
+    }
+
+    let (liquidity, deposit_amount_0, deposit_amount_1) = {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let liquidity = get_zap_liquidity(
+            pool_state.sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_0,
+            amount_1,
+            liquidity_min,
+        )?;
+        let (deposit_amount_0, deposit_amount_1) = add_liquidity_to_ticks(
+            &AccountLoad::<TickArrayState>::try_from(tick_array_lower)?,
+            &AccountLoad::<TickArrayState>::try_from(tick_array_upper)?,
+            protocol_position,
+            tickarray_bitmap_extension,
+            pool_state,
+            liquidity,
+            tick_lower,
+            tick_upper,
+        )?;
+        (liquidity, deposit_amount_0, deposit_amount_1)
+    };
+
+    personal_position.token_fees_owed_0 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_0,
+        personal_position.fee_growth_inside_0_last_x64,
+        protocol_position.fee_growth_inside_0_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.token_fees_owed_1 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_1,
+        personal_position.fee_growth_inside_1_last_x64,
+        protocol_position.fee_growth_inside_1_last_x64,
+        personal_position.liquidity,
+    );
+
+    personal_position.fee_growth_inside_0_last_x64 = protocol_position.fee_growth_inside_0_last_x64;
+    personal_position.fee_growth_inside_1_last_x64 = protocol_position.fee_growth_inside_1_last_x64;
+
+    // update rewards, must update before increase liquidity
+    personal_position.update_rewards(protocol_position.reward_growth_inside, true)?;
+    personal_position.liquidity = personal_position.liquidity.checked_add(liquidity).unwrap();
+
+    // send back the dust the range can't take
+    let refund_amount_0 = amount_0.checked_sub(deposit_amount_0).unwrap();
+    let refund_amount_1 = amount_1.checked_sub(deposit_amount_1).unwrap();
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        token_vault_0,
+        token_account_0,
+        Some(vault_0_mint),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        refund_amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        token_vault_1,
+        token_account_1,
+        Some(vault_1_mint),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        refund_amount_1,
+    )?;
+    check_unclaimed_fees_and_vault(pool_state_loader, token_vault_0, token_vault_1)?;
+
+    emit!(IncreaseLiquidityZapEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        zero_for_one,
+        amount_in,
+        amount_in_transfer_fee,
+        swap_amount_in,
+        swap_amount_out,
+        amount_0: deposit_amount_0,
+        amount_1: deposit_amount_1,
+        refund_amount_0,
+        refund_amount_1,
+    });
+
+    Ok(())
+}
+
+/// The liquidity the tokens left after the zap swap add to the range, at least `liquidity_min`
+pub fn get_zap_liquidity(
+    sqrt_price_x64: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    amount_0: u64,
+    amount_1: u64,
+    liquidity_min: u128,
+) -> Result<u128> {
+    let liquidity = liquidity_math::get_liquidity_from_amounts(
+        sqrt_price_x64,
+        sqrt_price_lower_x64,
+        sqrt_price_upper_x64,
+        amount_0,
+        amount_1,
+    );
+    require_gt!(liquidity, 0, ErrorCode::ForbidBothZeroForSupplyLiquidity);
+    require_gte!(liquidity, liquidity_min, ErrorCode::PriceSlippageCheck);
+    Ok(liquidity)
+}
+
+#[cfg(test)]
+mod increase_liquidity_zap_test {
+    use super::*;
+    use crate::libraries::{fixed_point_64, MulDiv, U256};
+    use crate::states::config::FEE_RATE_DENOMINATOR_VALUE;
+
+    #[test]
+    fn zap_swap_amount_balances_the_deposit_test() {
+        let tick_lower = -1200;
+        let tick_upper = 2400;
+        let trade_fee_rate = 2500;
+        for (tick_current, amount_in, zero_for_one) in [
+            (0, 1_000_000_000u64, true),
+            (0, 1_000_000_000, false),
+            (-1000, 77_777_777, true),
+            (2300, 5_000_000_000, false),
+            (600, u32::MAX as u64, true),
+        ] {
+            let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(tick_current).unwrap();
+            let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower).unwrap();
+            let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper).unwrap();
+            let swap_amount = liquidity_math::get_zap_swap_amount(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in,
+                zero_for_one,
+                trade_fee_rate,
+            );
+            assert!(swap_amount < amount_in);
+
+            // swap without price impact
+            let price_x64 = U256::from(sqrt_price_x64)
+                .mul_div_floor(U256::from(sqrt_price_x64), U256::from(fixed_point_64::Q64))
+                .unwrap();
+            let amount_after_fee = U256::from(swap_amount)
+                .mul_div_floor(
+                    U256::from(FEE_RATE_DENOMINATOR_VALUE - trade_fee_rate),
+                    U256::from(FEE_RATE_DENOMINATOR_VALUE),
+                )
+                .unwrap();
+            let (amount_0, amount_1) = if zero_for_one {
+                let amount_out = amount_after_fee
+                    .mul_div_floor(price_x64, U256::from(fixed_point_64::Q64))
+                    .unwrap();
+                (amount_in - swap_amount, amount_out.as_u64())
+            } else {
+                let amount_out = amount_after_fee
+                    .mul_div_floor(U256::from(fixed_point_64::Q64), price_x64)
+                    .unwrap();
+                (amount_out.as_u64(), amount_in - swap_amount)
+            };
+            let liquidity = liquidity_math::get_liquidity_from_amounts(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_0,
+                amount_1,
+            );
+            let (deposit_amount_0, deposit_amount_1) = liquidity_math::get_delta_amounts_signed(
+                tick_current,
+                sqrt_price_x64,
+                tick_lower,
+                tick_upper,
+                i128::try_from(liquidity).unwrap(),
+            )
+            .unwrap();
+            // both halves are used up to rounding
+            assert!(amount_0 - deposit_amount_0 <= amount_0 / 1_000_000 + 1);
+            assert!(amount_1 - deposit_amount_1 <= amount_1 / 1_000_000 + 1);
+        }
+    }
+}
@@ -0,0 +1,593 @@
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::cell::RefMut;
+
+#[derive(Accounts)]
+#[instruction(order_nonce: u64, tick_array_start_index: i32)]
+pub struct PlaceLimitOrder<'info> {
+    /// The owner of the order, pays for the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The pool to place the order on
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The order account
+    #[account(
+        init,
+        seeds = [
+            LIMIT_ORDER_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &order_nonce.to_be_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = LimitOrderState::LEN
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// CHECK: The tick array holding both ticks of the order range, created if needed
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array: UncheckedAccount<'info>,
+
+    /// The owner's token account of the sold token
+    #[account(mut, token::mint = input_vault.mint)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The pool vault of the sold token
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the sold token
+    #[account(address = input_vault.mint)]
+    pub input_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// Program to create the order and the tick array
+    pub system_program: Program<'info, System>,
+    // remaining account
+    // #[account(
+    //     seeds = [
+    //         POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+    //         pool_state.key().as_ref(),
+    //     ],
+    //     bump
+    // )]
+    // pub tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    /// The owner of the order, receives the rent of the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The order to cancel
+    #[account(
+        mut,
+        close = owner,
+        constraint = limit_order.pool_id == pool_state.key(),
+        constraint = limit_order.owner == owner.key(),
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// The tick array holding both ticks of the order range
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_0
+    #[account(mut, token::mint = token_vault_0.mint)]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_1
+    #[account(mut, token::mint = token_vault_1.mint)]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(address = token_vault_0.mint)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(address = token_vault_1.mint)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining account
+    // tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLimitOrder<'info> {
+    /// The owner of the order, receives the rent of the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The filled order to claim
+    #[account(
+        mut,
+        close = owner,
+        constraint = limit_order.pool_id == pool_state.key(),
+        constraint = limit_order.owner == owner.key(),
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// The tick array holding both ticks of the order range
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// The pool vault of the bought token
+    #[account(mut)]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the bought token
+    #[account(mut, token::mint = output_vault.mint)]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the bought token
+    #[account(address = output_vault.mint)]
+    pub output_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining account
+    // tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+pub fn place_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, PlaceLimitOrder<'info>>,
+    _order_nonce: u64,
+    tick_array_start_index: i32,
+    tick_lower_index: i32,
+    zero_for_one: bool,
+    amount: u64,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_locked(), ErrorCode::LOK);
+    // the in range liquidity can change without calling the hooks
+    require!(
+        pool_state.hook_permissions == 0,
+        ErrorCode::HookNotSupported
+    );
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let tick_spacing = pool_state.tick_spacing;
+    let tick_upper_index = tick_lower_index
+        .checked_add(i32::from(tick_spacing))
+        .ok_or(ErrorCode::TickUpperOverflow)?;
+    check_tick_array_start_index(tick_array_start_index, tick_lower_index, tick_spacing)?;
+    // both ticks must be in the same tick array, so that a swap crossing the range can fill the order
+    require_eq!(
+        TickArrayState::get_array_start_index(tick_upper_index, tick_spacing),
+        tick_array_start_index,
+        ErrorCode::InvalidLimitOrderRange
+    );
+    check_tick_array_start_index(tick_array_start_index, tick_upper_index, tick_spacing)?;
+    // the order must sit on the side of the price where it only holds the sold token
+    if zero_for_one {
+        require_gt!(
+            tick_lower_index,
+            pool_state.tick_current,
+            ErrorCode::InvalidLimitOrderRange
+        );
+        require_keys_eq!(
+            ctx.accounts.input_vault.key(),
+            pool_state.token_vault_0,
+            ErrorCode::InvalidInputPoolVault
+        );
+    } else {
+        require_gte!(
+            pool_state.tick_current,
+            tick_upper_index,
+            ErrorCode::InvalidLimitOrderRange
+        );
+        require_keys_eq!(
+            ctx.accounts.input_vault.key(),
+            pool_state.token_vault_1,
+            ErrorCode::InvalidInputPoolVault
+        );
+    }
+    require_gt!(amount, 0, ErrorCode::ZeroAmountSpecified);
+
+    // must deduct transfer fee before calculate liquidity
+    let transfer_fee = get_transfer_fee(ctx.accounts.input_vault_mint.clone(), amount)?;
+    let amount_in_vault = amount.checked_sub(transfer_fee).unwrap();
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower_index)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper_index)?;
+    let (liquidity, amount_needed) = if zero_for_one {
+        let liquidity = liquidity_math::get_liquidity_from_amount_0(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in_vault,
+        );
+        require_gt!(liquidity, 0, ErrorCode::InvalidLiquidity);
+        let amount_needed = liquidity_math::get_delta_amount_0_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            true,
+        )?;
+        (liquidity, amount_needed)
+    } else {
+        let liquidity = liquidity_math::get_liquidity_from_amount_1(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in_vault,
+        );
+        require_gt!(liquidity, 0, ErrorCode::InvalidLiquidity);
+        let amount_needed = liquidity_math::get_delta_amount_1_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            true,
+        )?;
+        (liquidity, amount_needed)
+    };
+
+    let tick_array_loader = TickArrayState::get_or_create_tick_array(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.tick_array.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.pool_state,
+        tick_array_start_index,
+        tick_spacing,
+    )?;
+    let fill_count = {
+        let mut tick_array = tick_array_loader.load_mut()?;
+        update_limit_order_ticks(
+            &mut pool_state,
+            &mut tick_array,
+            get_tickarray_bitmap_extension(ctx.remaining_accounts, &ctx.accounts.pool_state),
+            tick_lower_index,
+            tick_upper_index,
+            i128::try_from(liquidity).unwrap(),
+        )?;
+        let tick_lower_state = tick_array.get_tick_state_mut(tick_lower_index, tick_spacing)?;
+        if zero_for_one {
+            tick_lower_state.limit_order_liquidity_0 = tick_lower_state
+                .limit_order_liquidity_0
+                .checked_add(liquidity)
+                .unwrap();
+        } else {
+            tick_lower_state.limit_order_liquidity_1 = tick_lower_state
+                .limit_order_liquidity_1
+                .checked_add(liquidity)
+                .unwrap();
+        }
+        tick_lower_state.limit_order_fill_count
+    };
+
+    let limit_order = &mut ctx.accounts.limit_order;
+    limit_order.bump = ctx.bumps.limit_order;
+    limit_order.pool_id = ctx.accounts.pool_state.key();
+    limit_order.owner = ctx.accounts.owner.key();
+    limit_order.tick_lower_index = tick_lower_index;
+    limit_order.tick_upper_index = tick_upper_index;
+    limit_order.zero_for_one = zero_for_one;
+    limit_order.liquidity = liquidity;
+    limit_order.fill_count = fill_count;
+    drop(pool_state);
+
+    let transfer_fee =
+        get_transfer_inverse_fee(ctx.accounts.input_vault_mint.clone(), amount_needed)?;
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.owner,
+        &ctx.accounts.input_token_account.to_account_info(),
+        &ctx.accounts.input_vault.to_account_info(),
+        Some(ctx.accounts.input_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_needed.checked_add(transfer_fee).unwrap(),
+    )?;
+
+    emit!(PlaceLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        limit_order: ctx.accounts.limit_order.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower_index,
+        tick_upper_index,
+        zero_for_one,
+        liquidity,
+        amount: amount_needed.checked_add(transfer_fee).unwrap(),
+        transfer_fee,
+    });
+    Ok(())
+}
+
+pub fn cancel_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CancelLimitOrder<'info>>,
+) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_locked(), ErrorCode::LOK);
+    // the in range liquidity can change without calling the hooks
+    require!(
+        pool_state.hook_permissions == 0,
+        ErrorCode::HookNotSupported
+    );
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let tick_spacing = pool_state.tick_spacing;
+    let liquidity = limit_order.liquidity;
+    let (amount_0, amount_1) = {
+        let mut tick_array = ctx.accounts.tick_array.load_mut()?;
+        let tick_lower_state =
+            tick_array.get_tick_state_mut(limit_order.tick_lower_index, tick_spacing)?;
+        require_eq!(
+            tick_lower_state.limit_order_fill_count,
+            limit_order.fill_count,
+            ErrorCode::LimitOrderFilled
+        );
+        if limit_order.zero_for_one {
+            tick_lower_state.limit_order_liquidity_0 = tick_lower_state
+                .limit_order_liquidity_0
+                .checked_sub(liquidity)
+                .unwrap();
+        } else {
+            tick_lower_state.limit_order_liquidity_1 = tick_lower_state
+                .limit_order_liquidity_1
+                .checked_sub(liquidity)
+                .unwrap();
+        }
+
+        let liquidity_delta = -i128::try_from(liquidity).unwrap();
+        update_limit_order_ticks(
+            &mut pool_state,
+            &mut tick_array,
+            get_tickarray_bitmap_extension(ctx.remaining_accounts, &ctx.accounts.pool_state),
+            limit_order.tick_lower_index,
+            limit_order.tick_upper_index,
+            liquidity_delta,
+        )?;
+        // the order is partially converted while the price is inside its range
+        if pool_state.tick_current >= limit_order.tick_lower_index
+            && pool_state.tick_current < limit_order.tick_upper_index
+        {
+            pool_state.liquidity = pool_state.liquidity.checked_sub(liquidity).unwrap();
+            pool_state.limit_order_liquidity = pool_state
+                .limit_order_liquidity
+                .checked_sub(liquidity)
+                .unwrap();
+        }
+        liquidity_math::get_delta_amounts_signed(
+            pool_state.tick_current,
+            pool_state.sqrt_price_x64,
+            limit_order.tick_lower_index,
+            limit_order.tick_upper_index,
+            liquidity_delta,
+        )?
+    };
+    drop(pool_state);
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.recipient_token_account_0.to_account_info(),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.recipient_token_account_1.to_account_info(),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+
+    emit!(CancelLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        limit_order: ctx.accounts.limit_order.key(),
+        owner: ctx.accounts.owner.key(),
+        liquidity,
+        amount_0,
+        amount_1,
+    });
+    Ok(())
+}
+
+pub fn claim_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ClaimLimitOrder<'info>>,
+) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_locked(), ErrorCode::LOK);
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity) {
+        return err!(ErrorCode::NotApproved);
+    }
+    // the order sold token_0 for token_1 or the other way around
+    require_keys_eq!(
+        ctx.accounts.output_vault.key(),
+        if limit_order.zero_for_one {
+            pool_state.token_vault_1
+        } else {
+            pool_state.token_vault_0
+        },
+        ErrorCode::InvalidInputPoolVault
+    );
+    let tick_spacing = pool_state.tick_spacing;
+    let liquidity = limit_order.liquidity;
+    {
+        let mut tick_array = ctx.accounts.tick_array.load_mut()?;
+        let tick_lower_state =
+            tick_array.get_tick_state_mut(limit_order.tick_lower_index, tick_spacing)?;
+        require_neq!(
+            tick_lower_state.limit_order_fill_count,
+            limit_order.fill_count,
+            ErrorCode::LimitOrderNotFilled
+        );
+        let flipped_lower = tick_lower_state.remove_filled_limit_order(liquidity)?;
+        let flipped_upper = tick_array
+            .get_tick_state_mut(limit_order.tick_upper_index, tick_spacing)?
+            .remove_filled_limit_order(liquidity)?;
+        update_tick_array_initialized_count(
+            &mut pool_state,
+            &mut tick_array,
+            get_tickarray_bitmap_extension(ctx.remaining_accounts, &ctx.accounts.pool_state),
+            flipped_lower,
+            flipped_upper,
+            false,
+        )?;
+    }
+    drop(pool_state);
+
+    // the swaps filling the order paid at least the rounded down amount over the whole range
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(limit_order.tick_lower_index)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(limit_order.tick_upper_index)?;
+    let amount = if limit_order.zero_for_one {
+        liquidity_math::get_delta_amount_1_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            false,
+        )?
+    } else {
+        liquidity_math::get_delta_amount_0_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            false,
+        )?
+    };
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.output_vault.to_account_info(),
+        &ctx.accounts.recipient_token_account.to_account_info(),
+        Some(ctx.accounts.output_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount,
+    )?;
+
+    emit!(ClaimLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        limit_order: ctx.accounts.limit_order.key(),
+        owner: ctx.accounts.owner.key(),
+        liquidity,
+        zero_for_one: limit_order.zero_for_one,
+        amount,
+    });
+    Ok(())
+}
+
+fn get_tickarray_bitmap_extension<'c: 'info, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+) -> Option<&'c AccountInfo<'info>> {
+    remaining_accounts.iter().find(|account_info| {
+        account_info.key() == TickArrayBitmapExtension::key(pool_state_loader.key())
+    })
+}
+
+/// Adds (removes) the order liquidity to (from) both ticks of the order range
+fn update_limit_order_ticks<'c: 'info, 'info>(
+    pool_state: &mut RefMut<PoolState>,
+    tick_array: &mut RefMut<TickArrayState>,
+    tickarray_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    liquidity_delta: i128,
+) -> Result<()> {
+    let updated_reward_infos =
+        pool_state.update_reward_infos(Clock::get()?.unix_timestamp as u64)?;
+    let mut flipped = [false; 2];
+    for (i, (tick_index, upper)) in [(tick_lower_index, false), (tick_upper_index, true)]
+        .into_iter()
+        .enumerate()
+    {
+        let tick_state = tick_array.get_tick_state_mut(tick_index, pool_state.tick_spacing)?;
+        if tick_state.tick == 0 {
+            tick_state.tick = tick_index;
+        }
+        flipped[i] = tick_state.update(
+            pool_state.tick_current,
+            liquidity_delta,
+            pool_state.fee_growth_global_0_x64,
+            pool_state.fee_growth_global_1_x64,
+            upper,
+            &updated_reward_infos,
+        )?;
+        if flipped[i] && liquidity_delta < 0 {
+            tick_state.clear();
+        }
+    }
+    update_tick_array_initialized_count(
+        pool_state,
+        tick_array,
+        tickarray_bitmap_extension,
+        flipped[0],
+        flipped[1],
+        liquidity_delta > 0,
+    )
+}
+
+fn update_tick_array_initialized_count<'c: 'info, 'info>(
+    pool_state: &mut RefMut<PoolState>,
+    tick_array: &mut RefMut<TickArrayState>,
+    tickarray_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    flipped_lower: bool,
+    flipped_upper: bool,
+    add: bool,
+) -> Result<()> {
+    let before_init_tick_count = tick_array.initialized_tick_count;
+    for flipped in [flipped_lower, flipped_upper] {
+        if flipped {
+            tick_array.update_initialized_tick_count(add)?;
+        }
+    }
+    if (before_init_tick_count == 0) != (tick_array.initialized_tick_count == 0) {
+        pool_state.flip_tick_array_bit(tickarray_bitmap_extension, tick_array.start_tick_index)?;
+    }
+    Ok(())
+}
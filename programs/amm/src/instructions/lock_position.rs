@@ -0,0 +1,164 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct LockPosition<'info> {
+    /// The position owner, or the lock authority releasing the lock early
+    pub authority: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lock the liquidity of this position
+    #[account(mut)]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+}
+
+/// Sets the time before which the liquidity of the position can't be removed.
+/// While the position is locked the owner can only extend the lock, the lock authority can
+/// shorten or release it
+pub fn lock_position(
+    ctx: Context<LockPosition>,
+    unlock_timestamp: u64,
+    lock_authority: Option<Pubkey>,
+) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    let authority = ctx.accounts.authority.key();
+    let lock_authority = lock_authority.unwrap_or_default();
+    check_lock_update(
+        personal_position,
+        authority,
+        ctx.accounts.nft_account.owner,
+        unlock_timestamp,
+        lock_authority,
+        u64::try_from(Clock::get()?.unix_timestamp).unwrap(),
+    )?;
+    personal_position.unlock_timestamp = unlock_timestamp;
+    personal_position.lock_authority = lock_authority;
+
+    emit!(PositionLockedEvent {
+        position_nft_mint: personal_position.nft_mint,
+        authority,
+        unlock_timestamp,
+        lock_authority,
+    });
+
+    Ok(())
+}
+
+fn check_lock_update(
+    personal_position: &PersonalPositionState,
+    authority: Pubkey,
+    owner: Pubkey,
+    unlock_timestamp: u64,
+    lock_authority: Pubkey,
+    block_timestamp: u64,
+) -> Result<()> {
+    if personal_position.is_locked(block_timestamp) {
+        if personal_position.lock_authority != Pubkey::default()
+            && authority == personal_position.lock_authority
+        {
+            return Ok(());
+        }
+        require_keys_eq!(authority, owner, ErrorCode::NotApproved);
+        require_keys_eq!(
+            lock_authority,
+            personal_position.lock_authority,
+            ErrorCode::PositionLocked
+        );
+        require_gte!(
+            unlock_timestamp,
+            personal_position.unlock_timestamp,
+            ErrorCode::InvalidUnlockTimestamp
+        );
+    } else {
+        require_keys_eq!(authority, owner, ErrorCode::NotApproved);
+        require_gt!(
+            unlock_timestamp,
+            block_timestamp,
+            ErrorCode::InvalidUnlockTimestamp
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod lock_position_test {
+    use super::*;
+
+    #[test]
+    fn check_lock_update_test() {
+        let owner = Pubkey::new_unique();
+        let lock_authority = Pubkey::new_unique();
+        let mut personal_position = PersonalPositionState::default();
+
+        // only the owner can lock, in the future
+        assert!(
+            check_lock_update(&personal_position, owner, owner, 100, lock_authority, 50).is_ok()
+        );
+        assert!(check_lock_update(
+            &personal_position,
+            lock_authority,
+            owner,
+            100,
+            lock_authority,
+            50
+        )
+        .is_err());
+        assert!(
+            check_lock_update(&personal_position, owner, owner, 50, lock_authority, 50).is_err()
+        );
+
+        personal_position.unlock_timestamp = 100;
+        personal_position.lock_authority = lock_authority;
+        // the owner can only extend the lock
+        assert!(
+            check_lock_update(&personal_position, owner, owner, 200, lock_authority, 50).is_ok()
+        );
+        assert!(
+            check_lock_update(&personal_position, owner, owner, 99, lock_authority, 50).is_err()
+        );
+        assert!(
+            check_lock_update(&personal_position, owner, owner, 200, Pubkey::default(), 50)
+                .is_err()
+        );
+        // the lock authority can release the lock
+        assert!(check_lock_update(
+            &personal_position,
+            lock_authority,
+            owner,
+            0,
+            Pubkey::default(),
+            50
+        )
+        .is_ok());
+        // the lock expired
+        assert!(check_lock_update(
+            &personal_position,
+            owner,
+            owner,
+            150,
+            Pubkey::default(),
+            100
+        )
+        .is_ok());
+
+        // no one can release a lock without an authority
+        personal_position.lock_authority = Pubkey::default();
+        assert!(check_lock_update(
+            &personal_position,
+            Pubkey::default(),
+            owner,
+            0,
+            Pubkey::default(),
+            50
+        )
+        .is_err());
+    }
+}
@@ -25,15 +25,60 @@ pub use decrease_liquidity::*;
 pub mod decrease_liquidity_v2;
 pub use decrease_liquidity_v2::*;
 
+pub mod collect_fees;
+pub use collect_fees::*;
+
+pub mod collect_rewards;
+pub use collect_rewards::*;
+
+pub mod compound_position;
+pub use compound_position::*;
+
+pub mod rebalance_position;
+pub use rebalance_position::*;
+
+pub mod increase_liquidity_zap;
+pub use increase_liquidity_zap::*;
+
+pub mod open_position_zap;
+pub use open_position_zap::*;
+
+pub mod decrease_liquidity_zap_out;
+pub use decrease_liquidity_zap_out::*;
+
+pub mod lock_position;
+pub use lock_position::*;
+
 pub mod swap;
 pub use swap::*;
 
 pub mod swap_v2;
 pub use swap_v2::*;
 
+pub mod quote_swap;
+pub use quote_swap::*;
+
+pub mod flash_swap;
+pub use flash_swap::*;
+
+pub mod flash_loan;
+pub use flash_loan::*;
+
+pub mod limit_order;
+pub use limit_order::*;
+
+pub mod twamm;
+pub use twamm::*;
+
 pub mod swap_router_base_in;
 pub use swap_router_base_in::*;
 
+pub mod swap_router_base_in_v2;
+pub use swap_router_base_in_v2::*;
+
+pub mod swap_router_base_out;
+pub use swap_router_base_out::*;
+
 pub mod update_reward_info;
 pub use update_reward_info::*;
 
@@ -46,5 +91,8 @@ pub use set_reward_params::*;
 pub mod collect_remaining_rewards;
 pub use collect_remaining_rewards::*;
 
+pub mod collect_creator_fee;
+pub use collect_creator_fee::*;
+
 pub mod admin;
 pub use admin::*;
@@ -260,8 +260,28 @@ pub fn open_position<'a, 'b, 'c: 'info, 'info>(
     use_metadata_extension: bool,
 ) -> Result<()> {
     let mut liquidity = liquidity;
-    {
+    let (remaining_accounts, hook_accounts) = split_hook_accounts(
+        pool_state_loader,
+        remaining_accounts,
+        &MODIFY_POSITION_HOOK_PERMISSIONS,
+    )?;
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeModifyPosition,
+        BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &BeforeModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: position_nft_owner.key(),
+            tick_lower_index,
+            tick_upper_index,
+            liquidity_delta: i128::try_from(liquidity).unwrap(),
+        },
+    )?;
+
+    let (amount_0, amount_1) = {
         let pool_state = &mut pool_state_loader.load_mut()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
         if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
             return err!(ErrorCode::NotApproved);
         }
@@ -384,7 +404,24 @@ pub fn open_position<'a, 'b, 'c: 'info, 'info>(
             deposit_amount_0_transfer_fee: amount_0_transfer_fee,
             deposit_amount_1_transfer_fee: amount_1_transfer_fee
         });
-    }
+        (amount_0, amount_1)
+    };
+
+    invoke_hook(
+        pool_state_loader,
+        hook_accounts,
+        HookPermissionBitIndex::AfterModifyPosition,
+        AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+        &AfterModifyPositionHook {
+            pool_state: pool_state_loader.key(),
+            owner: position_nft_owner.key(),
+            tick_lower_index,
+            tick_upper_index,
+            liquidity_delta: i128::try_from(liquidity).unwrap(),
+            amount_0,
+            amount_1,
+        },
+    )?;
 
     mint_nft_and_remove_mint_authority(
         payer,
@@ -471,73 +508,17 @@ pub fn add_liquidity<'b, 'c: 'info, 'info>(
     }
     assert!(*liquidity > 0);
     let liquidity_before = pool_state.liquidity;
-    require_keys_eq!(tick_array_lower_loader.load()?.pool_id, pool_state.key());
-    require_keys_eq!(tick_array_upper_loader.load()?.pool_id, pool_state.key());
-
-    // get tick_state
-    let mut tick_lower_state = *tick_array_lower_loader
-        .load_mut()?
-        .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
-    let mut tick_upper_state = *tick_array_upper_loader
-        .load_mut()?
-        .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
-    if tick_lower_state.tick == 0 {
-        tick_lower_state.tick = tick_lower_index;
-    }
-    if tick_upper_state.tick == 0 {
-        tick_upper_state.tick = tick_upper_index;
-    }
-    let clock = Clock::get()?;
-    let (amount_0, amount_1, flip_tick_lower, flip_tick_upper) = modify_position(
-        i128::try_from(*liquidity).unwrap(),
-        pool_state,
+    let (amount_0, amount_1) = add_liquidity_to_ticks(
+        tick_array_lower_loader,
+        tick_array_upper_loader,
         protocol_position,
-        &mut tick_lower_state,
-        &mut tick_upper_state,
-        clock.unix_timestamp as u64,
-    )?;
-
-    // update tick_state
-    tick_array_lower_loader.load_mut()?.update_tick_state(
+        tick_array_bitmap_extension,
+        pool_state,
+        *liquidity,
         tick_lower_index,
-        pool_state.tick_spacing,
-        tick_lower_state,
-    )?;
-    tick_array_upper_loader.load_mut()?.update_tick_state(
         tick_upper_index,
-        pool_state.tick_spacing,
-        tick_upper_state,
     )?;
 
-    if flip_tick_lower {
-        let mut tick_array_lower = tick_array_lower_loader.load_mut()?;
-        let before_init_tick_count = tick_array_lower.initialized_tick_count;
-        tick_array_lower.update_initialized_tick_count(true)?;
-
-        if before_init_tick_count == 0 {
-            pool_state.flip_tick_array_bit(
-                tick_array_bitmap_extension,
-                tick_array_lower.start_tick_index,
-            )?;
-        }
-    }
-    if flip_tick_upper {
-        let mut tick_array_upper = tick_array_upper_loader.load_mut()?;
-        let before_init_tick_count = tick_array_upper.initialized_tick_count;
-        tick_array_upper.update_initialized_tick_count(true)?;
-
-        if before_init_tick_count == 0 {
-            pool_state.flip_tick_array_bit(
-                tick_array_bitmap_extension,
-                tick_array_upper.start_tick_index,
-            )?;
-        }
-    }
-    require!(
-        amount_0 > 0 || amount_1 > 0,
-        ErrorCode::ForbidBothZeroForSupplyLiquidity
-    );
-
     let mut amount_0_transfer_fee = 0;
     let mut amount_1_transfer_fee = 0;
     if vault_0_mint.is_some() {
@@ -615,6 +596,87 @@ pub fn add_liquidity<'b, 'c: 'info, 'info>(
     ))
 }
 
+/// Adds the liquidity to the ticks of the position and to the pool,
+/// returns the amounts of token_0 and token_1 the liquidity is worth
+pub fn add_liquidity_to_ticks<'c: 'info, 'info>(
+    tick_array_lower_loader: &AccountLoad<'info, TickArrayState>,
+    tick_array_upper_loader: &AccountLoad<'info, TickArrayState>,
+    protocol_position: &mut ProtocolPositionState,
+    tick_array_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    pool_state: &mut RefMut<PoolState>,
+    liquidity: u128,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+) -> Result<(u64, u64)> {
+    require_keys_eq!(tick_array_lower_loader.load()?.pool_id, pool_state.key());
+    require_keys_eq!(tick_array_upper_loader.load()?.pool_id, pool_state.key());
+
+    // get tick_state
+    let mut tick_lower_state = *tick_array_lower_loader
+        .load_mut()?
+        .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
+    let mut tick_upper_state = *tick_array_upper_loader
+        .load_mut()?
+        .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
+    if tick_lower_state.tick == 0 {
+        tick_lower_state.tick = tick_lower_index;
+    }
+    if tick_upper_state.tick == 0 {
+        tick_upper_state.tick = tick_upper_index;
+    }
+    let clock = Clock::get()?;
+    let (amount_0, amount_1, flip_tick_lower, flip_tick_upper) = modify_position(
+        i128::try_from(liquidity).unwrap(),
+        pool_state,
+        protocol_position,
+        &mut tick_lower_state,
+        &mut tick_upper_state,
+        clock.unix_timestamp as u64,
+    )?;
+
+    // update tick_state
+    tick_array_lower_loader.load_mut()?.update_tick_state(
+        tick_lower_index,
+        pool_state.tick_spacing,
+        tick_lower_state,
+    )?;
+    tick_array_upper_loader.load_mut()?.update_tick_state(
+        tick_upper_index,
+        pool_state.tick_spacing,
+        tick_upper_state,
+    )?;
+
+    if flip_tick_lower {
+        let mut tick_array_lower = tick_array_lower_loader.load_mut()?;
+        let before_init_tick_count = tick_array_lower.initialized_tick_count;
+        tick_array_lower.update_initialized_tick_count(true)?;
+
+        if before_init_tick_count == 0 {
+            pool_state.flip_tick_array_bit(
+                tick_array_bitmap_extension,
+                tick_array_lower.start_tick_index,
+            )?;
+        }
+    }
+    if flip_tick_upper {
+        let mut tick_array_upper = tick_array_upper_loader.load_mut()?;
+        let before_init_tick_count = tick_array_upper.initialized_tick_count;
+        tick_array_upper.update_initialized_tick_count(true)?;
+
+        if before_init_tick_count == 0 {
+            pool_state.flip_tick_array_bit(
+                tick_array_bitmap_extension,
+                tick_array_upper.start_tick_index,
+            )?;
+        }
+    }
+    require!(
+        amount_0 > 0 || amount_1 > 0,
+        ErrorCode::ForbidBothZeroForSupplyLiquidity
+    );
+    Ok((amount_0, amount_1))
+}
+
 pub fn modify_position(
     liquidity_delta: i128,
     pool_state: &mut RefMut<PoolState>,
@@ -0,0 +1,358 @@
+use super::{open_position, zap_into_position};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::create_position_nft_mint_with_extensions;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{create, AssociatedToken, Create};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(tick_lower_index: i32, tick_upper_index: i32,tick_array_lower_start_index:i32,tick_array_upper_start_index:i32)]
+pub struct OpenPositionZap<'info> {
+    /// Pays to mint the position and pays the deposited token
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Receives the position NFT
+    pub position_nft_owner: UncheckedAccount<'info>,
+
+    /// Unique token mint address, initialize in contract
+    #[account(mut)]
+    pub position_nft_mint: Signer<'info>,
+
+    /// CHECK: ATA address where position NFT will be minted, initialize in contract
+    #[account(mut)]
+    pub position_nft_account: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Add liquidity for this pool
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Store the information of market marking in range
+    #[account(
+        init_if_needed,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = ProtocolPositionState::LEN
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// CHECK:  Account to store data for the position's lower tick
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: Account to store data for the position's upper tick
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    /// personal position state
+    #[account(
+        init,
+        seeds = [POSITION_SEED.as_bytes(), position_nft_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PersonalPositionState::LEN
+    )]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The payer's token account for token_0, receives the token_0 left over
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account for token_1, receives the token_1 left over
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Sysvar for token mint and ATA creation
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Program to create the position manager state account
+    pub system_program: Program<'info, System>,
+
+    /// Program to transfer for token account
+    pub token_program: Program<'info, Token>,
+
+    /// Program to create an ATA for receiving position NFT
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Program to create NFT mint/token account and transfer for token22 account
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must be the first account if need
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+/// Opens a position wrapped in a Token2022 NFT with a single token, the position is opened
+/// without liquidity and the deposit is zapped into it like `increase_liquidity_zap` does
+pub fn open_position_zap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, OpenPositionZap<'info>>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+    with_metadata: bool,
+) -> Result<()> {
+    // the liquidity change and the swap are done without calling the hooks
+    require!(
+        ctx.accounts.pool_state.load()?.hook_permissions == 0,
+        ErrorCode::HookNotSupported
+    );
+    create_position_nft_mint_with_extensions(
+        &ctx.accounts.payer,
+        &ctx.accounts.position_nft_mint,
+        &ctx.accounts.pool_state.to_account_info(),
+        &ctx.accounts.personal_position.to_account_info(),
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program_2022,
+        with_metadata,
+    )?;
+
+    // create user position nft account
+    create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.position_nft_account.to_account_info(),
+            authority: ctx.accounts.position_nft_owner.to_account_info(),
+            mint: ctx.accounts.position_nft_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program_2022.to_account_info(),
+        },
+    ))?;
+
+    // open the position with zero liquidity, it creates the tick arrays if needed
+    open_position(
+        &ctx.accounts.payer,
+        &ctx.accounts.position_nft_owner,
+        &ctx.accounts.position_nft_mint,
+        &ctx.accounts.position_nft_account,
+        None,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.token_account_0.to_account_info(),
+        &ctx.accounts.token_account_1.to_account_info(),
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.rent,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        None,
+        Some(&ctx.accounts.token_program_2022),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        ctx.remaining_accounts,
+        ctx.bumps.protocol_position,
+        ctx.bumps.personal_position,
+        0,
+        0,
+        0,
+        tick_lower_index,
+        tick_upper_index,
+        tick_array_lower_start_index,
+        tick_array_upper_start_index,
+        with_metadata,
+        None,
+        true,
+    )?;
+
+    zap_into_position(
+        &ctx.accounts.payer,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.observation_state,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.tick_array_lower.to_account_info(),
+        &ctx.accounts.tick_array_upper.to_account_info(),
+        &ctx.accounts.token_account_0.to_account_info(),
+        &ctx.accounts.token_account_1.to_account_info(),
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        ctx.accounts.vault_0_mint.clone(),
+        ctx.accounts.vault_1_mint.clone(),
+        ctx.remaining_accounts,
+        amount_in,
+        zero_for_one,
+        liquidity_min,
+    )
+}
+
+#[cfg(test)]
+mod open_position_zap_test {
+    use super::*;
+    use crate::instructions::get_zap_liquidity;
+    use crate::libraries::{liquidity_math, tick_math};
+
+    #[test]
+    fn zap_single_token_into_new_position_test() {
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let trade_fee_rate = 2500;
+        let amount_in = 1_000_000_000;
+
+        // the range above the price only holds token_0
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(600).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(1200).unwrap();
+        let swap_amount = liquidity_math::get_zap_swap_amount(
+            sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            true,
+            trade_fee_rate,
+        );
+        assert_eq!(swap_amount, 0);
+        let liquidity = get_zap_liquidity(
+            sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            0,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            liquidity,
+            liquidity_math::get_liquidity_from_single_amount_0(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in,
+            )
+        );
+        // a deposit of token_1 is swapped entirely
+        let swap_amount = liquidity_math::get_zap_swap_amount(
+            sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            false,
+            trade_fee_rate,
+        );
+        assert_eq!(swap_amount, amount_in);
+        // the token_1 left over can't open the position
+        assert_eq!(
+            get_zap_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                0,
+                amount_in,
+                0,
+            )
+            .unwrap_err(),
+            ErrorCode::ForbidBothZeroForSupplyLiquidity.into()
+        );
+
+        // the range around the price is opened with both tokens
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1200).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(1200).unwrap();
+        let swap_amount = liquidity_math::get_zap_swap_amount(
+            sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            true,
+            trade_fee_rate,
+        );
+        assert!(swap_amount > 0 && swap_amount < amount_in);
+        // the swap at the price of tick 0 pays the input minus the fee
+        let amount_out = swap_amount - swap_amount * u64::from(trade_fee_rate) / 1_000_000;
+        let liquidity = get_zap_liquidity(
+            sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in - swap_amount,
+            amount_out,
+            0,
+        )
+        .unwrap();
+        assert!(liquidity > 0);
+        assert_eq!(
+            get_zap_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in - swap_amount,
+                amount_out,
+                liquidity + 1,
+            )
+            .unwrap_err(),
+            ErrorCode::PriceSlippageCheck.into()
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::error::ErrorCode;
+use crate::libraries::tick_math;
+use crate::states::*;
+use crate::swap::swap_internal_with_state;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_lang::ZeroCopy;
+use std::cell::{RefCell, RefMut};
+use std::collections::VecDeque;
+use std::mem;
+use std::ops::Deref;
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool to quote
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+/// The result of `quote_swap`, set as the return data of the instruction.
+/// The amounts are the pool side amounts, token 2022 transfer fees are not included
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwapQuote {
+    /// The amount of input token paid into the pool, the trade fee included
+    pub amount_in: u64,
+    /// The amount of output token paid out of the pool
+    pub amount_out: u64,
+    /// The trade fee credited to the liquidity providers
+    pub lp_fee: u64,
+    /// The trade fee taken as protocol fee
+    pub protocol_fee: u64,
+    /// The trade fee taken as fund fee
+    pub fund_fee: u64,
+    /// The trade fee taken as pool creator fee
+    pub creator_fee: u64,
+    /// The trade fee paid to the referrer
+    pub referral_fee: u64,
+    /// The sqrt price of the pool after the swap
+    pub sqrt_price_x64: u128,
+    /// The tick of the pool after the swap
+    pub tick: i32,
+    /// The number of initialized ticks crossed by the swap
+    pub ticks_crossed: u32,
+}
+
+/// Copies the data of a zero copy account, so the swap can run against the copy without writing the account.
+/// Only used for the pool and the observation, the tick arrays are too large to be copied onto the heap
+/// and are read in place as the quote never writes them
+fn copy_account_data<'info, T: ZeroCopy + Owner>(
+    account_loader: &AccountLoader<'info, T>,
+) -> Result<RefCell<Vec<u8>>> {
+    Ok(RefCell::new(
+        account_loader.as_ref().try_borrow_data()?.to_vec(),
+    ))
+}
+
+fn load_copy_mut<T: ZeroCopy>(data: &RefCell<Vec<u8>>) -> RefMut<'_, T> {
+    RefMut::map(data.borrow_mut(), |data| {
+        bytemuck::from_bytes_mut(&mut data[8..mem::size_of::<T>() + 8])
+    })
+}
+
+pub fn quote_swap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, QuoteSwap<'info>>,
+    amount: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
+) -> Result<()> {
+    require_gte!(
+        ctx.accounts.amm_config.referral_fee_bps_max,
+        referral_fee_bps,
+        ErrorCode::ReferralFeeTooHigh
+    );
+    let pool_state_data = copy_account_data(&ctx.accounts.pool_state)?;
+    let observation_state_data = copy_account_data(&ctx.accounts.observation_state)?;
+    let pool_state = &mut load_copy_mut::<PoolState>(&pool_state_data);
+
+    let mut tickarray_bitmap_extension = None;
+    let mut tick_array_loaders = Vec::new();
+    let mut pool_fee_override = None;
+    let pool_fee_override_key = if pool_state.is_fee_overridden() {
+        Some(PoolFeeOverride::key(ctx.accounts.pool_state.key()))
+    } else {
+        None
+    };
+    let tick_array_bitmap_extension_key =
+        TickArrayBitmapExtension::key(ctx.accounts.pool_state.key());
+    for account_info in ctx.remaining_accounts.iter() {
+        if pool_fee_override_key == Some(account_info.key()) {
+            pool_fee_override =
+                Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+            continue;
+        }
+        if account_info.key().eq(&tick_array_bitmap_extension_key) {
+            tickarray_bitmap_extension = Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            );
+            continue;
+        }
+        tick_array_loaders.push(AccountLoader::<TickArrayState>::try_from(account_info)?);
+    }
+    let tick_array_states = &mut tick_array_loaders
+        .iter()
+        .map(|tick_array_loader| tick_array_loader.load())
+        .collect::<Result<VecDeque<_>>>()?;
+
+    let amm_config =
+        get_effective_amm_config(&ctx.accounts.amm_config, pool_state, pool_fee_override)?;
+    let (amount_0, amount_1, state) = swap_internal_with_state(
+        &amm_config,
+        pool_state,
+        tick_array_states,
+        &mut load_copy_mut::<ObservationState>(&observation_state_data),
+        &tickarray_bitmap_extension,
+        amount,
+        if sqrt_price_limit_x64 == 0 {
+            if zero_for_one {
+                tick_math::MIN_SQRT_PRICE_X64 + 1
+            } else {
+                tick_math::MAX_SQRT_PRICE_X64 - 1
+            }
+        } else {
+            sqrt_price_limit_x64
+        },
+        zero_for_one,
+        is_base_input,
+        referral_fee_bps,
+        max_deviation_ticks,
+        allow_partial_fill,
+        oracle::block_timestamp(),
+    )?;
+    let (amount_in, amount_out) = if zero_for_one {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    };
+
+    let quote = SwapQuote {
+        amount_in,
+        amount_out,
+        lp_fee: state.fee_amount,
+        protocol_fee: state.protocol_fee,
+        fund_fee: state.fund_fee,
+        creator_fee: state.creator_fee,
+        referral_fee: state.referral_fee,
+        sqrt_price_x64: state.sqrt_price_x64,
+        tick: state.tick,
+        ticks_crossed: state.ticks_crossed,
+    };
+    set_return_data(&quote.try_to_vec()?);
+    Ok(())
+}
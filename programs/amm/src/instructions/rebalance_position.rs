@@ -0,0 +1,665 @@
+use super::{
+    add_liquidity_to_ticks, burn_liquidity, calculate_latest_token_fees,
+    check_unclaimed_fees_and_vault, emit_circuit_breaker_tripped, swap_internal_with_state,
+};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+
+#[derive(Accounts)]
+#[instruction(tick_lower_index: i32, tick_upper_index: i32, tick_array_lower_start_index: i32, tick_array_upper_start_index: i32)]
+pub struct RebalancePosition<'info> {
+    /// The position owner, pays for the accounts of the new range
+    #[account(mut)]
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1,
+        token::authority = nft_owner,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Move the liquidity of this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The protocol position of the current range
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores the lower tick of the current range
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores the upper tick of the current range
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The protocol position of the new range
+    #[account(
+        init_if_needed,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        payer = nft_owner,
+        space = ProtocolPositionState::LEN
+    )]
+    pub new_protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// CHECK: Account to store data for the lower tick of the new range
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub new_tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: Account to store data for the upper tick of the new range
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub new_tick_array_upper: UncheckedAccount<'info>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the token_0 left over
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the token_1 left over
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Program to create the new protocol position and tick arrays
+    pub system_program: Program<'info, System>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+/// Moves all the liquidity of the position to a new range on the same position nft.
+/// The withdrawn tokens stay in the vaults, if `swap_surplus` the part of the surplus computed by
+/// `get_rebalance_swap_amount` is swapped through the pool before the deposit and the tokens
+/// the new range can't take are sent to the recipients. The fees and rewards owed to the position are kept
+pub fn rebalance_position<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, RebalancePosition<'info>>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    liquidity_min: u128,
+    swap_surplus: bool,
+    sqrt_price_limit_x64: u128,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let personal_position = &mut ctx.accounts.personal_position;
+    let protocol_position = &mut ctx.accounts.protocol_position;
+    let tick_lower_index_before = personal_position.tick_lower_index;
+    let tick_upper_index_before = personal_position.tick_upper_index;
+    let liquidity_before = personal_position.liquidity;
+    require!(
+        tick_lower_index != tick_lower_index_before || tick_upper_index != tick_upper_index_before,
+        ErrorCode::SameTickRange
+    );
+    require!(
+        !personal_position.is_locked(u64::try_from(Clock::get()?.unix_timestamp).unwrap()),
+        ErrorCode::PositionLocked
+    );
+
+    let mut tickarray_bitmap_extension = None;
+    let mut tick_array_accounts = Vec::new();
+    let amm_config = {
+        let pool_state = pool_state_loader.load()?;
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        // the liquidity changes and the swap are done without calling the hooks
+        require!(
+            pool_state.hook_permissions == 0,
+            ErrorCode::HookNotSupported
+        );
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        check_ticks_order(tick_lower_index, tick_upper_index)?;
+        check_tick_array_start_index(
+            tick_array_lower_start_index,
+            tick_lower_index,
+            pool_state.tick_spacing,
+        )?;
+        check_tick_array_start_index(
+            tick_array_upper_start_index,
+            tick_upper_index,
+            pool_state.tick_spacing,
+        )?;
+
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
+        let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
+        for account_info in ctx.remaining_accounts.iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
+            if account_info.key().eq(&tick_array_bitmap_extension_key) {
+                tickarray_bitmap_extension = Some(account_info);
+                continue;
+            }
+            tick_array_accounts.push(account_info);
+        }
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![
+            ctx.accounts.tick_array_lower.load()?.start_tick_index,
+            ctx.accounts.tick_array_upper.load()?.start_tick_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+        ]) {
+            require!(
+                tickarray_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+        get_effective_amm_config(&ctx.accounts.amm_config, &pool_state, pool_fee_override)?
+    };
+
+    // withdraw all the liquidity of the current range, the tokens stay in the vaults
+    let (decrease_amount_0, decrease_amount_1) = burn_liquidity(
+        &mut pool_state_loader.load_mut()?,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+        protocol_position,
+        tickarray_bitmap_extension,
+        liquidity_before,
+    )?;
+    personal_position.token_fees_owed_0 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_0,
+        personal_position.fee_growth_inside_0_last_x64,
+        protocol_position.fee_growth_inside_0_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.token_fees_owed_1 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_1,
+        personal_position.fee_growth_inside_1_last_x64,
+        protocol_position.fee_growth_inside_1_last_x64,
+        personal_position.liquidity,
+    );
+    // update rewards, must update before decrease liquidity
+    personal_position.update_rewards(protocol_position.reward_growth_inside, true)?;
+    personal_position.liquidity = 0;
+
+    let mut amount_0 = decrease_amount_0;
+    let mut amount_1 = decrease_amount_1;
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower_index)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper_index)?;
+    let block_timestamp = oracle::block_timestamp();
+    let (swap_amount, zero_for_one) = if swap_surplus {
+        let pool_state = pool_state_loader.load()?;
+        liquidity_math::get_rebalance_swap_amount(
+            pool_state.sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_0,
+            amount_1,
+            amm_config.get_trade_fee_rate(
+                ctx.accounts.observation_state.load()?.deref(),
+                pool_state.tick_current,
+                block_timestamp,
+            ),
+        )
+    } else {
+        (0, false)
+    };
+    let mut swap_amount_in = 0;
+    let mut swap_amount_out = 0;
+    if swap_amount > 0 {
+        let tick_array_bitmap_extension = match tickarray_bitmap_extension {
+            Some(account_info) => Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            ),
+            None => None,
+        };
+        let mut tick_array_states = VecDeque::new();
+        for account_info in tick_array_accounts.iter() {
+            tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+        }
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let (swap_amount_0, swap_amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
+            pool_state,
+            &mut tick_array_states,
+            &mut ctx.accounts.observation_state.load_mut()?,
+            &tick_array_bitmap_extension,
+            swap_amount,
+            if sqrt_price_limit_x64 == 0 {
+                if zero_for_one {
+                    tick_math::MIN_SQRT_PRICE_X64 + 1
+                } else {
+                    tick_math::MAX_SQRT_PRICE_X64 - 1
+                }
+            } else {
+                sqrt_price_limit_x64
+            },
+            zero_for_one,
+            true,
+            0,
+            None,
+            false,
+            block_timestamp,
+        )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        // the swapped tokens don't leave the vaults
+        if zero_for_one {
+            (swap_amount_in, swap_amount_out) = (swap_amount_0, swap_amount_1);
+            amount_0 -= swap_amount_0;
+            amount_1 = amount_1.checked_add(swap_amount_1).unwrap();
+        } else {
+            (swap_amount_in, swap_amount_out) = (swap_amount_1, swap_amount_0);
+            amount_1 -= swap_amount_1;
+            amount_0 = amount_0.checked_add(swap_amount_0).unwrap();
+        }
+    }
+
+    // deposit into the new range
+    let new_protocol_position = ctx.accounts.new_protocol_position.deref_mut();
+    let (liquidity, increase_amount_0, increase_amount_1) = {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let tick_array_lower_loader = TickArrayState::get_or_create_tick_array(
+            ctx.accounts.nft_owner.to_account_info(),
+            ctx.accounts.new_tick_array_lower.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            pool_state_loader,
+            tick_array_lower_start_index,
+            pool_state.tick_spacing,
+        )?;
+        let tick_array_upper_loader =
+            if tick_array_lower_start_index == tick_array_upper_start_index {
+                AccountLoad::<TickArrayState>::try_from(
+                    &ctx.accounts.new_tick_array_upper.to_account_info(),
+                )?
+            } else {
+                TickArrayState::get_or_create_tick_array(
+                    ctx.accounts.nft_owner.to_account_info(),
+                    ctx.accounts.new_tick_array_upper.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    pool_state_loader,
+                    tick_array_upper_start_index,
+                    pool_state.tick_spacing,
+                )?
+            };
+
+        // check if protocol position is initialized
+        if new_protocol_position.pool_id == Pubkey::default() {
+            new_protocol_position.bump = ctx.bumps.new_protocol_position;
+            new_protocol_position.pool_id = pool_state_loader.key();
+            new_protocol_position.tick_lower_index = tick_lower_index;
+            new_protocol_position.tick_upper_index = tick_upper_index;
+            tick_array_lower_loader
+                .load_mut()?
+                .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?
+                .tick = tick_lower_index;
+            tick_array_upper_loader
+                .load_mut()?
+                .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?
+                .tick = tick_upper_index;
+        }
+
+        let liquidity = get_rebalance_liquidity(
+            pool_state.sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_0,
+            amount_1,
+            liquidity_min,
+        )?;
+        let (increase_amount_0, increase_amount_1) = add_liquidity_to_ticks(
+            &tick_array_lower_loader,
+            &tick_array_upper_loader,
+            new_protocol_position,
+            tickarray_bitmap_extension,
+            pool_state,
+            liquidity,
+            tick_lower_index,
+            tick_upper_index,
+        )?;
+        (liquidity, increase_amount_0, increase_amount_1)
+    };
+
+    personal_position.tick_lower_index = tick_lower_index;
+    personal_position.tick_upper_index = tick_upper_index;
+    personal_position.fee_growth_inside_0_last_x64 =
+        new_protocol_position.fee_growth_inside_0_last_x64;
+    personal_position.fee_growth_inside_1_last_x64 =
+        new_protocol_position.fee_growth_inside_1_last_x64;
+    // update rewards, must update before update liquidity
+    personal_position.update_rewards(new_protocol_position.reward_growth_inside, false)?;
+    personal_position.liquidity = liquidity;
+
+    // send back the tokens the new range can't take
+    let refund_amount_0 = amount_0.checked_sub(increase_amount_0).unwrap();
+    let refund_amount_1 = amount_1.checked_sub(increase_amount_1).unwrap();
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.recipient_token_account_0.to_account_info(),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        refund_amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.recipient_token_account_1.to_account_info(),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        refund_amount_1,
+    )?;
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.token_vault_1.to_account_info(),
+    )?;
+
+    emit!(RebalancePositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        tick_lower_index_before,
+        tick_upper_index_before,
+        liquidity_before,
+        tick_lower_index,
+        tick_upper_index,
+        liquidity,
+        decrease_amount_0,
+        decrease_amount_1,
+        zero_for_one,
+        swap_amount_in,
+        swap_amount_out,
+        increase_amount_0,
+        increase_amount_1,
+        refund_amount_0,
+        refund_amount_1,
+    });
+
+    Ok(())
+}
+
+/// Returns the liquidity `amount_0` and `amount_1` add to the new range at the current price,
+/// fails if it's less than `liquidity_min`
+fn get_rebalance_liquidity(
+    sqrt_price_x64: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    amount_0: u64,
+    amount_1: u64,
+    liquidity_min: u128,
+) -> Result<u128> {
+    let liquidity = liquidity_math::get_liquidity_from_amounts(
+        sqrt_price_x64,
+        sqrt_price_lower_x64,
+        sqrt_price_upper_x64,
+        amount_0,
+        amount_1,
+    );
+    require_gte!(liquidity, liquidity_min, ErrorCode::PriceSlippageCheck);
+    Ok(liquidity)
+}
+
+#[cfg(test)]
+mod rebalance_position_test {
+    use super::*;
+    use crate::libraries::{fixed_point_64, MulDiv, U256};
+    use crate::states::config::FEE_RATE_DENOMINATOR_VALUE;
+
+    #[test]
+    fn rebalance_swap_amount_balances_the_deposit_test() {
+        let tick_lower = -1200;
+        let tick_upper = 2400;
+        let trade_fee_rate = 2500;
+        for (tick_current, amount_0, amount_1) in [
+            (0, 1_000_000_000u64, 0u64),
+            (0, 0, 1_000_000_000),
+            (0, 1_000_000_000, 1_000_000),
+            (0, 1_000_000, 1_000_000_000),
+            (-1000, 77_777_777, 5_000),
+            (2300, 5_000, 5_000_000_000),
+        ] {
+            let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(tick_current).unwrap();
+            let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower).unwrap();
+            let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper).unwrap();
+            let (swap_amount, zero_for_one) = liquidity_math::get_rebalance_swap_amount(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_0,
+                amount_1,
+                trade_fee_rate,
+            );
+            // only the surplus side is swapped
+            assert_eq!(zero_for_one, amount_0 > amount_1);
+            assert!(swap_amount < if zero_for_one { amount_0 } else { amount_1 });
+
+            // swap without price impact
+            let price_x64 = U256::from(sqrt_price_x64)
+                .mul_div_floor(U256::from(sqrt_price_x64), U256::from(fixed_point_64::Q64))
+                .unwrap();
+            let amount_after_fee = U256::from(swap_amount)
+                .mul_div_floor(
+                    U256::from(FEE_RATE_DENOMINATOR_VALUE - trade_fee_rate),
+                    U256::from(FEE_RATE_DENOMINATOR_VALUE),
+                )
+                .unwrap();
+            let (amount_0, amount_1) = if zero_for_one {
+                let amount_out = amount_after_fee
+                    .mul_div_floor(price_x64, U256::from(fixed_point_64::Q64))
+                    .unwrap();
+                (amount_0 - swap_amount, amount_1 + amount_out.as_u64())
+            } else {
+                let amount_out = amount_after_fee
+                    .mul_div_floor(U256::from(fixed_point_64::Q64), price_x64)
+                    .unwrap();
+                (amount_0 + amount_out.as_u64(), amount_1 - swap_amount)
+            };
+            let liquidity = get_rebalance_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_0,
+                amount_1,
+                0,
+            )
+            .unwrap();
+            let (deposit_amount_0, deposit_amount_1) = liquidity_math::get_delta_amounts_signed(
+                tick_current,
+                sqrt_price_x64,
+                tick_lower,
+                tick_upper,
+                i128::try_from(liquidity).unwrap(),
+            )
+            .unwrap();
+            // both sides are used up to rounding
+            assert!(amount_0 - deposit_amount_0 <= amount_0 / 1_000_000 + 1);
+            assert!(amount_1 - deposit_amount_1 <= amount_1 / 1_000_000 + 1);
+        }
+    }
+
+    #[test]
+    fn rebalance_swap_amount_out_of_range_test() {
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1200).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(2400).unwrap();
+        // below the range only token_0 is deposited, all the token_1 is swapped
+        let (swap_amount, zero_for_one) = liquidity_math::get_rebalance_swap_amount(
+            tick_math::get_sqrt_price_at_tick(-2000).unwrap(),
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            1_000,
+            1_000_000,
+            2500,
+        );
+        assert_eq!((swap_amount, zero_for_one), (1_000_000, false));
+        // above the range only token_1 is deposited, all the token_0 is swapped
+        let (swap_amount, zero_for_one) = liquidity_math::get_rebalance_swap_amount(
+            tick_math::get_sqrt_price_at_tick(3000).unwrap(),
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            1_000_000,
+            1_000,
+            2500,
+        );
+        assert_eq!((swap_amount, zero_for_one), (1_000_000, true));
+    }
+
+    #[test]
+    fn rebalance_empty_position_test() {
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1200).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(2400).unwrap();
+        // a position with liquidity 0 withdraws nothing, so nothing is swapped
+        assert_eq!(
+            liquidity_math::get_rebalance_swap_amount(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                0,
+                0,
+                2500,
+            ),
+            (0, false)
+        );
+        // and the new range gets liquidity 0
+        assert_eq!(
+            get_rebalance_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                0,
+                0,
+                0,
+            )
+            .unwrap(),
+            0
+        );
+        assert_eq!(
+            get_rebalance_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                0,
+                0,
+                1,
+            )
+            .unwrap_err(),
+            ErrorCode::PriceSlippageCheck.into()
+        );
+    }
+
+    #[test]
+    fn rebalance_liquidity_min_test() {
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1200).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(2400).unwrap();
+        let get_liquidity = |liquidity_min: u128| {
+            get_rebalance_liquidity(
+                sqrt_price_x64,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                1_000_000_000,
+                500_000_000,
+                liquidity_min,
+            )
+        };
+        let liquidity = get_liquidity(0).unwrap();
+        assert!(liquidity > 0);
+        assert_eq!(get_liquidity(liquidity).unwrap(), liquidity);
+        assert_eq!(
+            get_liquidity(liquidity + 1).unwrap_err(),
+            ErrorCode::PriceSlippageCheck.into()
+        );
+    }
+}
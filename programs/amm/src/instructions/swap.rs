@@ -4,7 +4,7 @@ use crate::libraries::{
 };
 use crate::states::*;
 use crate::util::*;
-use anchor_lang::{prelude::*, solana_program};
+use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 use std::cell::RefMut;
 use std::collections::VecDeque;
@@ -103,8 +103,18 @@ pub struct SwapState {
     pub protocol_fee: u64,
     // amount of input token paid as fund fee
     pub fund_fee: u64,
+    // amount of input token paid as pool creator fee
+    pub creator_fee: u64,
+    // amount of input token paid to the referrer
+    pub referral_fee: u64,
     // the current liquidity in range
     pub liquidity: u128,
+    // the part of the liquidity in range owned by limit orders
+    pub limit_order_liquidity: u128,
+    // the number of initialized ticks crossed
+    pub ticks_crossed: u32,
+    // the tick the circuit breaker window started at, if the swap tripped the circuit breaker
+    pub circuit_breaker_tick_start: Option<i32>,
 }
 
 #[derive(Default)]
@@ -137,7 +147,76 @@ pub fn swap_internal<'b, 'info>(
     is_base_input: bool,
     block_timestamp: u32,
 ) -> Result<(u64, u64)> {
+    let (amount_0, amount_1, _) = swap_internal_with_referral(
+        amm_config,
+        pool_state,
+        tick_array_states,
+        observation_state,
+        tickarray_bitmap_extension,
+        amount_specified,
+        sqrt_price_limit_x64,
+        zero_for_one,
+        is_base_input,
+        0,
+        block_timestamp,
+    )?;
+    Ok((amount_0, amount_1))
+}
+
+/// Same as `swap_internal`, but carves `referral_fee_bps` of the trade fee out for the referrer.
+/// Returns the token_0 amount, the token_1 amount and the referral fee in input token
+pub fn swap_internal_with_referral(
+    amm_config: &AmmConfig,
+    pool_state: &mut RefMut<PoolState>,
+    tick_array_states: &mut VecDeque<RefMut<TickArrayState>>,
+    observation_state: &mut RefMut<ObservationState>,
+    tickarray_bitmap_extension: &Option<TickArrayBitmapExtension>,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+    referral_fee_bps: u16,
+    block_timestamp: u32,
+) -> Result<(u64, u64, u64)> {
+    let (amount_0, amount_1, state) = swap_internal_with_state(
+        amm_config,
+        pool_state,
+        tick_array_states,
+        observation_state,
+        tickarray_bitmap_extension,
+        amount_specified,
+        sqrt_price_limit_x64,
+        zero_for_one,
+        is_base_input,
+        referral_fee_bps,
+        None,
+        false,
+        block_timestamp,
+    )?;
+    Ok((amount_0, amount_1, state.referral_fee))
+}
+
+/// Same as `swap_internal_with_referral`, but returns the final swap state
+/// with the fee breakdown and the number of crossed ticks instead of the referral fee.
+/// `max_deviation_ticks` tightens the oracle price guard of the pool for this swap.
+/// If `allow_partial_fill`, the swap stops where the liquidity runs out instead of failing
+pub fn swap_internal_with_state<T: SwapTickArray>(
+    amm_config: &AmmConfig,
+    pool_state: &mut RefMut<PoolState>,
+    tick_array_states: &mut VecDeque<T>,
+    observation_state: &mut RefMut<ObservationState>,
+    tickarray_bitmap_extension: &Option<TickArrayBitmapExtension>,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
+    block_timestamp: u32,
+) -> Result<(u64, u64, SwapState)> {
     require!(amount_specified != 0, ErrorCode::ZeroAmountSpecified);
+    require!(!pool_state.is_locked(), ErrorCode::LOK);
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap) {
         return err!(ErrorCode::NotApproved);
     }
@@ -152,7 +231,21 @@ pub fn swap_internal<'b, 'info>(
         ErrorCode::SqrtPriceLimitOverflow
     );
 
+    require_gt!(
+        u64::from(block_timestamp),
+        pool_state.open_time,
+        ErrorCode::PoolNotOpen
+    );
+    pool_state.check_circuit_breaker(zero_for_one, block_timestamp)?;
+
     let liquidity_start = pool_state.liquidity;
+    let trade_fee_mode = amm_config.get_trade_fee_mode()?;
+    // the flat fee is elevated during the launch window of the pool
+    let trade_fee_flat =
+        pool_state.get_trade_fee_flat(amm_config.trade_fee_flat, block_timestamp.into());
+    // measure the volatility before the observation is updated by this swap
+    let trade_fee_rate =
+        amm_config.get_trade_fee_rate(observation_state, pool_state.tick_current, block_timestamp);
 
     let updated_reward_infos = pool_state.update_reward_infos(block_timestamp as u64)?;
 
@@ -169,7 +262,29 @@ pub fn swap_internal<'b, 'info>(
         fee_amount: 0,
         protocol_fee: 0,
         fund_fee: 0,
+        creator_fee: 0,
+        referral_fee: 0,
         liquidity: liquidity_start,
+        limit_order_liquidity: pool_state.limit_order_liquidity,
+        ticks_crossed: 0,
+        circuit_breaker_tick_start: None,
+    };
+
+    // in per swap accounting mode the flat fee is charged only once, before walking the ticks,
+    // and it's shared by the liquidity in range at the starting price
+    let step_flat_fee = match amm_config.get_fee_accounting_mode()? {
+        FeeAccountingMode::PerStep => trade_fee_flat,
+        FeeAccountingMode::PerSwap => {
+            if is_base_input {
+                let entry_fee = trade_fee_flat.min(amount_specified);
+                state.amount_specified_remaining = amount_specified - entry_fee;
+                accrue_swap_fee(amm_config, referral_fee_bps, &mut state, entry_fee);
+            } else {
+                state.amount_calculated = trade_fee_flat;
+                accrue_swap_fee(amm_config, referral_fee_bps, &mut state, trade_fee_flat);
+            }
+            0
+        }
     };
 
     // check observation account is owned by the pool
@@ -247,6 +362,9 @@ pub fn swap_internal<'b, 'info>(
                     zero_for_one,
                 )?;
             if next_initialized_tickarray_index.is_none() {
+                if allow_partial_fill {
+                    break;
+                }
                 return err!(ErrorCode::LiquidityInsufficient);
             }
 
@@ -302,7 +420,9 @@ pub fn swap_internal<'b, 'info>(
             target_price,
             state.liquidity,
             state.amount_specified_remaining,
-            amm_config.trade_fee_flat,
+            step_flat_fee,
+            trade_fee_rate,
+            trade_fee_mode,
             is_base_input,
             zero_for_one,
             block_timestamp,
@@ -344,49 +464,8 @@ pub fn swap_internal<'b, 'info>(
                 .ok_or(ErrorCode::CalculateOverflow)?;
         }
 
-        let step_fee_amount = step.fee_amount;
-        // if the protocol fee is on, calculate how much is owed, decrement fee_amount, and increment protocol_fee
-        if amm_config.protocol_fee_rate > 0 {
-            let delta = U128::from(step_fee_amount)
-                .checked_mul(amm_config.protocol_fee_rate.into())
-                .unwrap()
-                .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
-                .unwrap()
-                .as_u64();
-            step.fee_amount = step.fee_amount.checked_sub(delta).unwrap();
-            state.protocol_fee = state.protocol_fee.checked_add(delta).unwrap();
-        }
-        // if the fund fee is on, calculate how much is owed, decrement fee_amount, and increment fund_fee
-        if amm_config.fund_fee_rate > 0 {
-            let delta = U128::from(step_fee_amount)
-                .checked_mul(amm_config.fund_fee_rate.into())
-                .unwrap()
-                .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
-                .unwrap()
-                .as_u64();
-            step.fee_amount = step.fee_amount.checked_sub(delta).unwrap();
-            state.fund_fee = state.fund_fee.checked_add(delta).unwrap();
-        }
-
-        // update global fee tracker
-        if state.liquidity > 0 {
-            let fee_growth_global_x64_delta = U128::from(step.fee_amount)
-                .mul_div_floor(U128::from(fixed_point_64::Q64), U128::from(state.liquidity))
-                .unwrap()
-                .as_u128();
-
-            state.fee_growth_global_x64 = state
-                .fee_growth_global_x64
-                .checked_add(fee_growth_global_x64_delta)
-                .unwrap();
-            state.fee_amount = state.fee_amount.checked_add(step.fee_amount).unwrap();
-            #[cfg(feature = "enable-log")]
-            msg!(
-                "fee_growth_global_x64_delta:{}, state.fee_growth_global_x64:{}, state.liquidity:{}, step.fee_amount:{}, state.fee_amount:{}",
-                fee_growth_global_x64_delta,
-                state.fee_growth_global_x64, state.liquidity, step.fee_amount, state.fee_amount
-            );
-        }
+        step.fee_amount =
+            accrue_swap_fee(amm_config, referral_fee_bps, &mut state, step.fee_amount);
         // shift tick if we reached the next price
         if state.sqrt_price_x64 == step.sqrt_price_next_x64 {
             // if the tick is initialized, run the tick transition
@@ -413,11 +492,25 @@ pub fn swap_internal<'b, 'info>(
                     pool_state.tick_spacing.into(),
                     *next_initialized_tick,
                 )?;
+                // fill the limit orders of the range the price leaves
+                let (limit_order_liquidity_entering, limit_order_liquidity_leaving) =
+                    tick_array_current.cross_limit_orders(
+                        step.tick_next,
+                        pool_state.tick_spacing,
+                        zero_for_one,
+                    )?;
+                state.limit_order_liquidity = state
+                    .limit_order_liquidity
+                    .checked_sub(limit_order_liquidity_leaving)
+                    .unwrap()
+                    .checked_add(limit_order_liquidity_entering)
+                    .unwrap();
 
                 if zero_for_one {
                     liquidity_net = liquidity_net.neg();
                 }
                 state.liquidity = liquidity_math::add_delta(state.liquidity, liquidity_net)?;
+                state.ticks_crossed += 1;
             }
 
             state.tick = if zero_for_one {
@@ -460,6 +553,28 @@ pub fn swap_internal<'b, 'info>(
         //     zero_for_one,
         // });
     }
+    // reject the swap if it moves the price too far from the oracle, before this swap is observed
+    let max_deviation_ticks = pool_state.get_max_deviation_ticks(max_deviation_ticks);
+    if max_deviation_ticks > 0 {
+        let twap_window_seconds = pool_state.get_twap_window_seconds();
+        // the guard is skipped until the observations cover the window
+        if let Some(twap_tick) = observation_state.get_twap_tick(twap_window_seconds) {
+            let deviation_ticks = state.tick.abs_diff(twap_tick);
+            // a swap moving the price back toward the oracle is allowed outside the limit
+            if deviation_ticks > max_deviation_ticks {
+                require_gte!(
+                    pool_state.tick_current.abs_diff(twap_tick),
+                    deviation_ticks,
+                    ErrorCode::PriceDeviationTooHigh
+                );
+            }
+        }
+    }
+
+    // pause the swaps moving the price further if this swap moves it too far within the window
+    state.circuit_breaker_tick_start =
+        pool_state.update_circuit_breaker(state.tick, block_timestamp);
+
     // update tick
     if state.tick != pool_state.tick_current {
         // update the previous tick to the observation
@@ -471,6 +586,7 @@ pub fn swap_internal<'b, 'info>(
     if liquidity_start != state.liquidity {
         pool_state.liquidity = state.liquidity;
     }
+    pool_state.limit_order_liquidity = state.limit_order_liquidity;
 
     let (amount_0, amount_1) = if zero_for_one == is_base_input {
         (
@@ -507,6 +623,12 @@ pub fn swap_internal<'b, 'info>(
                 .checked_add(state.fund_fee)
                 .unwrap();
         }
+        if state.creator_fee > 0 {
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(state.creator_fee)
+                .unwrap();
+        }
         pool_state.swap_in_amount_token_0 = pool_state
             .swap_in_amount_token_0
             .checked_add(u128::from(amount_0))
@@ -534,6 +656,12 @@ pub fn swap_internal<'b, 'info>(
                 .checked_add(state.fund_fee)
                 .unwrap();
         }
+        if state.creator_fee > 0 {
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(state.creator_fee)
+                .unwrap();
+        }
         pool_state.swap_in_amount_token_1 = pool_state
             .swap_in_amount_token_1
             .checked_add(u128::from(amount_1))
@@ -544,7 +672,109 @@ pub fn swap_internal<'b, 'info>(
             .unwrap();
     }
 
-    Ok((amount_0, amount_1))
+    Ok((amount_0, amount_1, state))
+}
+
+/// Takes the protocol fee, fund fee, creator fee and referral fee out of `fee_amount`, and credits the rest to the global fee growth of the liquidity in range.
+/// Returns the fee amount left for liquidity providers
+pub fn accrue_swap_fee(
+    amm_config: &AmmConfig,
+    referral_fee_bps: u16,
+    state: &mut SwapState,
+    fee_amount: u64,
+) -> u64 {
+    let mut lp_fee_amount = fee_amount;
+    // if the protocol fee is on, calculate how much is owed, decrement fee_amount, and increment protocol_fee
+    if amm_config.protocol_fee_rate > 0 {
+        let delta = U128::from(fee_amount)
+            .checked_mul(amm_config.protocol_fee_rate.into())
+            .unwrap()
+            .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+            .unwrap()
+            .as_u64();
+        lp_fee_amount = lp_fee_amount.checked_sub(delta).unwrap();
+        state.protocol_fee = state.protocol_fee.checked_add(delta).unwrap();
+    }
+    // if the fund fee is on, calculate how much is owed, decrement fee_amount, and increment fund_fee
+    if amm_config.fund_fee_rate > 0 {
+        let delta = U128::from(fee_amount)
+            .checked_mul(amm_config.fund_fee_rate.into())
+            .unwrap()
+            .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+            .unwrap()
+            .as_u64();
+        lp_fee_amount = lp_fee_amount.checked_sub(delta).unwrap();
+        state.fund_fee = state.fund_fee.checked_add(delta).unwrap();
+    }
+    // if the creator fee is on, calculate how much is owed, decrement fee_amount, and increment creator_fee
+    if amm_config.creator_fee_rate > 0 {
+        let delta = U128::from(fee_amount)
+            .checked_mul(amm_config.creator_fee_rate.into())
+            .unwrap()
+            .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+            .unwrap()
+            .as_u64();
+        lp_fee_amount = lp_fee_amount.checked_sub(delta).unwrap();
+        state.creator_fee = state.creator_fee.checked_add(delta).unwrap();
+    }
+    // if the swap is referred, the referrer takes its share of the fee as well
+    if referral_fee_bps > 0 {
+        let delta = U128::from(fee_amount)
+            .checked_mul(referral_fee_bps.into())
+            .unwrap()
+            .checked_div(REFERRAL_FEE_BPS_DENOMINATOR.into())
+            .unwrap()
+            .as_u64()
+            .min(lp_fee_amount);
+        lp_fee_amount = lp_fee_amount.checked_sub(delta).unwrap();
+        state.referral_fee = state.referral_fee.checked_add(delta).unwrap();
+    }
+
+    // update global fee tracker, limit orders in range don't earn the fee
+    let fee_liquidity = state
+        .liquidity
+        .checked_sub(state.limit_order_liquidity)
+        .unwrap();
+    if fee_liquidity > 0 {
+        let fee_growth_global_x64_delta = U128::from(lp_fee_amount)
+            .mul_div_floor(U128::from(fixed_point_64::Q64), U128::from(fee_liquidity))
+            .unwrap()
+            .as_u128();
+
+        state.fee_growth_global_x64 = state
+            .fee_growth_global_x64
+            .checked_add(fee_growth_global_x64_delta)
+            .unwrap();
+        state.fee_amount = state.fee_amount.checked_add(lp_fee_amount).unwrap();
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "fee_growth_global_x64_delta:{}, state.fee_growth_global_x64:{}, state.liquidity:{}, lp_fee_amount:{}, state.fee_amount:{}",
+            fee_growth_global_x64_delta,
+            state.fee_growth_global_x64, state.liquidity, lp_fee_amount, state.fee_amount
+        );
+    } else {
+        // no position earns the fee, the protocol takes the LP share
+        state.protocol_fee = state.protocol_fee.checked_add(lp_fee_amount).unwrap();
+        lp_fee_amount = 0;
+    }
+    lp_fee_amount
+}
+
+/// Emits `CircuitBreakerTrippedEvent` if the swap ending at the current tick of the pool
+/// tripped its circuit breaker, the instructions committing a swap call it after the swap
+pub fn emit_circuit_breaker_tripped(
+    pool_state: &PoolState,
+    circuit_breaker_tick_start: Option<i32>,
+) {
+    if let Some(tick_start) = circuit_breaker_tick_start {
+        emit!(CircuitBreakerTrippedEvent {
+            pool_state: pool_state.key(),
+            tick_start,
+            tick: pool_state.tick_current,
+            zero_for_one: pool_state.circuit_breaker_paused_direction == 1,
+            paused_until: pool_state.circuit_breaker_paused_until,
+        });
+    }
 }
 
 /// Performs a single exact input/output swap
@@ -556,22 +786,37 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
 ) -> Result<u64> {
-    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
-
     let amount_0;
     let amount_1;
     let zero_for_one;
     let swap_price_before;
+    let fee_amount;
 
     let input_balance_before = ctx.input_vault.amount;
     let output_balance_before = ctx.output_vault.amount;
 
+    let (remaining_accounts, hook_accounts) =
+        split_hook_accounts(ctx.pool_state, remaining_accounts, &SWAP_HOOK_PERMISSIONS)?;
+    invoke_hook(
+        ctx.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeSwap,
+        BEFORE_SWAP_HOOK_DISCRIMINATOR,
+        &BeforeSwapHook {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.signer.key(),
+            zero_for_one: ctx.input_vault.mint == ctx.pool_state.load()?.token_mint_0,
+            is_base_input,
+            amount_specified,
+            sqrt_price_limit_x64,
+        },
+    )?;
+
     {
         swap_price_before = ctx.pool_state.load()?.sqrt_price_x64;
         let pool_state = &mut ctx.pool_state.load_mut()?;
         zero_for_one = ctx.input_vault.mint == pool_state.token_mint_0;
-
-        require_gt!(block_timestamp, pool_state.open_time);
+        let swap_fees_before = pool_state.get_accumulated_swap_fees(zero_for_one);
 
         require!(
             if zero_for_one {
@@ -588,8 +833,19 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
         let tick_array_states = &mut VecDeque::new();
         tick_array_states.push_back(ctx.tick_array_state.load_mut()?);
 
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
         let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
         for account_info in remaining_accounts.into_iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
             if account_info.key().eq(&tick_array_bitmap_extension_key) {
                 tickarray_bitmap_extension = Some(
                     *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
@@ -601,8 +857,10 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
             tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
         }
 
-        (amount_0, amount_1) = swap_internal(
-            &ctx.amm_config,
+        let amm_config = get_effective_amm_config(ctx.amm_config, pool_state, pool_fee_override)?;
+        let swap_state;
+        (amount_0, amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
             pool_state,
             tick_array_states,
             &mut ctx.observation_state.load_mut()?,
@@ -619,8 +877,13 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
             },
             zero_for_one,
             is_base_input,
+            0,
+            None,
+            false,
             oracle::block_timestamp(),
         )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        fee_amount = pool_state.get_accumulated_swap_fees(zero_for_one) - swap_fees_before;
 
         #[cfg(feature = "enable-log")]
         msg!(
@@ -634,6 +897,27 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
             ErrorCode::TooSmallInputOrOutputAmount
         );
     }
+    let after_swap_hook = {
+        let pool_state = ctx.pool_state.load()?;
+        AfterSwapHook {
+            pool_state: pool_state.key(),
+            sender: ctx.signer.key(),
+            zero_for_one,
+            is_base_input,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        }
+    };
+    invoke_hook(
+        ctx.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::AfterSwap,
+        AFTER_SWAP_HOOK_DISCRIMINATOR,
+        &after_swap_hook,
+    )?;
+
     let (token_account_0, token_account_1, vault_0, vault_1) = if zero_for_one {
         (
             ctx.input_token_account.clone(),
@@ -702,6 +986,11 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
     ctx.output_vault.reload()?;
     ctx.input_vault.reload()?;
 
+    let filled_amount = if zero_for_one == is_base_input {
+        amount_0
+    } else {
+        amount_1
+    };
     let pool_state = ctx.pool_state.load()?;
     emit!(SwapEvent {
         pool_state: pool_state.key(),
@@ -715,7 +1004,10 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
         zero_for_one,
         sqrt_price_x64: pool_state.sqrt_price_x64,
         liquidity: pool_state.liquidity,
-        tick: pool_state.tick_current
+        tick: pool_state.tick_current,
+        fee_amount,
+        filled_amount,
+        unfilled_amount: amount_specified.saturating_sub(filled_amount),
     });
     if zero_for_one {
         require_gt!(swap_price_before, pool_state.sqrt_price_x64);
@@ -1548,6 +1840,799 @@ mod swap_test {
     }
 
     #[cfg(test)]
+    mod fee_accounting_mode_test {
+        use super::*;
+        use std::convert::identity;
+
+        fn build_fee_test_pool(
+            fee_accounting_mode: FeeAccountingMode,
+        ) -> (
+            AmmConfig,
+            RefCell<PoolState>,
+            VecDeque<RefCell<TickArrayState>>,
+            RefCell<ObservationState>,
+        ) {
+            let (mut amm_config, pool_state, tick_array_states, observation_state) =
+                build_swap_param(
+                    -32395,
+                    60,
+                    3651942632306380802,
+                    5124165121219,
+                    vec![
+                        TickArrayInfo {
+                            start_tick_index: -32400,
+                            ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                        },
+                        TickArrayInfo {
+                            start_tick_index: -36000,
+                            ticks: vec![
+                                build_tick(-32460, 1194569667438, 536061033698).take(),
+                                build_tick(-32520, 790917615645, 790917615645).take(),
+                                build_tick(-32580, 152146472301, 128451145459).take(),
+                                build_tick(-32640, 2625605835354, -1492054447712).take(),
+                            ],
+                        },
+                    ],
+                );
+            amm_config.protocol_fee_rate = 120000;
+            amm_config.fund_fee_rate = 40000;
+            amm_config.fee_accounting_mode = fee_accounting_mode as u8;
+            (amm_config, pool_state, tick_array_states, observation_state)
+        }
+
+        /// Returns the total fee charged in token_0 and the ending tick
+        fn swap_and_get_total_fee(
+            fee_accounting_mode: FeeAccountingMode,
+            amount_specified: u64,
+            is_base_input: bool,
+        ) -> (u64, i32) {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_fee_test_pool(fee_accounting_mode);
+            swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                amount_specified,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                is_base_input,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            let pool_state = pool_state.borrow();
+            (
+                pool_state.total_fees_token_0
+                    + pool_state.protocol_fees_token_0
+                    + pool_state.fund_fees_token_0,
+                pool_state.tick_current,
+            )
+        }
+
+        #[test]
+        fn per_swap_fee_not_depend_on_crossed_ticks_test() {
+            // stay in the starting tick range
+            let (small_swap_fee, tick) =
+                swap_and_get_total_fee(FeeAccountingMode::PerSwap, 1_000_000, true);
+            assert!(tick > -32400);
+            assert_eq!(small_swap_fee, 1000);
+
+            // cross the ticks -32400, -32460, -32520 and -32580
+            let (large_swap_fee, tick) =
+                swap_and_get_total_fee(FeeAccountingMode::PerSwap, 400_000_000_000, true);
+            assert!(tick < -32580);
+            assert_eq!(large_swap_fee, 1000);
+
+            let (large_swap_fee, tick) =
+                swap_and_get_total_fee(FeeAccountingMode::PerSwap, 1_000_000_000_000, false);
+            assert!(tick < -32580);
+            assert_eq!(large_swap_fee, 1000);
+
+            // the flat fee is charged by every step in per step mode
+            let (large_swap_fee, tick) =
+                swap_and_get_total_fee(FeeAccountingMode::PerStep, 400_000_000_000, true);
+            assert!(tick < -32580);
+            assert!(large_swap_fee >= 5 * 1000);
+        }
+
+        #[test]
+        fn per_swap_fee_split_once_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_fee_test_pool(FeeAccountingMode::PerSwap);
+            let fee_growth_global_0_x64_before = pool_state.borrow().fee_growth_global_0_x64;
+            let liquidity_start = pool_state.borrow().liquidity;
+            let (amount_0, _) = swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                200_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(amount_0, 200_000_000_000);
+
+            let pool_state = pool_state.borrow();
+            assert_eq!(identity(pool_state.protocol_fees_token_0), 120);
+            assert_eq!(identity(pool_state.fund_fees_token_0), 40);
+            assert_eq!(identity(pool_state.total_fees_token_0), 840);
+            // the whole lp fee goes to the liquidity at the starting price
+            assert_eq!(
+                pool_state.fee_growth_global_0_x64 - fee_growth_global_0_x64_before,
+                U128::from(840u64)
+                    .mul_div_floor(U128::from(fixed_point_64::Q64), U128::from(liquidity_start))
+                    .unwrap()
+                    .as_u128()
+            );
+        }
+
+        #[test]
+        fn referral_fee_carved_out_of_fee_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_fee_test_pool(FeeAccountingMode::PerSwap);
+            let (amount_0, _, referral_fee) = swap_internal_with_referral(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                200_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                true,
+                2500,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(amount_0, 200_000_000_000);
+            assert_eq!(referral_fee, 250);
+
+            let pool_state = pool_state.borrow();
+            assert_eq!(identity(pool_state.protocol_fees_token_0), 120);
+            assert_eq!(identity(pool_state.fund_fees_token_0), 40);
+            assert_eq!(identity(pool_state.total_fees_token_0), 590);
+        }
+
+        #[test]
+        fn creator_fee_accrued_to_pool_test() {
+            let (mut amm_config, pool_state, tick_array_states, observation_state) =
+                build_fee_test_pool(FeeAccountingMode::PerSwap);
+            amm_config.creator_fee_rate = 100000;
+            swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                200_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+
+            let pool_state = pool_state.borrow();
+            assert_eq!(identity(pool_state.creator_fees_token_0), 100);
+            assert_eq!(identity(pool_state.creator_fees_token_1), 0);
+            assert_eq!(identity(pool_state.total_fees_token_0), 740);
+            assert_eq!(pool_state.get_accumulated_swap_fees(true), 1000);
+        }
+
+        #[test]
+        fn swap_state_fee_breakdown_and_crossed_ticks_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_fee_test_pool(FeeAccountingMode::PerSwap);
+            let (_, _, state) = swap_internal_with_state(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                400_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                true,
+                2500,
+                None,
+                false,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            // cross the ticks -32400, -32460, -32520 and -32580
+            assert!(state.tick < -32580);
+            assert_eq!(state.ticks_crossed, 4);
+            assert_eq!(state.protocol_fee, 120);
+            assert_eq!(state.fund_fee, 40);
+            assert_eq!(state.referral_fee, 250);
+            assert_eq!(state.fee_amount, 590);
+            assert_eq!(
+                state.sqrt_price_x64,
+                identity(pool_state.borrow().sqrt_price_x64)
+            );
+        }
+    }
+
+    mod dynamic_fee_test {
+        use super::*;
+
+        fn swap_with_observations(observation_ticks: Vec<i32>) -> u64 {
+            let tick_current = -32395;
+            let (mut amm_config, pool_state, tick_array_states, observation_state) =
+                build_swap_param(
+                    tick_current,
+                    60,
+                    3651942632306380802,
+                    5124165121219,
+                    vec![TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    }],
+                );
+            amm_config.trade_fee_mode = TradeFeeMode::Sum as u8;
+            amm_config.dynamic_fee_base = 100;
+            amm_config.dynamic_fee_max = 10000;
+            amm_config.dynamic_fee_sensitivity = 10;
+
+            let block_timestamp = oracle::block_timestamp_mock() as u32;
+            let mut observation_timestamp = block_timestamp - 60;
+            observation_state
+                .borrow_mut()
+                .update(observation_timestamp, tick_current);
+            for tick in observation_ticks {
+                observation_timestamp += OBSERVATION_UPDATE_DURATION_DEFAULT;
+                observation_state
+                    .borrow_mut()
+                    .update(observation_timestamp, tick);
+            }
+
+            swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                1_000_000,
+                tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
+                true,
+                true,
+                block_timestamp,
+            )
+            .unwrap();
+            let fees = pool_state.borrow().get_accumulated_swap_fees(true);
+            fees
+        }
+
+        #[test]
+        fn volatility_raise_fee_test() {
+            // calm pool only pays the base surcharge, 1000 + ceil(998900 * 100 / 999900)
+            let calm_fee = swap_with_observations(vec![]);
+            assert_eq!(calm_fee, 1100);
+
+            // 150 ticks volatility, surcharge rate is 100 + 150 * 10,
+            // 1000 + ceil(997401 * 1600 / 998400)
+            let volatile_fee = swap_with_observations(vec![-32295, -32445, -32375]);
+            assert_eq!(volatile_fee, 2599);
+
+            // the surcharge is capped by dynamic_fee_max, 1000 + ceil(989010 * 10000 / 990000)
+            let capped_fee = swap_with_observations(vec![-30000, -35000]);
+            assert_eq!(capped_fee, 10990);
+        }
+    }
+
+    mod price_guard_test {
+        use super::*;
+
+        /// Swaps from tick -32395 down to tick -32630 after the oracle observed the pool at `observed_tick`
+        fn swap_with_price_guard(
+            observed_tick: i32,
+            pool_max_deviation_ticks: u32,
+            max_deviation_ticks: Option<u32>,
+        ) -> Result<()> {
+            let tick_current = -32395;
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                tick_current,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(-32460, 1194569667438, 536061033698).take(),
+                            build_tick(-32520, 790917615645, 790917615645).take(),
+                            build_tick(-32580, 152146472301, 128451145459).take(),
+                            build_tick(-32640, 2625605835354, -1492054447712).take(),
+                        ],
+                    },
+                ],
+            );
+            pool_state.borrow_mut().max_deviation_ticks = pool_max_deviation_ticks;
+            pool_state.borrow_mut().twap_window_seconds = 30;
+
+            let block_timestamp = oracle::block_timestamp_mock() as u32;
+            let mut observation_timestamp = block_timestamp - 60;
+            for _ in 0..4 {
+                observation_state
+                    .borrow_mut()
+                    .update(observation_timestamp, observed_tick);
+                observation_timestamp += OBSERVATION_UPDATE_DURATION_DEFAULT;
+            }
+
+            swap_internal_with_state(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                400_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32630).unwrap(),
+                true,
+                true,
+                0,
+                max_deviation_ticks,
+                false,
+                block_timestamp,
+            )?;
+            Ok(())
+        }
+
+        #[test]
+        fn price_deviation_guard_test() {
+            // the guard is disabled
+            assert!(swap_with_price_guard(-32395, 0, None).is_ok());
+            assert!(swap_with_price_guard(-32395, 300, None).is_ok());
+            assert_eq!(
+                swap_with_price_guard(-32395, 100, None).unwrap_err(),
+                ErrorCode::PriceDeviationTooHigh.into()
+            );
+            // the swap override tightens the pool limit, but can not loosen it
+            assert_eq!(
+                swap_with_price_guard(-32395, 0, Some(100)).unwrap_err(),
+                ErrorCode::PriceDeviationTooHigh.into()
+            );
+            assert_eq!(
+                swap_with_price_guard(-32395, 300, Some(100)).unwrap_err(),
+                ErrorCode::PriceDeviationTooHigh.into()
+            );
+            assert_eq!(
+                swap_with_price_guard(-32395, 100, Some(300)).unwrap_err(),
+                ErrorCode::PriceDeviationTooHigh.into()
+            );
+            assert!(swap_with_price_guard(-32395, 300, Some(0)).is_ok());
+        }
+
+        #[test]
+        fn price_moving_toward_twap_test() {
+            // the swap starts 305 ticks above the TWAP and ends 70 ticks above it
+            assert!(swap_with_price_guard(-32700, 100, None).is_ok());
+            assert!(swap_with_price_guard(-32700, 0, Some(50)).is_ok());
+            // the swap crosses the TWAP, starts 105 ticks above it and ends 130 ticks below it
+            assert_eq!(
+                swap_with_price_guard(-32500, 100, None).unwrap_err(),
+                ErrorCode::PriceDeviationTooHigh.into()
+            );
+            // the swap crosses the TWAP, starts 125 ticks above it and ends 110 ticks below it
+            assert!(swap_with_price_guard(-32520, 100, None).is_ok());
+        }
+    }
+
+    mod twamm_price_limit_test {
+        use super::*;
+        use crate::instructions::twamm::swap_twamm_segment;
+
+        /// Sells token_0 from tick -32395 after the oracle observed the pool at `observed_tick`,
+        /// returns the amounts sold and bought and the tick after the execution
+        fn execute_twamm_segment(
+            pool_max_deviation_ticks: u32,
+            observed_tick: Option<i32>,
+            amount_specified: u64,
+        ) -> (u64, u64, i32) {
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(-32460, 1194569667438, 536061033698).take(),
+                            build_tick(-32520, 790917615645, 790917615645).take(),
+                            build_tick(-32580, 152146472301, 128451145459).take(),
+                            build_tick(-32640, 2625605835354, -1492054447712).take(),
+                        ],
+                    },
+                ],
+            );
+            pool_state.borrow_mut().max_deviation_ticks = pool_max_deviation_ticks;
+            pool_state.borrow_mut().twap_window_seconds = 30;
+
+            let block_timestamp = oracle::block_timestamp_mock() as u32;
+            if let Some(observed_tick) = observed_tick {
+                let mut observation_timestamp = block_timestamp - 60;
+                for _ in 0..4 {
+                    observation_state
+                        .borrow_mut()
+                        .update(observation_timestamp, observed_tick);
+                    observation_timestamp += OBSERVATION_UPDATE_DURATION_DEFAULT;
+                }
+            }
+
+            let (amount_in, amount_out, _) = swap_twamm_segment(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                amount_specified,
+                true,
+                block_timestamp,
+            )
+            .unwrap();
+            let tick_current = pool_state.borrow().tick_current;
+            (amount_in, amount_out, tick_current)
+        }
+
+        #[test]
+        fn twamm_execution_within_limit_test() {
+            let (amount_in, amount_out, tick_current) =
+                execute_twamm_segment(0, Some(-32395), 1_000_000);
+            assert_eq!(amount_in, 1_000_000);
+            assert!(amount_out > 0);
+            assert_eq!(tick_current, -32395);
+        }
+
+        #[test]
+        fn twamm_execution_stops_at_limit_test() {
+            // the pool limit applies, the price stops one tick inside it
+            let (amount_in, amount_out, tick_current) =
+                execute_twamm_segment(100, Some(-32395), 400_000_000_000);
+            assert!(amount_in > 0 && amount_in < 400_000_000_000);
+            assert!(amount_out > 0);
+            assert_eq!(tick_current, -32494);
+
+            // the default TWAMM limit applies to a pool without a limit
+            let (amount_in, _, tick_current) =
+                execute_twamm_segment(0, Some(-32395), 400_000_000_000);
+            assert!(amount_in > 0 && amount_in < 400_000_000_000);
+            assert_eq!(tick_current, -32395 - TWAMM_MAX_DEVIATION_TICKS as i32 + 1);
+
+            // a wider pool limit sells more
+            let (wider_amount_in, _, tick_current) =
+                execute_twamm_segment(200, Some(-32395), 400_000_000_000);
+            assert!(wider_amount_in > amount_in);
+            assert_eq!(tick_current, -32594);
+        }
+
+        #[test]
+        fn twamm_execution_beyond_limit_sells_nothing_test() {
+            // the price is already 195 ticks below the TWAP
+            assert_eq!(
+                execute_twamm_segment(100, Some(-32200), 1_000_000),
+                (0, 0, -32395)
+            );
+            // the observations don't cover the TWAP window yet
+            assert_eq!(execute_twamm_segment(100, None, 1_000_000), (0, 0, -32395));
+        }
+    }
+
+    mod partial_fill_test {
+        use super::*;
+
+        /// Swaps down from tick -32395 through the only initialized tick array of the pool
+        fn swap_past_liquidity(allow_partial_fill: bool) -> Result<(u64, u64, SwapState)> {
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![TickArrayInfo {
+                    start_tick_index: -32400,
+                    ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                }],
+            );
+            let (amount_0, amount_1, state) = swap_internal_with_state(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                // no tick array is initialized in the extension either
+                &Some(TickArrayBitmapExtension::default()),
+                u64::MAX / 2,
+                tick_math::MIN_SQRT_PRICE_X64 + 1,
+                true,
+                true,
+                0,
+                None,
+                allow_partial_fill,
+                oracle::block_timestamp_mock() as u32,
+            )?;
+            Ok((amount_0, amount_1, state))
+        }
+
+        #[test]
+        fn partial_fill_stops_where_liquidity_runs_out_test() {
+            assert_eq!(
+                swap_past_liquidity(false).unwrap_err(),
+                ErrorCode::LiquidityInsufficient.into()
+            );
+
+            let (amount_0, amount_1, state) = swap_past_liquidity(true).unwrap();
+            assert!(amount_0 > 0 && amount_1 > 0);
+            // only the filled part of the specified amount is charged
+            assert_eq!(amount_0 + state.amount_specified_remaining, u64::MAX / 2);
+            assert_eq!(state.amount_calculated, amount_1);
+            assert!(state.tick < -32400);
+        }
+    }
+
+    #[test]
+    fn locked_pool_rejects_swap_test() {
+        let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+            -32395,
+            60,
+            3651942632306380802,
+            5124165121219,
+            vec![TickArrayInfo {
+                start_tick_index: -32400,
+                ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+            }],
+        );
+        // a flash swap is waiting for its callback
+        pool_state.borrow_mut().locked = 1;
+        let result = swap_internal(
+            &amm_config,
+            &mut pool_state.borrow_mut(),
+            &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+            &mut observation_state.borrow_mut(),
+            &None,
+            1_000_000,
+            tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
+            true,
+            true,
+            oracle::block_timestamp_mock() as u32,
+        );
+        assert_eq!(result.unwrap_err(), ErrorCode::LOK.into());
+    }
+
+    #[test]
+    fn circuit_breaker_tick_start_test() {
+        // swaps token_0 down from tick -32395 to tick -32400 with a circuit breaker of `max_ticks`
+        let swap_with_circuit_breaker = |max_ticks: u32| {
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![TickArrayInfo {
+                    start_tick_index: -32400,
+                    ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                }],
+            );
+            let block_timestamp = oracle::block_timestamp_mock() as u32;
+            pool_state
+                .borrow_mut()
+                .initialize_circuit_breaker(max_ticks, 60, 300, block_timestamp);
+            let (_, _, state) = swap_internal_with_state(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                1_000_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
+                true,
+                true,
+                0,
+                None,
+                false,
+                block_timestamp,
+            )
+            .unwrap();
+            let pool_state = pool_state.borrow();
+            (
+                state.circuit_breaker_tick_start,
+                state.tick,
+                pool_state.tick_current,
+                pool_state.circuit_breaker_paused_direction,
+            )
+        };
+
+        let (tick_start, tick, tick_current, paused_direction) = swap_with_circuit_breaker(1);
+        assert_eq!(tick_start, Some(-32395));
+        assert!(tick <= -32400);
+        // the event reads the tick the swap ended at from the pool
+        assert_eq!(tick_current, tick);
+        assert_eq!(paused_direction, 1);
+
+        let (tick_start, _, _, paused_direction) = swap_with_circuit_breaker(100);
+        assert_eq!(tick_start, None);
+        assert_eq!(paused_direction, 0);
+    }
+
+    #[test]
+    fn only_limit_order_liquidity_in_range_fee_test() {
+        fn swap_in_range(limit_order_liquidity: u128) -> (u128, SwapState) {
+            let liquidity = 5124165121219;
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                liquidity,
+                vec![TickArrayInfo {
+                    start_tick_index: -32400,
+                    ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                }],
+            );
+            pool_state.borrow_mut().limit_order_liquidity = limit_order_liquidity;
+            let fee_growth_global_0_x64 = pool_state.borrow().fee_growth_global_0_x64;
+
+            let (_, _, state) = swap_internal_with_state(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &None,
+                1_000_000,
+                tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
+                true,
+                true,
+                0,
+                None,
+                false,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(state.ticks_crossed, 0);
+            (state.fee_growth_global_x64 - fee_growth_global_0_x64, state)
+        }
+
+        let (fee_growth_delta, state) = swap_in_range(0);
+        assert!(fee_growth_delta > 0);
+        assert!(state.fee_amount > 0);
+        assert_eq!(state.protocol_fee, 0);
+
+        // the limit orders don't earn the fee, the protocol takes the LP share
+        let (limit_order_fee_growth_delta, limit_order_state) = swap_in_range(5124165121219);
+        assert_eq!(limit_order_fee_growth_delta, 0);
+        assert_eq!(limit_order_state.fee_amount, 0);
+        assert_eq!(limit_order_state.protocol_fee, state.fee_amount);
+    }
+
+    #[test]
+    fn read_only_tick_arrays_swap_test() {
+        // crosses the ticks -32400, -32460 and -32520, filling the limit order selling token_1 in [-32520, -32460]
+        let limit_order_liquidity = 100_000_000_000;
+        let build_limit_order_pool = || {
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(
+                                -32460,
+                                1194569667438 + limit_order_liquidity,
+                                536061033698 - limit_order_liquidity as i128,
+                            )
+                            .take(),
+                            build_tick(
+                                -32520,
+                                790917615645 + limit_order_liquidity,
+                                790917615645 + limit_order_liquidity as i128,
+                            )
+                            .take(),
+                            build_tick(-32580, 152146472301, 128451145459).take(),
+                        ],
+                    },
+                ],
+            );
+            tick_array_states[1]
+                .borrow_mut()
+                .get_tick_state_mut(-32520, 60)
+                .unwrap()
+                .limit_order_liquidity_1 = limit_order_liquidity;
+            (amm_config, pool_state, tick_array_states, observation_state)
+        };
+        fn swap<T: SwapTickArray>(
+            amm_config: &AmmConfig,
+            pool_state: &RefCell<PoolState>,
+            tick_array_states: &mut VecDeque<T>,
+            observation_state: &RefCell<ObservationState>,
+        ) -> (u64, u64, SwapState) {
+            swap_internal_with_state(
+                amm_config,
+                &mut pool_state.borrow_mut(),
+                tick_array_states,
+                &mut observation_state.borrow_mut(),
+                &None,
+                400_000_000_000,
+                tick_math::get_sqrt_price_at_tick(-32570).unwrap(),
+                true,
+                true,
+                0,
+                None,
+                false,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap()
+        }
+
+        let (amm_config, pool_state, tick_array_states, observation_state) =
+            build_limit_order_pool();
+        let (amount_0, amount_1, state) = swap(
+            &amm_config,
+            &pool_state,
+            &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+            &observation_state,
+        );
+        assert_eq!(state.ticks_crossed, 3);
+        let tick_state = *tick_array_states[1]
+            .borrow_mut()
+            .get_tick_state_mut(-32520, 60)
+            .unwrap();
+        assert_eq!(
+            std::convert::identity(tick_state.limit_order_liquidity_1),
+            0
+        );
+
+        let (amm_config, pool_state, tick_array_states, observation_state) =
+            build_limit_order_pool();
+        let tick_array_states_before = tick_array_states
+            .iter()
+            .map(|tick_array_state| *tick_array_state.borrow())
+            .collect::<Vec<_>>();
+        let (quote_amount_0, quote_amount_1, quote_state) = swap(
+            &amm_config,
+            &pool_state,
+            &mut tick_array_states
+                .iter()
+                .map(|tick_array_state| tick_array_state.borrow())
+                .collect::<VecDeque<_>>(),
+            &observation_state,
+        );
+        // the swap against the read only tick arrays gives the same result without writing them
+        assert_eq!((quote_amount_0, quote_amount_1), (amount_0, amount_1));
+        assert_eq!(quote_state.ticks_crossed, state.ticks_crossed);
+        assert_eq!(quote_state.tick, state.tick);
+        assert_eq!(quote_state.sqrt_price_x64, state.sqrt_price_x64);
+        assert_eq!(quote_state.liquidity, state.liquidity);
+        assert_eq!(
+            quote_state.limit_order_liquidity,
+            state.limit_order_liquidity
+        );
+        for (tick_array_state, tick_array_state_before) in tick_array_states
+            .iter()
+            .zip(tick_array_states_before.iter())
+        {
+            assert_eq!(
+                bytemuck::bytes_of(&*tick_array_state.borrow()),
+                bytemuck::bytes_of(tick_array_state_before)
+            );
+        }
+    }
+
     mod find_next_initialized_tick_test {
         use super::*;
 
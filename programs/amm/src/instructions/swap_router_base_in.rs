@@ -1,6 +1,6 @@
 use crate::error::ErrorCode;
 use crate::states::*;
-use crate::swap_v2::{exact_internal_v2, SwapSingleV2};
+use crate::swap_v2::{exact_internal_v2, SwapReturnData, SwapSingleV2};
 use anchor_lang::prelude::*;
 use anchor_spl::memo::Memo;
 use anchor_spl::{
@@ -28,14 +28,22 @@ pub struct SwapRouterBaseIn<'info> {
 
     /// Memo program
     pub memo_program: Program<'info, Memo>,
+
+    /// The referrer token account of input token which receives the referral fee of the first hop,
+    /// pass the program id if the swap has no referrer
+    #[account(mut)]
+    pub referral_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
 pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
     amount_in: u64,
     amount_out_minimum: u64,
+    referral_fee_bps: u16,
 ) -> Result<()> {
     let mut amount_in_internal = amount_in;
+    // the referral fee is charged in the input token of the route, so only the first hop pays it
+    let mut referral_token_account = ctx.accounts.referral_token_account.clone().map(Box::new);
     let mut input_token_account = Box::new(ctx.accounts.input_token_account.clone());
     let mut input_token_mint = Box::new(ctx.accounts.input_token_mint.clone());
     let mut accounts: &[AccountInfo] = ctx.remaining_accounts;
@@ -91,11 +99,15 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
                 token_program: ctx.accounts.token_program.clone(),
                 token_program_2022: ctx.accounts.token_program_2022.clone(),
                 memo_program: ctx.accounts.memo_program.clone(),
+                referral_token_account: referral_token_account.take(),
             },
             accounts,
             amount_in_internal,
             0,
             true,
+            referral_fee_bps,
+            None,
+            false,
         )?;
         // output token is the new swap input token
         input_token_account = output_token_account;
@@ -107,5 +119,15 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
         ErrorCode::TooLittleOutputReceived
     );
 
-    Ok(())
+    let input_balance_before = ctx.accounts.input_token_account.amount;
+    ctx.accounts.input_token_account.reload()?;
+    let amount_in_paid = input_balance_before
+        .checked_sub(ctx.accounts.input_token_account.amount)
+        .unwrap();
+    SwapReturnData {
+        amount_in: amount_in_paid,
+        amount_out: amount_in_internal,
+        unfilled_amount: amount_in.saturating_sub(amount_in_paid),
+    }
+    .set()
 }
@@ -0,0 +1,212 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::swap_router_base_in::SwapRouterBaseIn;
+use crate::swap_v2::{exact_internal_v2, SwapReturnData, SwapSingleV2};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/// The accounts every hop starts with: amm_config, pool_state, output_token_account,
+/// input_vault, output_vault, output_token_mint and observation_state
+pub const ROUTE_HOP_FIXED_ACCOUNTS_LEN: usize = 7;
+
+/// Describes one hop of a route in the remaining accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RouteHop {
+    /// The index in the remaining accounts where the accounts of this hop start
+    pub pool_index: u8,
+    /// The number of accounts of this hop, the fixed accounts followed by
+    /// the tick array bitmap extension, fee override and tick array accounts
+    pub account_count: u8,
+    /// The swap direction of this hop, must match the input token of the hop
+    pub zero_for_one: bool,
+    /// The price limit of this hop, 0 means no limit
+    pub sqrt_price_limit_x64: u128,
+    /// The minimum output amount of this hop, 0 means no check
+    pub amount_out_minimum: u64,
+}
+
+impl RouteHop {
+    /// Splits the accounts of this hop out of the remaining accounts, returns the fixed accounts
+    /// and the accounts passed on to the swap
+    pub fn split_accounts<'c, T>(&self, remaining_accounts: &'c [T]) -> Result<(&'c [T], &'c [T])> {
+        let accounts_start = usize::from(self.pool_index);
+        let accounts_end = accounts_start + usize::from(self.account_count);
+        require!(
+            usize::from(self.account_count) >= ROUTE_HOP_FIXED_ACCOUNTS_LEN
+                && accounts_end <= remaining_accounts.len(),
+            ErrorCode::InvalidRouterHops
+        );
+        Ok(remaining_accounts[accounts_start..accounts_end].split_at(ROUTE_HOP_FIXED_ACCOUNTS_LEN))
+    }
+
+    /// Checks the output of this hop against its minimum
+    pub fn check_amount_out(&self, amount_out: u64) -> Result<()> {
+        require_gte!(
+            amount_out,
+            self.amount_out_minimum,
+            ErrorCode::TooLittleOutputReceived
+        );
+        Ok(())
+    }
+}
+
+pub fn swap_router_base_in_v2<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
+    amount_in: u64,
+    amount_out_minimum: u64,
+    referral_fee_bps: u16,
+    hops: Vec<RouteHop>,
+) -> Result<()> {
+    require!(!hops.is_empty(), ErrorCode::InvalidRouterHops);
+    let mut amount_in_internal = amount_in;
+    // the referral fee is charged in the input token of the route, so only the first hop pays it
+    let mut referral_token_account = ctx.accounts.referral_token_account.clone().map(Box::new);
+    let mut input_token_account = Box::new(ctx.accounts.input_token_account.clone());
+    let mut input_token_mint = Box::new(ctx.accounts.input_token_mint.clone());
+    for hop in hops.iter() {
+        let (hop_accounts, tick_array_accounts) = hop.split_accounts(ctx.remaining_accounts)?;
+        let amm_config = Box::new(Account::<AmmConfig>::try_from(&hop_accounts[0])?);
+        let pool_state_loader = AccountLoader::<PoolState>::try_from(&hop_accounts[1])?;
+        let output_token_account = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            &hop_accounts[2],
+        )?);
+        let input_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            &hop_accounts[3],
+        )?);
+        let output_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            &hop_accounts[4],
+        )?);
+        let output_token_mint = Box::new(InterfaceAccount::<Mint>::try_from(&hop_accounts[5])?);
+        let observation_state = AccountLoader::<ObservationState>::try_from(&hop_accounts[6])?;
+
+        {
+            let pool_state = pool_state_loader.load()?;
+            // check observation account is owned by the pool
+            require_keys_eq!(pool_state.observation_key, observation_state.key());
+            // check ammConfig account is associate with the pool
+            require_keys_eq!(pool_state.amm_config, amm_config.key());
+            // check the described direction is the one the input token swaps in
+            require!(
+                hop.zero_for_one == (input_token_mint.key() == pool_state.token_mint_0),
+                ErrorCode::InvalidRouterHops
+            );
+        }
+
+        amount_in_internal = exact_internal_v2(
+            &mut SwapSingleV2 {
+                payer: ctx.accounts.payer.clone(),
+                amm_config,
+                input_token_account: input_token_account.clone(),
+                pool_state: pool_state_loader,
+                output_token_account: output_token_account.clone(),
+                input_vault,
+                output_vault,
+                input_vault_mint: input_token_mint.clone(),
+                output_vault_mint: output_token_mint.clone(),
+                observation_state,
+                token_program: ctx.accounts.token_program.clone(),
+                token_program_2022: ctx.accounts.token_program_2022.clone(),
+                memo_program: ctx.accounts.memo_program.clone(),
+                referral_token_account: referral_token_account.take(),
+            },
+            tick_array_accounts,
+            amount_in_internal,
+            hop.sqrt_price_limit_x64,
+            true,
+            referral_fee_bps,
+            None,
+            false,
+        )?;
+        hop.check_amount_out(amount_in_internal)?;
+        // output token is the new swap input token
+        input_token_account = output_token_account;
+        input_token_mint = output_token_mint;
+    }
+    require_gte!(
+        amount_in_internal,
+        amount_out_minimum,
+        ErrorCode::TooLittleOutputReceived
+    );
+
+    let input_balance_before = ctx.accounts.input_token_account.amount;
+    ctx.accounts.input_token_account.reload()?;
+    let amount_in_paid = input_balance_before
+        .checked_sub(ctx.accounts.input_token_account.amount)
+        .unwrap();
+    SwapReturnData {
+        amount_in: amount_in_paid,
+        amount_out: amount_in_internal,
+        unfilled_amount: amount_in.saturating_sub(amount_in_paid),
+    }
+    .set()
+}
+
+#[cfg(test)]
+mod swap_router_base_in_v2_test {
+    use super::*;
+
+    #[test]
+    fn split_accounts_test() {
+        let remaining_accounts: Vec<u8> = (0..20).collect();
+        let hop = RouteHop {
+            pool_index: 0,
+            account_count: 9,
+            ..Default::default()
+        };
+        let (hop_accounts, tick_array_accounts) = hop.split_accounts(&remaining_accounts).unwrap();
+        assert_eq!(hop_accounts, &[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(tick_array_accounts, &[7, 8]);
+
+        // the second hop starts after the accounts of the first one, without tick arrays
+        let hop = RouteHop {
+            pool_index: 9,
+            account_count: 7,
+            ..Default::default()
+        };
+        let (hop_accounts, tick_array_accounts) = hop.split_accounts(&remaining_accounts).unwrap();
+        assert_eq!(hop_accounts, &[9, 10, 11, 12, 13, 14, 15]);
+        assert!(tick_array_accounts.is_empty());
+
+        // the accounts of the last hop end at the last remaining account
+        let hop = RouteHop {
+            pool_index: 13,
+            account_count: 7,
+            ..Default::default()
+        };
+        assert!(hop.split_accounts(&remaining_accounts).is_ok());
+    }
+
+    #[test]
+    fn split_accounts_invalid_hop_test() {
+        let remaining_accounts: Vec<u8> = (0..20).collect();
+        for (pool_index, account_count) in [(0, 6), (14, 7), (20, 7), (255, 255)] {
+            let hop = RouteHop {
+                pool_index,
+                account_count,
+                ..Default::default()
+            };
+            assert_eq!(
+                hop.split_accounts(&remaining_accounts).unwrap_err(),
+                ErrorCode::InvalidRouterHops.into()
+            );
+        }
+    }
+
+    #[test]
+    fn intermediate_amount_out_minimum_test() {
+        let hop = RouteHop {
+            amount_out_minimum: 1000,
+            ..Default::default()
+        };
+        assert!(hop.check_amount_out(1000).is_ok());
+        assert!(hop.check_amount_out(1001).is_ok());
+        // an intermediate hop below its minimum fails the route even if the last hop would pass
+        assert_eq!(
+            hop.check_amount_out(999).unwrap_err(),
+            ErrorCode::TooLittleOutputReceived.into()
+        );
+
+        // no minimum by default
+        assert!(RouteHop::default().check_amount_out(0).is_ok());
+    }
+}
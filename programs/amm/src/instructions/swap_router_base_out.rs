@@ -0,0 +1,215 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::swap_v2::{settle_swap_v2, swap_pool_v2, SwapReturnData, SwapSingleV2};
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct SwapRouterBaseOut<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// The token account that pays input tokens for the swap
+    #[account(mut)]
+    pub input_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The mint of input token
+    #[account(mut)]
+    pub input_token_mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// Memo program
+    pub memo_program: Program<'info, Memo>,
+
+    /// The referrer token account of input token which receives the referral fee of the first hop,
+    /// pass the program id if the swap has no referrer
+    #[account(mut)]
+    pub referral_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Walks the hops backwards, each hop must output exactly what the next hop transfers in.
+/// `quote_hop` returns the quote of a hop for its output amount and the amount it transfers in,
+/// the quotes are returned in route order
+fn quote_hops_in_reverse<H, Q>(
+    hops: &mut [H],
+    amount_out: u64,
+    mut quote_hop: impl FnMut(&mut H, u64) -> Result<(Q, u64)>,
+) -> Result<Vec<Q>> {
+    let mut quotes = Vec::with_capacity(hops.len());
+    let mut amount_out_internal = amount_out;
+    for hop in hops.iter_mut().rev() {
+        let (quote, amount_in_internal) = quote_hop(hop, amount_out_internal)?;
+        amount_out_internal = amount_in_internal;
+        quotes.push(quote);
+    }
+    quotes.reverse();
+    Ok(quotes)
+}
+
+pub fn swap_router_base_out<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseOut<'info>>,
+    amount_out: u64,
+    amount_in_maximum: u64,
+    referral_fee_bps: u16,
+) -> Result<()> {
+    // collect the hops in route order, each with the tick array accounts up to the next hop
+    let mut hops: Vec<(SwapSingleV2<'info>, &'c [AccountInfo<'info>])> = Vec::new();
+    let mut input_token_account = Box::new(ctx.accounts.input_token_account.clone());
+    let mut input_token_mint = Box::new(ctx.accounts.input_token_mint.clone());
+    let mut accounts: &'c [AccountInfo<'info>] = ctx.remaining_accounts;
+    while !accounts.is_empty() {
+        let mut remaining_accounts = accounts.iter();
+        let amm_config = Box::new(Account::<AmmConfig>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        let pool_state_loader =
+            AccountLoader::<PoolState>::try_from(remaining_accounts.next().unwrap())?;
+        let output_token_account = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        let input_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        let output_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        let output_token_mint = Box::new(InterfaceAccount::<Mint>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        let observation_state =
+            AccountLoader::<ObservationState>::try_from(remaining_accounts.next().unwrap())?;
+
+        {
+            let pool_state = pool_state_loader.load()?;
+            // check observation account is owned by the pool
+            require_keys_eq!(pool_state.observation_key, observation_state.key());
+            // check ammConfig account is associate with the pool
+            require_keys_eq!(pool_state.amm_config, amm_config.key());
+        }
+
+        accounts = remaining_accounts.as_slice();
+        let hop_accounts_len = accounts
+            .iter()
+            .position(|account_info| account_info.data_len() == AmmConfig::LEN)
+            .unwrap_or(accounts.len());
+        let (hop_accounts, next_accounts) = accounts.split_at(hop_accounts_len);
+        accounts = next_accounts;
+
+        hops.push((
+            SwapSingleV2 {
+                payer: ctx.accounts.payer.clone(),
+                amm_config,
+                input_token_account: input_token_account.clone(),
+                pool_state: pool_state_loader,
+                output_token_account: output_token_account.clone(),
+                input_vault,
+                output_vault,
+                input_vault_mint: input_token_mint.clone(),
+                output_vault_mint: output_token_mint.clone(),
+                observation_state,
+                token_program: ctx.accounts.token_program.clone(),
+                token_program_2022: ctx.accounts.token_program_2022.clone(),
+                memo_program: ctx.accounts.memo_program.clone(),
+                referral_token_account: if hops.is_empty() {
+                    // the referral fee is charged in the input token of the route, so only the first hop pays it
+                    ctx.accounts.referral_token_account.clone().map(Box::new)
+                } else {
+                    None
+                },
+            },
+            hop_accounts,
+        ));
+        // output token is the new swap input token
+        input_token_account = output_token_account;
+        input_token_mint = output_token_mint;
+    }
+    require!(!hops.is_empty(), ErrorCode::InvalidRouterHops);
+
+    let pending_swaps = quote_hops_in_reverse(
+        &mut hops,
+        amount_out,
+        |(hop, hop_accounts), amount_out_internal| {
+            let pending_swap = swap_pool_v2(
+                hop,
+                hop_accounts,
+                amount_out_internal,
+                0,
+                false,
+                if hop.referral_token_account.is_some() {
+                    referral_fee_bps
+                } else {
+                    0
+                },
+                None,
+                false,
+            )?;
+            let amount_in_internal = pending_swap.transfer_amount_in(hop.input_vault_mint.clone());
+            Ok((pending_swap, amount_in_internal))
+        },
+    )?;
+
+    // settle the transfers in route order, so every intermediate token is received before it is paid
+    let mut amount_in = 0;
+    for (index, ((hop, _), pending_swap)) in hops.iter_mut().zip(pending_swaps.iter()).enumerate() {
+        let amount_in_internal = settle_swap_v2(hop, pending_swap)?;
+        if index == 0 {
+            amount_in = amount_in_internal;
+        }
+    }
+    require_gte!(amount_in_maximum, amount_in, ErrorCode::TooMuchInputPaid);
+
+    SwapReturnData {
+        amount_in,
+        amount_out,
+        unfilled_amount: 0,
+    }
+    .set()
+}
+
+#[cfg(test)]
+mod swap_router_base_out_test {
+    use super::*;
+
+    #[test]
+    fn quote_hops_in_reverse_test() {
+        // each hop takes twice its output plus a fixed fee of 1
+        let mut hops = vec!["a->b", "b->c", "c->d"];
+        let mut quoted = Vec::new();
+        let quotes = quote_hops_in_reverse(&mut hops, 100, |hop, amount_out| {
+            quoted.push(*hop);
+            let amount_in = amount_out * 2 + 1;
+            Ok(((*hop, amount_in, amount_out), amount_in))
+        })
+        .unwrap();
+        // quoted from the last hop, returned in route order
+        assert_eq!(quoted, vec!["c->d", "b->c", "a->b"]);
+        assert_eq!(
+            quotes,
+            vec![("a->b", 807, 403), ("b->c", 403, 201), ("c->d", 201, 100)]
+        );
+        // every hop outputs exactly what the next hop transfers in
+        for pair in quotes.windows(2) {
+            assert_eq!(pair[0].2, pair[1].1);
+        }
+
+        // an error of any hop fails the route
+        let result = quote_hops_in_reverse(&mut hops, 100, |hop, amount_out| {
+            if *hop == "b->c" {
+                return err!(ErrorCode::TooSmallInputOrOutputAmount);
+            }
+            Ok(((), amount_out))
+        });
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::TooSmallInputOrOutputAmount.into()
+        );
+    }
+}
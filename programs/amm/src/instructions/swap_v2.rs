@@ -2,11 +2,12 @@ use std::collections::VecDeque;
 use std::ops::Deref;
 
 use crate::error::ErrorCode;
-use crate::libraries::tick_math;
-use crate::swap::swap_internal;
+use crate::libraries::{full_math::MulDiv, tick_math};
+use crate::swap::{emit_circuit_breaker_tripped, swap_internal_with_state};
 use crate::util::*;
 use crate::{states::*, util};
-use anchor_lang::{prelude::*, solana_program};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
 use anchor_spl::memo::Memo;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
@@ -66,11 +67,72 @@ pub struct SwapSingleV2<'info> {
         address = output_vault.mint
     )]
     pub output_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The referrer token account of input token which receives the referral fee,
+    /// pass the program id if the swap has no referrer
+    #[account(mut)]
+    pub referral_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     // remaining accounts
     // tickarray_bitmap_extension: must add account if need regardless the sequence
     // tick_array_account_1
     // tick_array_account_2
     // tick_array_account_...
+    // hook accounts: must add the hook program and its accounts at last if the pool has a swap hook
+}
+
+/// The final amounts of a swap, set as the return data of swap_v2 and the routers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwapReturnData {
+    /// The amount of input token paid by the user
+    pub amount_in: u64,
+    /// The amount of output token received by the user
+    pub amount_out: u64,
+    /// The part of the specified amount left unfilled, in input token if base input, otherwise in output token.
+    /// The filled part is amount_in if base input, otherwise amount_out
+    pub unfilled_amount: u64,
+}
+
+impl SwapReturnData {
+    pub fn set(&self) -> Result<()> {
+        set_return_data(&self.try_to_vec()?);
+        Ok(())
+    }
+}
+
+/// The pool side result of an exact swap whose token transfers are not settled yet
+pub struct PendingSwapV2 {
+    pub amount_specified: u64,
+    pub sqrt_price_limit_x64: u128,
+    pub is_base_input: bool,
+    pub zero_for_one: bool,
+    pub swap_price_before: u128,
+    /// The amount passed to the pool, the specified amount adjusted by the transfer fee
+    pub amount_calculate_specified: u64,
+    /// The transfer fee of the specified amount
+    pub transfer_fee: u64,
+    pub amount_0: u64,
+    pub amount_1: u64,
+    pub fee_amount: u64,
+    pub referral_fee_bps: u16,
+    pub referral_fee: u64,
+    pub allow_partial_fill: bool,
+}
+
+impl PendingSwapV2 {
+    /// The amount the user must transfer into the input vault, transfer fee included
+    pub fn transfer_amount_in(&self, input_vault_mint: Box<InterfaceAccount<Mint>>) -> u64 {
+        let amount_in = if self.zero_for_one {
+            self.amount_0
+        } else {
+            self.amount_1
+        };
+        let transfer_fee = if self.is_base_input && amount_in == self.amount_calculate_specified {
+            self.transfer_fee
+        } else {
+            util::get_transfer_inverse_fee(input_vault_mint, amount_in).unwrap()
+        };
+        amount_in + transfer_fee
+    }
 }
 
 /// Performs a single exact input/output swap
@@ -81,18 +143,59 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
     amount_specified: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
 ) -> Result<u64> {
-    // invoke_memo_instruction(SWAP_MEMO_MSG, ctx.memo_program.to_account_info())?;
+    let pending_swap = swap_pool_v2(
+        ctx,
+        remaining_accounts,
+        amount_specified,
+        sqrt_price_limit_x64,
+        is_base_input,
+        referral_fee_bps,
+        max_deviation_ticks,
+        allow_partial_fill,
+    )?;
+    settle_swap_v2(ctx, &pending_swap)
+}
 
-    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+/// Updates the pool state for an exact input/output swap without moving any tokens,
+/// the returned swap must be settled by `settle_swap_v2`
+pub fn swap_pool_v2<'c: 'info, 'info>(
+    ctx: &mut SwapSingleV2<'info>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    is_base_input: bool,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
+) -> Result<PendingSwapV2> {
+    // invoke_memo_instruction(SWAP_MEMO_MSG, ctx.memo_program.to_account_info())?;
 
     let amount_0;
     let amount_1;
     let zero_for_one;
     let swap_price_before;
-
-    let input_balance_before = ctx.input_token_account.amount;
-    let output_balance_before = ctx.output_token_account.amount;
+    let fee_amount;
+    let referral_fee;
+
+    let referral_fee_bps = if let Some(referral_token_account) = &ctx.referral_token_account {
+        require_keys_eq!(
+            referral_token_account.mint,
+            ctx.input_vault.mint,
+            ErrorCode::InvalidReferralTokenAccount
+        );
+        require_gte!(
+            ctx.amm_config.referral_fee_bps_max,
+            referral_fee_bps,
+            ErrorCode::ReferralFeeTooHigh
+        );
+        referral_fee_bps
+    } else {
+        0
+    };
 
     // calculate specified amount because the amount includes transfer_fee as input and without transfer_fee as output
     let (amount_calculate_specified, transfer_fee) = if is_base_input {
@@ -106,12 +209,28 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         (amount_specified + transfer_fee, transfer_fee)
     };
 
+    let (remaining_accounts, hook_accounts) =
+        split_hook_accounts(&ctx.pool_state, remaining_accounts, &SWAP_HOOK_PERMISSIONS)?;
+    invoke_hook(
+        &ctx.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::BeforeSwap,
+        BEFORE_SWAP_HOOK_DISCRIMINATOR,
+        &BeforeSwapHook {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.payer.key(),
+            zero_for_one: ctx.input_vault.mint == ctx.pool_state.load()?.token_mint_0,
+            is_base_input,
+            amount_specified: amount_calculate_specified,
+            sqrt_price_limit_x64,
+        },
+    )?;
+
     {
         swap_price_before = ctx.pool_state.load()?.sqrt_price_x64;
         let pool_state = &mut ctx.pool_state.load_mut()?;
         zero_for_one = ctx.input_vault.mint == pool_state.token_mint_0;
-
-        require_gt!(block_timestamp, pool_state.open_time);
+        let swap_fees_before = pool_state.get_accumulated_swap_fees(zero_for_one);
 
         require!(
             if zero_for_one {
@@ -127,8 +246,19 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         let mut tickarray_bitmap_extension = None;
         let tick_array_states = &mut VecDeque::new();
 
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
         let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
         for account_info in remaining_accounts.into_iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
             if account_info.key().eq(&tick_array_bitmap_extension_key) {
                 tickarray_bitmap_extension = Some(
                     *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
@@ -140,8 +270,10 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
         }
 
-        (amount_0, amount_1) = swap_internal(
-            &ctx.amm_config,
+        let amm_config = get_effective_amm_config(&ctx.amm_config, pool_state, pool_fee_override)?;
+        let swap_state;
+        (amount_0, amount_1, swap_state) = swap_internal_with_state(
+            &amm_config,
             pool_state,
             tick_array_states,
             &mut ctx.observation_state.load_mut()?,
@@ -158,8 +290,15 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             },
             zero_for_one,
             is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
             oracle::block_timestamp(),
         )?;
+        emit_circuit_breaker_tripped(pool_state, swap_state.circuit_breaker_tick_start);
+        referral_fee = swap_state.referral_fee;
+        fee_amount =
+            pool_state.get_accumulated_swap_fees(zero_for_one) - swap_fees_before + referral_fee;
 
         #[cfg(feature = "enable-log")]
         msg!(
@@ -173,6 +312,82 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             ErrorCode::TooSmallInputOrOutputAmount
         );
     }
+    let after_swap_hook = {
+        let pool_state = ctx.pool_state.load()?;
+        AfterSwapHook {
+            pool_state: pool_state.key(),
+            sender: ctx.payer.key(),
+            zero_for_one,
+            is_base_input,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        }
+    };
+    invoke_hook(
+        &ctx.pool_state,
+        hook_accounts,
+        HookPermissionBitIndex::AfterSwap,
+        AFTER_SWAP_HOOK_DISCRIMINATOR,
+        &after_swap_hook,
+    )?;
+    Ok(PendingSwapV2 {
+        amount_specified,
+        sqrt_price_limit_x64,
+        is_base_input,
+        zero_for_one,
+        swap_price_before,
+        amount_calculate_specified,
+        transfer_fee,
+        amount_0,
+        amount_1,
+        fee_amount,
+        referral_fee_bps,
+        referral_fee,
+        allow_partial_fill,
+    })
+}
+
+/// The filled part of the specified amount, which is the amount transferred in by the user for a
+/// base input swap, and the amount received by the user after the transfer fee for a base output swap
+fn get_filled_amount(
+    is_base_input: bool,
+    transfer_amount_in: u64,
+    amount_out_without_fee: u64,
+) -> u64 {
+    if is_base_input {
+        transfer_amount_in
+    } else {
+        amount_out_without_fee
+    }
+}
+
+/// Transfers the tokens of a swap computed by `swap_pool_v2`
+/// if is_base_input = true, return value is the max_amount_out, otherwise is min_amount_in
+pub fn settle_swap_v2<'info>(
+    ctx: &mut SwapSingleV2<'info>,
+    pending_swap: &PendingSwapV2,
+) -> Result<u64> {
+    let PendingSwapV2 {
+        amount_specified,
+        sqrt_price_limit_x64,
+        is_base_input,
+        zero_for_one,
+        swap_price_before,
+        amount_calculate_specified,
+        transfer_fee,
+        amount_0,
+        amount_1,
+        fee_amount,
+        referral_fee_bps,
+        referral_fee,
+        allow_partial_fill,
+    } = *pending_swap;
+
+    let input_balance_before = ctx.input_token_account.amount;
+    let output_balance_before = ctx.output_token_account.amount;
+
     let (token_account_0, token_account_1, vault_0, vault_1, vault_0_mint, vault_1_mint) =
         if zero_for_one {
             (
@@ -288,9 +503,34 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             transfer_amount_0,
         )?;
     }
+    if referral_fee > 0 {
+        // the referral fee was paid into the input vault along with the input amount
+        let referral_token_account = ctx.referral_token_account.as_ref().unwrap();
+        transfer_from_pool_vault_to_user(
+            &ctx.pool_state,
+            &ctx.input_vault.to_account_info(),
+            &referral_token_account.to_account_info(),
+            Some(ctx.input_vault_mint.clone()),
+            &ctx.token_program,
+            Some(ctx.token_program_2022.to_account_info()),
+            referral_fee,
+        )?;
+        emit!(ReferralFeeEvent {
+            pool_state: ctx.pool_state.key(),
+            referral_token_account: referral_token_account.key(),
+            mint: ctx.input_vault.mint,
+            referral_fee_bps,
+            referral_fee_amount: referral_fee,
+        });
+    }
     ctx.output_token_account.reload()?;
     ctx.input_token_account.reload()?;
 
+    let filled_amount = if zero_for_one {
+        get_filled_amount(is_base_input, transfer_amount_0, amount_1_without_fee)
+    } else {
+        get_filled_amount(is_base_input, transfer_amount_1, amount_0_without_fee)
+    };
     let pool_state = ctx.pool_state.load()?;
     emit!(SwapEvent {
         pool_state: pool_state.key(),
@@ -304,28 +544,19 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         zero_for_one,
         sqrt_price_x64: pool_state.sqrt_price_x64,
         liquidity: pool_state.liquidity,
-        tick: pool_state.tick_current
+        tick: pool_state.tick_current,
+        fee_amount,
+        filled_amount,
+        unfilled_amount: amount_specified.saturating_sub(filled_amount),
     });
     if zero_for_one {
         require_gt!(swap_price_before, pool_state.sqrt_price_x64);
     } else {
         require_gt!(pool_state.sqrt_price_x64, swap_price_before);
     }
-    if sqrt_price_limit_x64 == 0 {
+    if sqrt_price_limit_x64 == 0 && !allow_partial_fill {
         // Does't allow partial filled without specified limit_price.
-        if is_base_input {
-            if zero_for_one {
-                require_eq!(amount_specified, transfer_amount_0);
-            } else {
-                require_eq!(amount_specified, transfer_amount_1);
-            }
-        } else {
-            if zero_for_one {
-                require_eq!(amount_specified, transfer_amount_1);
-            } else {
-                require_eq!(amount_specified, transfer_amount_0);
-            }
-        }
+        require_eq!(amount_specified, filled_amount);
     }
 
     if is_base_input {
@@ -347,14 +578,46 @@ pub fn swap_v2<'a, 'b, 'c: 'info, 'info>(
     other_amount_threshold: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
+    referral_fee_bps: u16,
+    max_deviation_ticks: Option<u32>,
+    allow_partial_fill: bool,
 ) -> Result<()> {
+    let input_balance_before = ctx.accounts.input_token_account.amount;
+    let output_balance_before = ctx.accounts.output_token_account.amount;
     let amount_result = exact_internal_v2(
         ctx.accounts,
         ctx.remaining_accounts,
         amount,
         sqrt_price_limit_x64,
         is_base_input,
+        referral_fee_bps,
+        max_deviation_ticks,
+        allow_partial_fill,
     )?;
+    let amount_in = input_balance_before
+        .checked_sub(ctx.accounts.input_token_account.amount)
+        .unwrap();
+    let amount_out = ctx
+        .accounts
+        .output_token_account
+        .amount
+        .checked_sub(output_balance_before)
+        .unwrap();
+    let filled_amount = if is_base_input { amount_in } else { amount_out };
+    // a partially filled swap is checked against the threshold of the filled part only
+    let other_amount_threshold = if allow_partial_fill && filled_amount < amount {
+        if is_base_input {
+            other_amount_threshold
+                .mul_div_floor(filled_amount, amount)
+                .unwrap()
+        } else {
+            other_amount_threshold
+                .mul_div_ceil(filled_amount, amount)
+                .unwrap()
+        }
+    } else {
+        other_amount_threshold
+    };
     if is_base_input {
         require_gte!(
             amount_result,
@@ -369,5 +632,92 @@ pub fn swap_v2<'a, 'b, 'c: 'info, 'info>(
         );
     }
 
-    Ok(())
+    SwapReturnData {
+        amount_in,
+        amount_out,
+        unfilled_amount: amount.saturating_sub(filled_amount),
+    }
+    .set()
+}
+
+#[cfg(test)]
+mod swap_v2_test {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    fn build_pending_swap(is_base_input: bool, zero_for_one: bool) -> PendingSwapV2 {
+        PendingSwapV2 {
+            amount_specified: 1000,
+            sqrt_price_limit_x64: 0,
+            is_base_input,
+            zero_for_one,
+            swap_price_before: 0,
+            amount_calculate_specified: 990,
+            transfer_fee: 10,
+            amount_0: 990,
+            amount_1: 500,
+            fee_amount: 0,
+            referral_fee_bps: 0,
+            referral_fee: 0,
+            allow_partial_fill: false,
+        }
+    }
+
+    #[test]
+    fn transfer_amount_in_test() {
+        let key = Pubkey::new_unique();
+        let owner = Token::id();
+        let mut lamports = 0;
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let mint_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let mint = Box::new(InterfaceAccount::<Mint>::try_from(&mint_info).unwrap());
+
+        // a fully filled base input swap pays the transfer fee of the specified amount
+        assert_eq!(
+            build_pending_swap(true, true).transfer_amount_in(mint.clone()),
+            1000
+        );
+        // the input amount of a base output swap is grossed up by the fee of the input mint
+        assert_eq!(
+            build_pending_swap(false, true).transfer_amount_in(mint.clone()),
+            990
+        );
+        assert_eq!(
+            build_pending_swap(false, false).transfer_amount_in(mint.clone()),
+            500
+        );
+        // a partially filled base input swap doesn't reuse the fee of the specified amount
+        let mut pending_swap = build_pending_swap(true, true);
+        pending_swap.amount_0 = 900;
+        assert_eq!(pending_swap.transfer_amount_in(mint), 900);
+    }
+
+    #[test]
+    fn base_output_filled_amount_test() {
+        // the output token charges a transfer fee of 10, the vault sends 1010 for 1000 received
+        let amount_specified = 1000;
+        let transfer_amount_out = 1010;
+        let transfer_fee_out = 10;
+        let filled_amount = get_filled_amount(false, 2000, transfer_amount_out - transfer_fee_out);
+        assert_eq!(filled_amount, amount_specified);
+
+        // a base input swap is filled by the amount transferred in, transfer fee included
+        assert_eq!(get_filled_amount(true, 1000, 1990), 1000);
+    }
 }
@@ -0,0 +1,536 @@
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, tick_math};
+use crate::states::*;
+use crate::swap::{emit_circuit_breaker_tripped, swap_internal_with_state};
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::cell::RefMut;
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+#[derive(Accounts)]
+#[instruction(zero_for_one: bool)]
+pub struct CreateTwammOrderPool<'info> {
+    /// Pays to create the order pool
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The pool the orders are executed against
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The order pool selling token_0 or token_1 of the pool
+    #[account(
+        init,
+        seeds = [
+            TWAMM_ORDER_POOL_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &[zero_for_one as u8],
+        ],
+        bump,
+        payer = payer,
+        space = TwammOrderPool::LEN
+    )]
+    pub order_pool: AccountLoader<'info, TwammOrderPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_nonce: u64)]
+pub struct PlaceTwammOrder<'info> {
+    /// The owner of the order, pays for the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The order pool the order sells through, executed up to the current time
+    #[account(mut, constraint = order_pool.load()?.pool_id == pool_state.key())]
+    pub order_pool: AccountLoader<'info, TwammOrderPool>,
+
+    /// The order account
+    #[account(
+        init,
+        seeds = [
+            TWAMM_ORDER_SEED.as_bytes(),
+            order_pool.key().as_ref(),
+            owner.key().as_ref(),
+            &order_nonce.to_be_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = TwammOrderState::LEN
+    )]
+    pub order: Box<Account<'info, TwammOrderState>>,
+
+    /// The owner's token account of the sold token
+    #[account(mut, token::mint = input_vault.mint)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The pool vault of the sold token
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the sold token
+    #[account(address = input_vault.mint)]
+    pub input_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTwammOrder<'info> {
+    /// The owner of the order, receives the rent of the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(mut, constraint = order_pool.load()?.pool_id == pool_state.key())]
+    pub order_pool: AccountLoader<'info, TwammOrderPool>,
+
+    /// The order to withdraw
+    #[account(
+        mut,
+        close = owner,
+        constraint = order.order_pool == order_pool.key(),
+        constraint = order.owner == owner.key(),
+    )]
+    pub order: Box<Account<'info, TwammOrderState>>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_0
+    #[account(mut, token::mint = token_vault_0.mint)]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_1
+    #[account(mut, token::mint = token_vault_1.mint)]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(address = token_vault_0.mint)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(address = token_vault_1.mint)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTwamm<'info> {
+    /// Anyone can execute the orders and earn the keeper fee
+    pub keeper: Signer<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The pool the orders are executed against
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(mut, constraint = order_pool.load()?.pool_id == pool_state.key())]
+    pub order_pool: AccountLoader<'info, TwammOrderPool>,
+
+    /// The pool vault of the sold token
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The keeper's token account receiving the keeper fee
+    #[account(mut, token::mint = input_vault.mint)]
+    pub keeper_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the sold token
+    #[account(address = input_vault.mint)]
+    pub input_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need
+    // pool_fee_override: must add account if the pool fees are overridden
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+pub fn create_twamm_order_pool(
+    ctx: Context<CreateTwammOrderPool>,
+    zero_for_one: bool,
+) -> Result<()> {
+    {
+        // the keeper executes the orders without calling the swap hooks
+        let pool_state = ctx.accounts.pool_state.load()?;
+        require!(
+            !pool_state.has_hook_permission(HookPermissionBitIndex::BeforeSwap)
+                && !pool_state.has_hook_permission(HookPermissionBitIndex::AfterSwap),
+            ErrorCode::HookNotSupported
+        );
+    }
+    ctx.accounts.order_pool.load_init()?.initialize(
+        ctx.bumps.order_pool,
+        ctx.accounts.pool_state.key(),
+        zero_for_one,
+        u64::from(oracle::block_timestamp()),
+    );
+    Ok(())
+}
+
+pub fn place_twamm_order(
+    ctx: Context<PlaceTwammOrder>,
+    _order_nonce: u64,
+    amount: u64,
+    intervals: u16,
+) -> Result<()> {
+    require!(!ctx.accounts.pool_state.load()?.is_locked(), ErrorCode::LOK);
+    let block_timestamp = u64::from(oracle::block_timestamp());
+    let mut order_pool = ctx.accounts.order_pool.load_mut()?;
+    let zero_for_one = order_pool.is_zero_for_one();
+    {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap) {
+            return err!(ErrorCode::NotApproved);
+        }
+        require_keys_eq!(
+            ctx.accounts.input_vault.key(),
+            if zero_for_one {
+                pool_state.token_vault_0
+            } else {
+                pool_state.token_vault_1
+            },
+            ErrorCode::InvalidInputPoolVault
+        );
+    }
+    // the order sells from the last execution, so the order pool must be executed first
+    require_eq!(
+        order_pool.last_execution_time,
+        block_timestamp,
+        ErrorCode::TwammOrderPoolNotExecuted
+    );
+    let expiry_time = TwammOrderPool::get_expiry_time(block_timestamp, intervals)?;
+
+    let transfer_fee = get_transfer_fee(ctx.accounts.input_vault_mint.clone(), amount)?;
+    let sell_rate_x64 = (u128::from(amount.checked_sub(transfer_fee).unwrap())
+        << fixed_point_64::RESOLUTION)
+        / u128::from(expiry_time - block_timestamp);
+    require_gt!(sell_rate_x64, 0, ErrorCode::ZeroAmountSpecified);
+    order_pool.add_order(sell_rate_x64, expiry_time)?;
+
+    let order = &mut ctx.accounts.order;
+    order.bump = ctx.bumps.order;
+    order.order_pool = ctx.accounts.order_pool.key();
+    order.owner = ctx.accounts.owner.key();
+    order.sell_rate_x64 = sell_rate_x64;
+    order.earnings_factor_last_x64 = order_pool.earnings_factor_x64;
+    order.unsold_factor_last_x64 = order_pool.unsold_factor_x64;
+    order.expiry_time = expiry_time;
+    drop(order_pool);
+
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.owner,
+        &ctx.accounts.input_token_account.to_account_info(),
+        &ctx.accounts.input_vault.to_account_info(),
+        Some(ctx.accounts.input_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount,
+    )?;
+
+    emit!(PlaceTwammOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        order_pool: ctx.accounts.order_pool.key(),
+        order: ctx.accounts.order.key(),
+        owner: ctx.accounts.owner.key(),
+        zero_for_one,
+        amount,
+        transfer_fee,
+        sell_rate_x64,
+        expiry_time,
+    });
+    Ok(())
+}
+
+pub fn withdraw_twamm_order(ctx: Context<WithdrawTwammOrder>) -> Result<()> {
+    require!(!ctx.accounts.pool_state.load()?.is_locked(), ErrorCode::LOK);
+    let order = &ctx.accounts.order;
+    let (zero_for_one, unsold_amount, earnings) = {
+        let mut order_pool = ctx.accounts.order_pool.load_mut()?;
+        // an order not executed up to its expiry yet is refunded from the last execution
+        let (earnings_factor_x64, unsold_factor_x64) =
+            order_pool.remove_order(order.sell_rate_x64, order.expiry_time);
+        (
+            order_pool.is_zero_for_one(),
+            order
+                .get_unsold_amount(order_pool.last_execution_time)
+                .checked_add(order.get_unsold_refund(unsold_factor_x64))
+                .unwrap(),
+            order.get_earnings(earnings_factor_x64),
+        )
+    };
+    let (amount_0, amount_1) = if zero_for_one {
+        (unsold_amount, earnings)
+    } else {
+        (earnings, unsold_amount)
+    };
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_0.to_account_info(),
+        &ctx.accounts.recipient_token_account_0.to_account_info(),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_1.to_account_info(),
+        &ctx.accounts.recipient_token_account_1.to_account_info(),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+
+    emit!(WithdrawTwammOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        order_pool: ctx.accounts.order_pool.key(),
+        order: ctx.accounts.order.key(),
+        owner: ctx.accounts.owner.key(),
+        unsold_amount,
+        earnings,
+    });
+    Ok(())
+}
+
+/// Sells `amount_specified` of the orders through the pool without moving the price further from
+/// the TWAP than the price deviation limit of the pool, or `TWAMM_MAX_DEVIATION_TICKS` if it has none.
+/// Returns the amount sold and bought, and the tick the circuit breaker window started at if the
+/// swap tripped the circuit breaker. Nothing is sold until the observations cover the TWAP window
+pub fn swap_twamm_segment(
+    amm_config: &AmmConfig,
+    pool_state: &mut RefMut<PoolState>,
+    tick_array_states: &mut VecDeque<RefMut<TickArrayState>>,
+    observation_state: &mut RefMut<ObservationState>,
+    tickarray_bitmap_extension: &Option<TickArrayBitmapExtension>,
+    amount_specified: u64,
+    zero_for_one: bool,
+    block_timestamp: u32,
+) -> Result<(u64, u64, Option<i32>)> {
+    let max_deviation_ticks = match pool_state.get_max_deviation_ticks(None) {
+        0 => TWAMM_MAX_DEVIATION_TICKS,
+        max_deviation_ticks => max_deviation_ticks,
+    };
+    let twap_tick = match observation_state.get_twap_tick(pool_state.get_twap_window_seconds()) {
+        Some(twap_tick) => twap_tick,
+        None => return Ok((0, 0, None)),
+    };
+    // one tick inside the limit, as a swap stopping at an initialized tick ends on the tick below it
+    let max_deviation_ticks =
+        i32::try_from(max_deviation_ticks.saturating_sub(1)).unwrap_or(i32::MAX);
+    let sqrt_price_limit_x64 = if zero_for_one {
+        tick_math::get_sqrt_price_at_tick(
+            twap_tick
+                .saturating_sub(max_deviation_ticks)
+                .max(tick_math::MIN_TICK + 1),
+        )?
+    } else {
+        tick_math::get_sqrt_price_at_tick(
+            twap_tick
+                .saturating_add(max_deviation_ticks)
+                .min(tick_math::MAX_TICK - 1),
+        )?
+    };
+    // the price is already past the limit
+    if (zero_for_one && sqrt_price_limit_x64 >= pool_state.sqrt_price_x64)
+        || (!zero_for_one && sqrt_price_limit_x64 <= pool_state.sqrt_price_x64)
+    {
+        return Ok((0, 0, None));
+    }
+
+    let (amount_0, amount_1, state) = swap_internal_with_state(
+        amm_config,
+        pool_state,
+        tick_array_states,
+        observation_state,
+        tickarray_bitmap_extension,
+        amount_specified,
+        sqrt_price_limit_x64,
+        zero_for_one,
+        true,
+        0,
+        None,
+        true,
+        block_timestamp,
+    )?;
+    Ok(if zero_for_one {
+        (amount_0, amount_1, state.circuit_breaker_tick_start)
+    } else {
+        (amount_1, amount_0, state.circuit_breaker_tick_start)
+    })
+}
+
+pub fn execute_twamm<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ExecuteTwamm<'info>>,
+) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let mut order_pool = ctx.accounts.order_pool.load_mut()?;
+    let zero_for_one = order_pool.is_zero_for_one();
+
+    let mut tickarray_bitmap_extension = None;
+    let mut tick_array_accounts = Vec::new();
+    let amm_config = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        // the swap hooks can be set after the order pool is created
+        require!(
+            !pool_state.has_hook_permission(HookPermissionBitIndex::BeforeSwap)
+                && !pool_state.has_hook_permission(HookPermissionBitIndex::AfterSwap),
+            ErrorCode::HookNotSupported
+        );
+        require_keys_eq!(
+            ctx.accounts.input_vault.key(),
+            if zero_for_one {
+                pool_state.token_vault_0
+            } else {
+                pool_state.token_vault_1
+            },
+            ErrorCode::InvalidInputPoolVault
+        );
+
+        let mut pool_fee_override = None;
+        let pool_fee_override_key = if pool_state.is_fee_overridden() {
+            Some(PoolFeeOverride::key(pool_state.key()))
+        } else {
+            None
+        };
+        let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
+        for account_info in ctx.remaining_accounts.iter() {
+            if pool_fee_override_key == Some(account_info.key()) {
+                pool_fee_override =
+                    Some(Account::<PoolFeeOverride>::try_from(account_info)?.into_inner());
+                continue;
+            }
+            if account_info.key().eq(&tick_array_bitmap_extension_key) {
+                tickarray_bitmap_extension = Some(
+                    *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                        .load()?
+                        .deref()),
+                );
+                continue;
+            }
+            tick_array_accounts.push(account_info);
+        }
+        get_effective_amm_config(&ctx.accounts.amm_config, &pool_state, pool_fee_override)?
+    };
+
+    let mut amount_in_total: u64 = 0;
+    let mut amount_out_total: u64 = 0;
+    let mut keeper_fee_total: u64 = 0;
+    // the orders are executed segment by segment, as the sell rate changes at every expiry
+    while order_pool.last_execution_time < u64::from(block_timestamp) {
+        let segment_end = order_pool.next_segment_end(u64::from(block_timestamp));
+        let amount_to_sell = order_pool.accrue_sell_amount(segment_end);
+        let keeper_fee = u64::try_from(
+            u128::from(amount_to_sell) * u128::from(TWAMM_KEEPER_FEE_RATE)
+                / u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+        .unwrap();
+        let amount_specified = amount_to_sell - keeper_fee;
+
+        let mut amount_in = 0;
+        let mut amount_out = 0;
+        if amount_specified > 0 {
+            let mut tick_array_states = VecDeque::new();
+            for account_info in tick_array_accounts.iter() {
+                tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+            }
+            // the sold token is already in the input vault and the bought token stays
+            // in the output vault until the orders are withdrawn
+            let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+            let circuit_breaker_tick_start;
+            (amount_in, amount_out, circuit_breaker_tick_start) = swap_twamm_segment(
+                &amm_config,
+                pool_state,
+                &mut tick_array_states,
+                &mut ctx.accounts.observation_state.load_mut()?,
+                &tickarray_bitmap_extension,
+                amount_specified,
+                zero_for_one,
+                block_timestamp,
+            )?;
+            emit_circuit_breaker_tripped(pool_state, circuit_breaker_tick_start);
+        }
+        // the keeper is only paid for the part sold
+        let keeper_fee = if amount_in < amount_specified {
+            u64::try_from(
+                u128::from(keeper_fee) * u128::from(amount_in) / u128::from(amount_specified),
+            )
+            .unwrap()
+        } else {
+            keeper_fee
+        };
+        // the amount the pool couldn't take is refunded to the orders of the segment
+        order_pool.settle_segment(segment_end, amount_in + keeper_fee, amount_out)?;
+
+        amount_in_total = amount_in_total.checked_add(amount_in).unwrap();
+        amount_out_total = amount_out_total.checked_add(amount_out).unwrap();
+        keeper_fee_total = keeper_fee_total.checked_add(keeper_fee).unwrap();
+    }
+    drop(order_pool);
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.input_vault.to_account_info(),
+        &ctx.accounts.keeper_token_account.to_account_info(),
+        Some(ctx.accounts.input_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        keeper_fee_total,
+    )?;
+
+    emit!(ExecuteTwammEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        order_pool: ctx.accounts.order_pool.key(),
+        keeper: ctx.accounts.keeper.key(),
+        zero_for_one,
+        amount_in: amount_in_total,
+        amount_out: amount_out_total,
+        keeper_fee: keeper_fee_total,
+        execution_time: u64::from(block_timestamp),
+    });
+    Ok(())
+}
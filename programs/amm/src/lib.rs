@@ -50,6 +50,8 @@ pub mod amm_v3 {
     /// * `index` - The index of amm config, there may be multiple config.
     /// * `tick_spacing` - The tickspacing binding with config, cannot be changed.
     /// * `trade_fee_flat` - Flat trade fee charged on each swap.
+    /// * `trade_fee_rate` - Proportional trade fee rate, denominated in hundredths of a bip.
+    /// * `trade_fee_mode` - How the proportional fee combines with the flat fee, 0: flat only, 1: max, 2: sum.
     /// * `protocol_fee_rate` - The rate of protocol fee within trade fee.
     /// * `fund_fee_rate` - The rate of fund fee within trade fee.
     ///
@@ -58,9 +60,12 @@ pub mod amm_v3 {
         index: u16,
         tick_spacing: u16,
         trade_fee_flat: u64,
+        trade_fee_rate: u32,
+        trade_fee_mode: u8,
         protocol_fee_rate: u32,
         fund_fee_rate: u32,
     ) -> Result<()> {
+        assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
         assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
         assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
         assert!(fund_fee_rate + protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
@@ -69,6 +74,8 @@ pub mod amm_v3 {
             index,
             tick_spacing,
             trade_fee_flat,
+            trade_fee_rate,
+            trade_fee_mode,
             protocol_fee_rate,
             fund_fee_rate,
         )
@@ -90,9 +97,25 @@ pub mod amm_v3 {
     /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
     /// * `new_owner`- The config's new owner, be set when `param` is 3
     /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The value can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
-    ///
-    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
+    /// * `trade_fee_rate`- The new proportional trade fee rate of amm config, be set when `param` is 5
+    /// * `trade_fee_mode`- The new trade fee mode of amm config, be set when `param` is 6
+    /// * `fee_accounting_mode`- Charge the flat fee per swap step(0) or once per swap(1), be set when `param` is 7
+    /// * `dynamic_fee_base`- The base rate of the volatility surcharge, be set when `param` is 8
+    /// * `dynamic_fee_max`- The max rate of the volatility surcharge, 0 disables it, be set when `param` is 9
+    /// * `dynamic_fee_sensitivity`- The surcharge rate per tick of volatility, be set when `param` is 10
+    /// * `referral_fee_bps_max`- The cap of the referral share of the trade fee in bps, be set when `param` is 11
+    /// * `creator_fee_rate`- The new pool creator fee rate of amm config, be set when `param` is 12
+    /// * `pool_creation_fee`- The lamports charged for creating a pool, at most u32::MAX, be set when `param` is 13
+    /// * `param`- The value can be 0 ~ 13, otherwise will report a error
+    ///
+    /// Note: When a fee rate is updated, the fee override accounts of the pools of the config
+    /// must be passed as the remaining accounts so that their effective fee rates are checked
+    ///
+    pub fn update_amm_config<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, UpdateAmmConfig<'info>>,
+        param: u8,
+        value: u64,
+    ) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
     }
 
@@ -102,13 +125,46 @@ pub mod amm_v3 {
     ///
     /// * `ctx`- The context of accounts
     /// * `sqrt_price_x64` - the initial sqrt price (amount_token_1 / amount_token_0) of the pool as a Q64.64
-    /// Note: The open_time must be smaller than the current block_timestamp on chain.
+    /// * `open_time` - Swaps are rejected until the block_timestamp is greater than it, at most `MAX_OPEN_TIME_DELAY` after now
+    /// * `launch_fee_flat` - The flat trade fee charged at open_time, must be greater than the config fee to take effect, at most `MAX_LAUNCH_FEE_FLAT`
+    /// * `launch_fee_duration` - The seconds after open_time for the launch fee to decay to the config fee, 0 disables the launch fee, at most `MAX_LAUNCH_FEE_DURATION`
+    /// * `launch_fee_decay_mode` - Decay the launch fee linearly(0) or exponentially(1)
+    /// Note: The `pool_creation_fee` of the amm config is charged in lamports and sent to the fee receiver.
     pub fn create_pool(
         ctx: Context<CreatePool>,
         sqrt_price_x64: u128,
         open_time: u64,
+        launch_fee_flat: u64,
+        launch_fee_duration: u32,
+        launch_fee_decay_mode: u8,
     ) -> Result<()> {
-        instructions::create_pool(ctx, sqrt_price_x64, open_time)
+        instructions::create_pool(
+            ctx,
+            sqrt_price_x64,
+            open_time,
+            launch_fee_flat,
+            launch_fee_duration,
+            launch_fee_decay_mode,
+        )
+    }
+
+    /// Set the fee override of a pool, the fees which are not provided fallback to the amm config
+    /// Must be called by the amm config owner or admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `trade_fee_flat` - The overridden flat trade fee
+    /// * `protocol_fee_rate` - The overridden protocol fee rate
+    /// * `fund_fee_rate` - The overridden fund fee rate
+    ///
+    pub fn set_pool_fee_override(
+        ctx: Context<SetPoolFeeOverride>,
+        trade_fee_flat: Option<u64>,
+        protocol_fee_rate: Option<u32>,
+        fund_fee_rate: Option<u32>,
+    ) -> Result<()> {
+        instructions::set_pool_fee_override(ctx, trade_fee_flat, protocol_fee_rate, fund_fee_rate)
     }
 
     /// Update pool status for given value
@@ -122,6 +178,84 @@ pub mod amm_v3 {
         instructions::update_pool_status(ctx, status)
     }
 
+    /// Update the oracle price guard of the pool, which rejects the swaps moving the pool tick
+    /// too far from the TWAP tick
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_deviation_ticks` - The max ticks the post-swap tick may deviate from the TWAP tick unless the swap
+    /// moves it closer, 0 to disable the guard
+    /// * `twap_window_seconds` - The seconds of the TWAP window, 0 to use the default window
+    ///
+    pub fn update_pool_price_guard(
+        ctx: Context<UpdatePoolPriceGuard>,
+        max_deviation_ticks: u32,
+        twap_window_seconds: u32,
+    ) -> Result<()> {
+        instructions::update_pool_price_guard(ctx, max_deviation_ticks, twap_window_seconds)
+    }
+
+    /// Update the flash loan fee of the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `flash_loan_fee_flat` - The flat fee charged for each token borrowed
+    /// * `flash_loan_fee_rate` - The proportional fee, denominated in hundredths of a bip (10^-6)
+    /// * `flash_loan_fee_mode` - How the proportional fee combines with the flat fee, 0: flat only, 1: max, 2: sum.
+    ///
+    pub fn update_pool_flash_loan_fee(
+        ctx: Context<UpdatePoolFlashLoanFee>,
+        flash_loan_fee_flat: u64,
+        flash_loan_fee_rate: u32,
+        flash_loan_fee_mode: u8,
+    ) -> Result<()> {
+        instructions::update_pool_flash_loan_fee(
+            ctx,
+            flash_loan_fee_flat,
+            flash_loan_fee_rate,
+            flash_loan_fee_mode,
+        )
+    }
+
+    /// Update the circuit breaker of the pool, a tripped breaker is reset
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_ticks` - The max ticks the price may move within the window, 0 disables the breaker
+    /// * `window_seconds` - The seconds of the window
+    /// * `cooldown_seconds` - The seconds the swaps in the direction of the move are paused once the breaker trips
+    ///
+    pub fn update_pool_circuit_breaker(
+        ctx: Context<UpdatePoolCircuitBreaker>,
+        max_ticks: u32,
+        window_seconds: u32,
+        cooldown_seconds: u32,
+    ) -> Result<()> {
+        instructions::update_pool_circuit_breaker(ctx, max_ticks, window_seconds, cooldown_seconds)
+    }
+
+    /// Update the hook program of the pool, must be called by the admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `hook_program` - The program called by the pool as allowed by `hook_permissions`
+    /// * `hook_permissions` - Call the hook before swap(bit0), after swap(bit1), before liquidity changes(bit2)
+    /// and after liquidity changes(bit3), 0 disables the hook
+    /// Note: The hook program and the accounts passed to it must trail the remaining accounts of the swap or
+    /// liquidity instructions of the pool the hook is called for, a failed hook call aborts the instruction.
+    ///
+    pub fn update_pool_hook(
+        ctx: Context<UpdatePoolHook>,
+        hook_program: Pubkey,
+        hook_permissions: u8,
+    ) -> Result<()> {
+        instructions::update_pool_hook(ctx, hook_program, hook_permissions)
+    }
+
     /// Creates an operation account for the program
     ///
     /// # Arguments
@@ -267,6 +401,22 @@ pub mod amm_v3 {
         instructions::collect_fund_fee(ctx, amount_0_requested, amount_1_requested)
     }
 
+    /// Collect the creator fee accrued to the pool, must be called by the pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_creator_fee(
+        ctx: Context<CollectCreatorFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_creator_fee(ctx, amount_0_requested, amount_1_requested)
+    }
+
     /// #[deprecated(note = "Use `open_position_with_token22_nft` instead.")]
     /// Creates a new position wrapped in a NFT
     ///
@@ -443,6 +593,32 @@ pub mod amm_v3 {
         instructions::increase_liquidity_v2(ctx, liquidity, amount_0_max, amount_1_max, base_flag)
     }
 
+    /// Same as `increase_liquidity_v2`, but rejects the transaction if it lands after the deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `liquidity` - The desired liquidity to be added, if zero, calculate liquidity base amount_0 or amount_1 according base_flag
+    /// * `amount_0_max` - The max amount of token_0 to spend, which serves as a slippage check
+    /// * `amount_1_max` - The max amount of token_1 to spend, which serves as a slippage check
+    /// * `base_flag` - must be specified if liquidity is zero, true: calculate liquidity base amount_0_max otherwise base amount_1_max
+    /// * `deadline` - The unix timestamp after which the transaction fails, none means no deadline
+    ///
+    pub fn increase_liquidity_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, IncreaseLiquidityV2<'info>>,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64,
+        base_flag: Option<bool>,
+        deadline: Option<u64>,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        if liquidity == 0 {
+            assert!(base_flag.is_some());
+        }
+        instructions::increase_liquidity_v2(ctx, liquidity, amount_0_max, amount_1_max, base_flag)
+    }
+
     /// #[deprecated(note = "Use `decrease_liquidity_v2` instead.")]
     /// Decreases liquidity for an existing position
     ///
@@ -480,6 +656,200 @@ pub mod amm_v3 {
         instructions::decrease_liquidity_v2(ctx, liquidity, amount_0_min, amount_1_min)
     }
 
+    /// Same as `decrease_liquidity_v2`, but rejects the transaction if it lands after the deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `liquidity` - The amount by which liquidity will be decreased
+    /// * `amount_0_min` - The minimum amount of token_0 that should be accounted for the burned liquidity
+    /// * `amount_1_min` - The minimum amount of token_1 that should be accounted for the burned liquidity
+    /// * `deadline` - The unix timestamp after which the transaction fails, none means no deadline
+    ///
+    pub fn decrease_liquidity_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DecreaseLiquidityV2<'info>>,
+        liquidity: u128,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        deadline: Option<u64>,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        instructions::decrease_liquidity_v2(ctx, liquidity, amount_0_min, amount_1_min)
+    }
+
+    /// Collects the fees owed to a position without changing its liquidity, support Token2022
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to collect
+    /// * `amount_1_requested` - The maximum amount of token_1 to collect
+    ///
+    pub fn collect_fees(
+        ctx: Context<CollectFees>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_fees(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Collects the rewards owed to a position without changing its liquidity, support Token2022
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `reward_indexes` - The indexes of the rewards to collect, each takes the reward vault,
+    /// the recipient token account and the reward mint from the remaining accounts in order
+    ///
+    pub fn collect_rewards<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CollectRewards<'info>>,
+        reward_indexes: Vec<u8>,
+    ) -> Result<()> {
+        instructions::collect_position_rewards(ctx, reward_indexes)
+    }
+
+    /// Adds the fees owed to a position back to its liquidity without the tokens leaving the vaults,
+    /// the part that doesn't fit the price ratio of the range stays owed.
+    /// Can be called by the position owner or the delegate of the position nft account
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    pub fn compound_position<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CompoundPosition<'info>>,
+    ) -> Result<()> {
+        instructions::compound_position(ctx)
+    }
+
+    /// Moves all the liquidity of a position to a new range on the same position nft in one instruction,
+    /// the fees and rewards owed to the position are kept
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `tick_lower_index` - The low boundary of the new range
+    /// * `tick_upper_index` - The upper boundary of the new range
+    /// * `tick_array_lower_start_index` - The start index of tick array which include tick low
+    /// * `tick_array_upper_start_index` - The start index of tick array which include tick upper
+    /// * `liquidity_min` - The minimum liquidity of the position in the new range
+    /// * `swap_surplus` - Whether to swap the surplus of the withdrawn tokens over the token ratio of the new range through the pool before the deposit
+    /// * `sqrt_price_limit_x64` - The Q64.64 sqrt price √P limit of the swap, 0 means no limit
+    ///
+    pub fn rebalance_position<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, RebalancePosition<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        liquidity_min: u128,
+        swap_surplus: bool,
+        sqrt_price_limit_x64: u128,
+    ) -> Result<()> {
+        instructions::rebalance_position(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            liquidity_min,
+            swap_surplus,
+            sqrt_price_limit_x64,
+        )
+    }
+
+    /// Increases liquidity of a position with a single token, the part of the deposit that
+    /// balances the range is swapped through the pool and the dust is sent back
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `amount_in` - The amount of the deposited token
+    /// * `zero_for_one` - Whether the deposited token is token_0
+    /// * `liquidity_min` - The minimum liquidity added to the position
+    ///
+    pub fn increase_liquidity_zap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, IncreaseLiquidityZap<'info>>,
+        amount_in: u64,
+        zero_for_one: bool,
+        liquidity_min: u128,
+    ) -> Result<()> {
+        instructions::increase_liquidity_zap(ctx, amount_in, zero_for_one, liquidity_min)
+    }
+
+    /// Opens a position wrapped in a Token2022 NFT with a single token, the part of the deposit that
+    /// balances the range is swapped through the pool and the dust is sent back
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `tick_lower_index` - The low boundary of market
+    /// * `tick_upper_index` - The upper boundary of market
+    /// * `tick_array_lower_start_index` - The start index of tick array which include tick low
+    /// * `tick_array_upper_start_index` - The start index of tick array which include tick upper
+    /// * `amount_in` - The amount of the deposited token
+    /// * `zero_for_one` - Whether the deposited token is token_0
+    /// * `liquidity_min` - The minimum liquidity of the position
+    /// * `with_metadata` - The flag indicating whether to create NFT mint metadata
+    ///
+    pub fn open_position_zap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, OpenPositionZap<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        amount_in: u64,
+        zero_for_one: bool,
+        liquidity_min: u128,
+        with_metadata: bool,
+    ) -> Result<()> {
+        instructions::open_position_zap(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            amount_in,
+            zero_for_one,
+            liquidity_min,
+            with_metadata,
+        )
+    }
+
+    /// Decreases liquidity of a position and collects its fees as a single token,
+    /// the other token is swapped through the pool. The token is selected by the mint of the recipient
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `liquidity` - The amount by which liquidity will be decreased
+    /// * `amount_out_min` - The minimum amount of the token received, after the transfer fee
+    ///
+    pub fn decrease_liquidity_zap_out<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DecreaseLiquidityZapOut<'info>>,
+        liquidity: u128,
+        amount_out_min: u64,
+    ) -> Result<()> {
+        instructions::decrease_liquidity_zap_out(ctx, liquidity, amount_out_min)
+    }
+
+    /// Locks the liquidity of a position until a timestamp, the fees and rewards can still be collected.
+    /// While the position is locked the owner can only extend the lock, the lock authority can shorten or release it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `unlock_timestamp` - The liquidity can't be removed before this timestamp
+    /// * `lock_authority` - The account allowed to release the lock early, no one if none
+    ///
+    pub fn lock_position(
+        ctx: Context<LockPosition>,
+        unlock_timestamp: u64,
+        lock_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::lock_position(ctx, unlock_timestamp, lock_authority)
+    }
+
     /// #[deprecated(note = "Use `swap_v2` instead.")]
     /// Swaps one token for as much as possible of another token across a single pool
     ///
@@ -516,6 +886,12 @@ pub mod amm_v3 {
     /// * `other_amount_threshold` - For slippage check
     /// * `sqrt_price_limit` - The Q64.64 sqrt price √P limit. If zero for one, the price cannot
     /// * `is_base_input` - swap base input or swap base output
+    /// * `referral_fee_bps` - The share of the trade fee paid to the referral token account, in basis points
+    /// * `max_deviation_ticks` - The max ticks the post-swap tick may deviate from the oracle TWAP tick,
+    /// can only tighten the pool limit, none or zero to use the pool limit
+    /// * `allow_partial_fill` - Stop at the price limit or where the liquidity runs out instead of failing,
+    /// the threshold is scaled to the filled part of the amount
+    /// Note: The amounts paid and received by the user are set as `SwapReturnData` return data.
     ///
     pub fn swap_v2<'a, 'b, 'c: 'info, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, SwapSingleV2<'info>>,
@@ -523,6 +899,9 @@ pub mod amm_v3 {
         other_amount_threshold: u64,
         sqrt_price_limit_x64: u128,
         is_base_input: bool,
+        referral_fee_bps: u16,
+        max_deviation_ticks: Option<u32>,
+        allow_partial_fill: bool,
     ) -> Result<()> {
         instructions::swap_v2(
             ctx,
@@ -530,9 +909,225 @@ pub mod amm_v3 {
             other_amount_threshold,
             sqrt_price_limit_x64,
             is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+        )
+    }
+
+    /// Same as `swap_v2`, but rejects the transaction if it lands after the deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount` - Arranged in pairs with other_amount_threshold. (amount_in, amount_out_minimum) or (amount_out, amount_in_maximum)
+    /// * `other_amount_threshold` - For slippage check
+    /// * `sqrt_price_limit` - The Q64.64 sqrt price √P limit. If zero for one, the price cannot
+    /// * `is_base_input` - swap base input or swap base output
+    /// * `referral_fee_bps` - The share of the trade fee paid to the referral token account, in basis points
+    /// * `max_deviation_ticks` - The max ticks the post-swap tick may deviate from the oracle TWAP tick,
+    /// can only tighten the pool limit, none or zero to use the pool limit
+    /// * `allow_partial_fill` - Stop at the price limit or where the liquidity runs out instead of failing,
+    /// the threshold is scaled to the filled part of the amount
+    /// * `deadline` - The unix timestamp after which the transaction fails, none means no deadline
+    ///
+    pub fn swap_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapSingleV2<'info>>,
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit_x64: u128,
+        is_base_input: bool,
+        referral_fee_bps: u16,
+        max_deviation_ticks: Option<u32>,
+        allow_partial_fill: bool,
+        deadline: Option<u64>,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        instructions::swap_v2(
+            ctx,
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64,
+            is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+        )
+    }
+
+    /// Flash swaps across a single pool, the output token is transferred to the receiver first, then the
+    /// callback program is called with `FlashSwapCallback` and must pay the input token into the input vault
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount` - Arranged in pairs with other_amount_threshold. (amount_in, amount_out_minimum) or (amount_out, amount_in_maximum)
+    /// * `other_amount_threshold` - For slippage check
+    /// * `sqrt_price_limit` - The Q64.64 sqrt price √P limit. If zero for one, the price cannot
+    /// * `is_base_input` - swap base input or swap base output
+    /// * `callback_account_count` - The number of the leading remaining accounts passed to the callback
+    /// * `callback_data` - The data passed through to the callback
+    /// Note: The amounts paid and received are set as `SwapReturnData` return data.
+    ///
+    pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit_x64: u128,
+        is_base_input: bool,
+        callback_account_count: u8,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_swap(
+            ctx,
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64,
+            is_base_input,
+            callback_account_count,
+            callback_data,
         )
     }
 
+    /// Lends the tokens of the pool vaults, the borrowed tokens are transferred to the receivers first, then the
+    /// callback program is called with `FlashLoanCallback` and must repay the loan and the fee into the vaults
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0` - The amount of token_0 to borrow
+    /// * `amount_1` - The amount of token_1 to borrow
+    /// * `callback_account_count` - The number of the leading remaining accounts passed to the callback
+    /// * `callback_data` - The data passed through to the callback
+    ///
+    pub fn flash_loan<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, FlashLoan<'info>>,
+        amount_0: u64,
+        amount_1: u64,
+        callback_account_count: u8,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_loan(
+            ctx,
+            amount_0,
+            amount_1,
+            callback_account_count,
+            callback_data,
+        )
+    }
+
+    /// Places a limit order selling one token of the pool on the range of a single tick spacing,
+    /// the order is filled once a swap moves the price across the whole range
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `order_nonce` - The nonce of the order account, unique per pool and owner
+    /// * `tick_array_start_index` - The start index of the tick array holding both ticks of the range
+    /// * `tick_lower_index` - The lower tick of the range, the upper tick is one tick spacing above
+    /// * `zero_for_one` - true to sell token_0 above the price, false to sell token_1 below the price
+    /// * `amount` - The amount of the sold token to deposit, including the transfer fee
+    ///
+    pub fn place_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, PlaceLimitOrder<'info>>,
+        order_nonce: u64,
+        tick_array_start_index: i32,
+        tick_lower_index: i32,
+        zero_for_one: bool,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::place_limit_order(
+            ctx,
+            order_nonce,
+            tick_array_start_index,
+            tick_lower_index,
+            zero_for_one,
+            amount,
+        )
+    }
+
+    /// Cancels an unfilled limit order, returning the tokens it holds at the current price
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn cancel_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CancelLimitOrder<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_limit_order(ctx)
+    }
+
+    /// Claims the bought token of a filled limit order
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn claim_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ClaimLimitOrder<'info>>,
+    ) -> Result<()> {
+        instructions::claim_limit_order(ctx)
+    }
+
+    /// Creates the TWAMM order pool of a pool selling token_0 or token_1
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `zero_for_one` - true for the orders selling token_0, false for the orders selling token_1
+    ///
+    pub fn create_twamm_order_pool(
+        ctx: Context<CreateTwammOrderPool>,
+        zero_for_one: bool,
+    ) -> Result<()> {
+        instructions::create_twamm_order_pool(ctx, zero_for_one)
+    }
+
+    /// Places a long term order selling the amount evenly until the expiry, the order pool
+    /// must be executed up to the current time first, usually in the same transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `order_nonce` - The nonce of the order account, unique per order pool and owner
+    /// * `amount` - The amount of the sold token to deposit, including the transfer fee
+    /// * `intervals` - The number of intervals after the current one the order expires at
+    ///
+    pub fn place_twamm_order(
+        ctx: Context<PlaceTwammOrder>,
+        order_nonce: u64,
+        amount: u64,
+        intervals: u16,
+    ) -> Result<()> {
+        instructions::place_twamm_order(ctx, order_nonce, amount, intervals)
+    }
+
+    /// Withdraws a TWAMM order, paying out the bought token and refunding the unsold token
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn withdraw_twamm_order(ctx: Context<WithdrawTwammOrder>) -> Result<()> {
+        instructions::withdraw_twamm_order(ctx)
+    }
+
+    /// Executes the orders of a TWAMM order pool against the pool up to the current time,
+    /// the keeper earns a fee from the sold amount. The price isn't moved further from the TWAP
+    /// than the price deviation limit of the pool, or `TWAMM_MAX_DEVIATION_TICKS` if it has none,
+    /// the part left unsold is sold by a later execution
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn execute_twamm<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ExecuteTwamm<'info>>,
+    ) -> Result<()> {
+        instructions::execute_twamm(ctx)
+    }
+
     /// Swap token for as much as possible of another token across the path provided, base input
     ///
     /// # Arguments
@@ -540,12 +1135,119 @@ pub mod amm_v3 {
     /// * `ctx` - The context of accounts
     /// * `amount_in` - Token amount to be swapped in
     /// * `amount_out_minimum` - Panic if output amount is below minimum amount. For slippage.
+    /// * `referral_fee_bps` - The share of the first hop trade fee paid to the referral token account, in basis points
+    /// Note: The amounts paid and received by the user are set as `SwapReturnData` return data.
     ///
     pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
         amount_in: u64,
         amount_out_minimum: u64,
+        referral_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum, referral_fee_bps)
+    }
+
+    /// Same as `swap_router_base_in`, but rejects the transaction if it lands after the deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_in` - Token amount to be swapped in
+    /// * `amount_out_minimum` - Panic if output amount is below minimum amount. For slippage.
+    /// * `referral_fee_bps` - The share of the first hop trade fee paid to the referral token account, in basis points
+    /// * `deadline` - The unix timestamp after which the transaction fails, none means no deadline
+    ///
+    pub fn swap_router_base_in_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
+        amount_in: u64,
+        amount_out_minimum: u64,
+        referral_fee_bps: u16,
+        deadline: Option<u64>,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum, referral_fee_bps)
+    }
+
+    /// Swap token for as much as possible of another token across the hops described, base input
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_in` - Token amount to be swapped in
+    /// * `amount_out_minimum` - Panic if output amount is below minimum amount. For slippage.
+    /// * `referral_fee_bps` - The share of the first hop trade fee paid to the referral token account, in basis points
+    /// * `hops` - The route in swap order, each hop locates its accounts in the remaining accounts
+    /// and can bound its own price impact with a price limit and a minimum output
+    /// Note: The amounts paid and received by the user are set as `SwapReturnData` return data.
+    ///
+    pub fn swap_router_base_in_v2<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
+        amount_in: u64,
+        amount_out_minimum: u64,
+        referral_fee_bps: u16,
+        hops: Vec<RouteHop>,
+    ) -> Result<()> {
+        instructions::swap_router_base_in_v2(
+            ctx,
+            amount_in,
+            amount_out_minimum,
+            referral_fee_bps,
+            hops,
+        )
+    }
+
+    /// Quotes a swap across a single pool without changing any account, the pool and tick arrays are read only
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount` - The amount_in if is_base_input, otherwise the amount_out
+    /// * `sqrt_price_limit_x64` - The Q64.64 sqrt price √P limit, zero means no limit
+    /// * `zero_for_one` - Swap token_0 for token_1 or token_1 for token_0
+    /// * `is_base_input` - swap base input or swap base output
+    /// * `referral_fee_bps` - The share of the trade fee paid to the referrer, in basis points
+    /// * `max_deviation_ticks` - Tightens the oracle price guard of the pool, none or zero to use the pool limit
+    /// * `allow_partial_fill` - Stop where the liquidity runs out instead of failing
+    /// Note: The result is set as `SwapQuote` return data.
+    ///
+    pub fn quote_swap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, QuoteSwap<'info>>,
+        amount: u64,
+        sqrt_price_limit_x64: u128,
+        zero_for_one: bool,
+        is_base_input: bool,
+        referral_fee_bps: u16,
+        max_deviation_ticks: Option<u32>,
+        allow_partial_fill: bool,
+    ) -> Result<()> {
+        instructions::quote_swap(
+            ctx,
+            amount,
+            sqrt_price_limit_x64,
+            zero_for_one,
+            is_base_input,
+            referral_fee_bps,
+            max_deviation_ticks,
+            allow_partial_fill,
+        )
+    }
+
+    /// Swap as little as possible of one token for an exact amount of another token across the path provided, base output
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_out` - Token amount to be received from the last hop
+    /// * `amount_in_maximum` - Panic if input amount is above maximum amount. For slippage.
+    /// * `referral_fee_bps` - The share of the first hop trade fee paid to the referral token account, in basis points
+    /// Note: The amounts paid and received by the user are set as `SwapReturnData` return data.
+    ///
+    pub fn swap_router_base_out<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseOut<'info>>,
+        amount_out: u64,
+        amount_in_maximum: u64,
+        referral_fee_bps: u16,
     ) -> Result<()> {
-        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum)
+        instructions::swap_router_base_out(ctx, amount_out, amount_in_maximum, referral_fee_bps)
     }
 }
@@ -5,6 +5,7 @@ use super::full_math::MulDiv;
 use super::tick_math;
 use super::unsafe_math::UnsafeMathTrait;
 use crate::error::ErrorCode;
+use crate::states::config::FEE_RATE_DENOMINATOR_VALUE;
 use anchor_lang::prelude::*;
 
 /// Add a signed liquidity delta to liquidity and revert if it overflows or underflows
@@ -314,3 +315,126 @@ pub fn get_delta_amounts_signed(
     }
     Ok((amount_0, amount_1))
 }
+
+/// Returns the values V_0 and V_1 of the token_0 and token_1 reserves of the range per liquidity
+/// at the current price, see `get_zap_swap_amount`
+fn get_range_values(
+    sqrt_price_x64: u128,
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+) -> (U256, U256) {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+    let sqrt_price_x64 = sqrt_price_x64.clamp(sqrt_ratio_a_x64, sqrt_ratio_b_x64);
+
+    let value_0 = U256::from(sqrt_ratio_b_x64 - sqrt_price_x64)
+        .mul_div_floor(U256::from(sqrt_price_x64), U256::from(sqrt_ratio_b_x64))
+        .unwrap();
+    let value_1 = U256::from(sqrt_price_x64 - sqrt_ratio_a_x64);
+    (value_0, value_1)
+}
+
+/// Computes the part of a single token deposit to swap so that the two halves match the token ratio
+/// of the range at the current price, the price impact of the swap is not taken into account.
+/// Solves (amount_in - Δin) / (Δin (1 - fee)) = V_in / V_out, where V_0 = (√P_upper - √P) √P / √P_upper
+/// and V_1 = √P - √P_lower are the values of the token_0 and token_1 reserves of the range per liquidity
+///
+/// # Arguments
+///
+/// * `sqrt_price_x64` - The current sqrt price of the pool
+/// * `sqrt_ratio_a_x64` - The sqrt price at one boundary of the range
+/// * `sqrt_ratio_b_x64` - The sqrt price at the other boundary of the range
+/// * `amount_in` - The amount of the deposited token
+/// * `zero_for_one` - Whether the deposited token is token_0
+/// * `trade_fee_rate` - The trade fee rate of the pool, denominated in hundredths of a bip
+///
+pub fn get_zap_swap_amount(
+    sqrt_price_x64: u128,
+    sqrt_ratio_a_x64: u128,
+    sqrt_ratio_b_x64: u128,
+    amount_in: u64,
+    zero_for_one: bool,
+    trade_fee_rate: u32,
+) -> u64 {
+    let (value_0, value_1) = get_range_values(sqrt_price_x64, sqrt_ratio_a_x64, sqrt_ratio_b_x64);
+    let (value_in, value_out) = if zero_for_one {
+        (value_0, value_1)
+    } else {
+        (value_1, value_0)
+    };
+    let denominator = U256::from(FEE_RATE_DENOMINATOR_VALUE);
+    let denominator_after_fee = U256::from(FEE_RATE_DENOMINATOR_VALUE - trade_fee_rate);
+    U256::from(amount_in)
+        .mul_div_floor(
+            value_out * denominator,
+            value_out * denominator + value_in * denominator_after_fee,
+        )
+        .unwrap()
+        .as_u64()
+}
+
+/// Computes the swap moving the surplus of `amount_0` and `amount_1` over the token ratio of the range
+/// at the current price into the other token, the price impact of the swap is not taken into account.
+/// The part of the tokens matching the ratio is kept and the surplus is split by `get_zap_swap_amount`.
+/// Returns the amount to swap and whether it is token_0, the amount is 0 if there is no surplus
+///
+/// # Arguments
+///
+/// * `sqrt_price_x64` - The current sqrt price of the pool
+/// * `sqrt_ratio_a_x64` - The sqrt price at one boundary of the range
+/// * `sqrt_ratio_b_x64` - The sqrt price at the other boundary of the range
+/// * `amount_0` - The amount of token_0
+/// * `amount_1` - The amount of token_1
+/// * `trade_fee_rate` - The trade fee rate of the pool, denominated in hundredths of a bip
+///
+pub fn get_rebalance_swap_amount(
+    sqrt_price_x64: u128,
+    sqrt_ratio_a_x64: u128,
+    sqrt_ratio_b_x64: u128,
+    amount_0: u64,
+    amount_1: u64,
+    trade_fee_rate: u32,
+) -> (u64, bool) {
+    let (value_0, value_1) = get_range_values(sqrt_price_x64, sqrt_ratio_a_x64, sqrt_ratio_b_x64);
+    // V_0 and V_1 are valued in token_1, so is amount_0 at the current price
+    let amount_0_value = U256::from(amount_0)
+        .mul_div_floor(U256::from(sqrt_price_x64), U256::from(fixed_point_64::Q64))
+        .unwrap()
+        .mul_div_floor(U256::from(sqrt_price_x64), U256::from(fixed_point_64::Q64))
+        .unwrap();
+    // a side is in surplus if amount_0_value / amount_1 differs from V_0 / V_1
+    let liquidity_value_0 = amount_0_value * value_1;
+    let liquidity_value_1 = U256::from(amount_1) * value_0;
+    let (surplus, zero_for_one) = if liquidity_value_0 > liquidity_value_1 {
+        let amount_0_matched = U256::from(amount_1)
+            .mul_div_floor(value_0, value_1)
+            .unwrap()
+            .mul_div_floor(U256::from(fixed_point_64::Q64), U256::from(sqrt_price_x64))
+            .unwrap()
+            .mul_div_floor(U256::from(fixed_point_64::Q64), U256::from(sqrt_price_x64))
+            .unwrap()
+            .as_u64();
+        (amount_0 - amount_0_matched, true)
+    } else if liquidity_value_1 > liquidity_value_0 {
+        let amount_1_matched = amount_0_value
+            .mul_div_floor(value_1, value_0)
+            .unwrap()
+            .as_u64();
+        (amount_1 - amount_1_matched, false)
+    } else {
+        return (0, false);
+    };
+    (
+        get_zap_swap_amount(
+            sqrt_price_x64,
+            sqrt_ratio_a_x64,
+            sqrt_ratio_b_x64,
+            surplus,
+            zero_for_one,
+            trade_fee_rate,
+        ),
+        zero_for_one,
+    )
+}
@@ -2,7 +2,7 @@ use super::full_math::MulDiv;
 use super::liquidity_math;
 use super::sqrt_price_math;
 use crate::error::ErrorCode;
-use crate::states::config::FEE_RATE_DENOMINATOR_VALUE;
+use crate::states::config::{TradeFeeMode, FEE_RATE_DENOMINATOR_VALUE};
 use anchor_lang::prelude::*;
 /// Result of a swap step
 #[derive(Default, Debug)]
@@ -21,6 +21,8 @@ pub fn compute_swap_step(
     liquidity: u128,
     amount_remaining: u64,
     flat_fee: u64,
+    fee_rate: u32,
+    fee_mode: TradeFeeMode,
     is_base_input: bool,
     zero_for_one: bool,
     block_timestamp: u32,
@@ -30,7 +32,8 @@ pub fn compute_swap_step(
     if is_base_input {
         // round up amount_in
         // In exact input case, amount_remaining is positive
-        let amount_remaining_less_fee = amount_remaining.saturating_sub(flat_fee);
+        let amount_remaining_less_fee =
+            amount_remaining_less_fee(amount_remaining, flat_fee, fee_rate, fee_mode)?;
 
         let amount_in = calculate_amount_in_range(
             sqrt_price_current_x64,
@@ -135,12 +138,62 @@ pub fn compute_swap_step(
                 .checked_sub(swap_step.amount_in)
                 .unwrap()
         } else {
-            flat_fee
+            calculate_fee_amount(swap_step.amount_in, flat_fee, fee_rate, fee_mode)?
         };
 
     Ok(swap_step)
 }
 
+/// Deducts the trade fee from the input amount, the result is the max amount that can be swapped
+fn amount_remaining_less_fee(
+    amount_remaining: u64,
+    flat_fee: u64,
+    fee_rate: u32,
+    fee_mode: TradeFeeMode,
+) -> Result<u64> {
+    let less_flat_fee = amount_remaining.saturating_sub(flat_fee);
+    let rate_less_fee = |amount: u64| {
+        amount
+            .mul_div_floor(
+                (FEE_RATE_DENOMINATOR_VALUE - fee_rate).into(),
+                u64::from(FEE_RATE_DENOMINATOR_VALUE),
+            )
+            .ok_or(ErrorCode::CalculateOverflow)
+    };
+    let amount = match fee_mode {
+        TradeFeeMode::FlatOnly => less_flat_fee,
+        TradeFeeMode::Max => less_flat_fee.min(rate_less_fee(amount_remaining)?),
+        TradeFeeMode::Sum => rate_less_fee(less_flat_fee)?,
+    };
+    Ok(amount)
+}
+
+/// Calculates the trade fee charged for swapping `amount_in`,
+/// the proportional part is rounded up and based on the amount after fee
+pub fn calculate_fee_amount(
+    amount_in: u64,
+    flat_fee: u64,
+    fee_rate: u32,
+    fee_mode: TradeFeeMode,
+) -> Result<u64> {
+    let rate_fee = || {
+        amount_in
+            .mul_div_ceil(
+                fee_rate.into(),
+                (FEE_RATE_DENOMINATOR_VALUE - fee_rate).into(),
+            )
+            .ok_or(ErrorCode::CalculateOverflow)
+    };
+    let fee_amount = match fee_mode {
+        TradeFeeMode::FlatOnly => flat_fee,
+        TradeFeeMode::Max => flat_fee.max(rate_fee()?),
+        TradeFeeMode::Sum => flat_fee
+            .checked_add(rate_fee()?)
+            .ok_or(ErrorCode::CalculateOverflow)?,
+    };
+    Ok(fee_amount)
+}
+
 /// Pre calcumate amount_in or amount_out for the specified price range
 /// The amount maybe overflow of u64 due to the `sqrt_price_target_x64` maybe unreasonable.
 /// Therefore, this situation needs to be handled in `compute_swap_step` to recalculate the price that can be reached based on the amount.
@@ -291,6 +344,8 @@ mod swap_math_test {
             liquidity in 1..u32::MAX as u128,
             amount_remaining in 1..u64::MAX,
             flat_fee in 1..1_000_000u64,
+            fee_rate in 0..FEE_RATE_DENOMINATOR_VALUE / 2,
+            fee_mode in 0..3u8,
             is_base_input in proptest::bool::ANY,
         ) {
             prop_assume!(sqrt_price_current_x64 != sqrt_price_target_x64);
@@ -302,6 +357,8 @@ mod swap_math_test {
                 liquidity,
                 amount_remaining,
                 flat_fee,
+                fee_rate,
+                TradeFeeMode::from_u8(fee_mode).unwrap(),
                 is_base_input,
                 zero_for_one,
                 1,
@@ -329,4 +386,40 @@ mod swap_math_test {
             assert!(sqrt_price_next_x64 <= price_upper);
         }
     }
+
+    #[test]
+    fn calculate_fee_amount_by_mode_test() {
+        // 0.25% rate, amount_in is the amount after fee
+        let amount_in = 997_500;
+        assert_eq!(
+            calculate_fee_amount(amount_in, 1000, 2500, TradeFeeMode::FlatOnly).unwrap(),
+            1000
+        );
+        assert_eq!(
+            calculate_fee_amount(amount_in, 1000, 2500, TradeFeeMode::Max).unwrap(),
+            2500
+        );
+        assert_eq!(
+            calculate_fee_amount(amount_in, 1000, 2500, TradeFeeMode::Sum).unwrap(),
+            3500
+        );
+        // the flat fee dominates small swaps in max mode
+        assert_eq!(
+            calculate_fee_amount(1000, 1000, 2500, TradeFeeMode::Max).unwrap(),
+            1000
+        );
+
+        assert_eq!(
+            amount_remaining_less_fee(1_000_000, 1000, 2500, TradeFeeMode::FlatOnly).unwrap(),
+            999_000
+        );
+        assert_eq!(
+            amount_remaining_less_fee(1_000_000, 1000, 2500, TradeFeeMode::Max).unwrap(),
+            997_500
+        );
+        assert_eq!(
+            amount_remaining_less_fee(1_000_000, 1000, 2500, TradeFeeMode::Sum).unwrap(),
+            996_502
+        );
+    }
 }
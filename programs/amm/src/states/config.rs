@@ -1,4 +1,5 @@
 use crate::error::ErrorCode;
+use crate::states::ObservationState;
 use anchor_lang::prelude::*;
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
@@ -6,6 +7,50 @@ pub const AMM_CONFIG_SEED: &str = "amm_config";
 pub const FEE_RATE_DENOMINATOR_VALUE: u32 = 1_000_000;
 /// Default flat trade fee charged on each swap
 pub const TRADE_FLAT_FEE_DEFAULT: u64 = 100_000;
+/// The referral fee is denominated in basis points of the trade fee
+pub const REFERRAL_FEE_BPS_DENOMINATOR: u16 = 10_000;
+
+/// Decides how the proportional trade fee is combined with the flat trade fee
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeFeeMode {
+    /// Only `trade_fee_flat` is charged, `trade_fee_rate` is ignored
+    FlatOnly,
+    /// The larger one of `trade_fee_flat` and the proportional fee is charged
+    Max,
+    /// Both `trade_fee_flat` and the proportional fee are charged
+    Sum,
+}
+
+impl TradeFeeMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        match mode {
+            0 => Ok(TradeFeeMode::FlatOnly),
+            1 => Ok(TradeFeeMode::Max),
+            2 => Ok(TradeFeeMode::Sum),
+            _ => err!(ErrorCode::InvalidTradeFeeMode),
+        }
+    }
+}
+
+/// Decides how often the flat trade fee is charged during a swap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeAccountingMode {
+    /// `trade_fee_flat` is charged on every swap step, so crossing more ticks costs more
+    PerStep,
+    /// `trade_fee_flat` is charged once at swap entry, and the proportional fee is still charged on every step.
+    /// Note that `TradeFeeMode::Max` behaves as `TradeFeeMode::Sum` in this mode
+    PerSwap,
+}
+
+impl FeeAccountingMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        match mode {
+            0 => Ok(FeeAccountingMode::PerStep),
+            1 => Ok(FeeAccountingMode::PerSwap),
+            _ => err!(ErrorCode::InvalidFeeAccountingMode),
+        }
+    }
+}
 
 /// Holds the current owner of the factory
 #[account]
@@ -24,10 +69,25 @@ pub struct AmmConfig {
     pub tick_spacing: u16,
     /// The fund fee, denominated in hundredths of a bip (10^-6)
     pub fund_fee_rate: u32,
-    // padding space for upgrade
-    pub padding_u32: u32,
+    /// Proportional trade fee, denominated in hundredths of a bip (10^-6)
+    pub trade_fee_rate: u32,
     pub fund_owner: Pubkey,
-    pub padding: [u64; 3],
+    /// How `trade_fee_rate` is combined with `trade_fee_flat`, see `TradeFeeMode`
+    pub trade_fee_mode: u8,
+    /// Whether the flat trade fee is charged on each swap step or once per swap, see `FeeAccountingMode`
+    pub fee_accounting_mode: u8,
+    /// The cap of the referral share of the trade fee which a swap can ask for, in basis points
+    pub referral_fee_bps_max: u16,
+    /// The lamports charged by create_pool and sent to the fee receiver
+    pub pool_creation_fee: u32,
+    /// The dynamic fee rate charged even when the pool is calm, denominated in hundredths of a bip (10^-6)
+    pub dynamic_fee_base: u32,
+    /// The cap of the dynamic fee rate, the dynamic fee is disabled when it is 0
+    pub dynamic_fee_max: u32,
+    /// The dynamic fee rate added for each tick of recent volatility
+    pub dynamic_fee_sensitivity: u32,
+    /// The pool creator fee, denominated in hundredths of a bip (10^-6)
+    pub creator_fee_rate: u32,
 }
 
 impl Default for AmmConfig {
@@ -40,9 +100,16 @@ impl Default for AmmConfig {
             trade_fee_flat: TRADE_FLAT_FEE_DEFAULT,
             tick_spacing: 0,
             fund_fee_rate: 0,
-            padding_u32: 0,
+            trade_fee_rate: 0,
             fund_owner: Pubkey::default(),
-            padding: [0u64; 3],
+            trade_fee_mode: TradeFeeMode::FlatOnly as u8,
+            fee_accounting_mode: FeeAccountingMode::PerStep as u8,
+            referral_fee_bps_max: 0,
+            pool_creation_fee: 0,
+            dynamic_fee_base: 0,
+            dynamic_fee_max: 0,
+            dynamic_fee_sensitivity: 0,
+            creator_fee_rate: 0,
         }
     }
 }
@@ -61,6 +128,38 @@ impl AmmConfig {
         );
         Ok(())
     }
+
+    pub fn get_trade_fee_mode(&self) -> Result<TradeFeeMode> {
+        TradeFeeMode::from_u8(self.trade_fee_mode)
+    }
+
+    pub fn get_fee_accounting_mode(&self) -> Result<FeeAccountingMode> {
+        FeeAccountingMode::from_u8(self.fee_accounting_mode)
+    }
+
+    /// The volatility surcharge on top of `trade_fee_rate`,
+    /// min(dynamic_fee_max, dynamic_fee_base + volatility_ticks * dynamic_fee_sensitivity)
+    pub fn get_dynamic_fee_rate(&self, volatility_ticks: u32) -> u32 {
+        let surcharge = u64::from(self.dynamic_fee_sensitivity)
+            .saturating_mul(volatility_ticks.into())
+            .saturating_add(self.dynamic_fee_base.into());
+        surcharge.min(self.dynamic_fee_max.into()) as u32
+    }
+
+    /// The proportional trade fee rate used by a swap, include the volatility surcharge measured by the oracle
+    pub fn get_trade_fee_rate(
+        &self,
+        observation_state: &ObservationState,
+        tick_current: i32,
+        block_timestamp: u32,
+    ) -> u32 {
+        if self.dynamic_fee_max == 0 {
+            return self.trade_fee_rate;
+        }
+        let volatility_ticks =
+            observation_state.get_volatility_ticks(tick_current, block_timestamp);
+        self.trade_fee_rate + self.get_dynamic_fee_rate(volatility_ticks)
+    }
 }
 
 /// Emitted when create or update a config
@@ -74,4 +173,13 @@ pub struct ConfigChangeEvent {
     pub tick_spacing: u16,
     pub fund_fee_rate: u32,
     pub fund_owner: Pubkey,
+    pub trade_fee_rate: u32,
+    pub trade_fee_mode: u8,
+    pub fee_accounting_mode: u8,
+    pub dynamic_fee_base: u32,
+    pub dynamic_fee_max: u32,
+    pub dynamic_fee_sensitivity: u32,
+    pub referral_fee_bps_max: u16,
+    pub creator_fee_rate: u32,
+    pub pool_creation_fee: u32,
 }
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+pub const LIMIT_ORDER_SEED: &str = "limit_order";
+
+/// A limit order selling one token of the pool on a single tick spacing range,
+/// it's filled once a swap moves the price across the whole range
+///
+/// PDA of `[LIMIT_ORDER_SEED, pool, owner, order_nonce]`
+///
+#[account]
+#[derive(Default, Debug)]
+pub struct LimitOrderState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool the order was placed on
+    pub pool_id: Pubkey,
+    /// The owner of the order, the only one allowed to cancel or claim it
+    pub owner: Pubkey,
+    /// The lower tick of the order range
+    pub tick_lower_index: i32,
+    /// The upper tick of the order range, one tick spacing above the lower tick
+    pub tick_upper_index: i32,
+    /// true if the order sells token_0 for token_1, false if it sells token_1 for token_0
+    pub zero_for_one: bool,
+    /// The amount of liquidity owned by the order
+    pub liquidity: u128,
+    /// The fill count of the range when the order was placed, the order is filled once the count moves on
+    pub fill_count: u32,
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 8],
+}
+
+impl LimitOrderState {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 4 + 4 + 1 + 16 + 4 + 64;
+}
+
+/// Emitted when a limit order is placed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PlaceLimitOrderEvent {
+    /// The pool the order was placed on
+    pub pool_state: Pubkey,
+    /// The order account
+    pub limit_order: Pubkey,
+    /// The owner of the order
+    pub owner: Pubkey,
+    /// The lower tick of the order range
+    pub tick_lower_index: i32,
+    /// The upper tick of the order range
+    pub tick_upper_index: i32,
+    /// true if the order sells token_0, false if it sells token_1
+    pub zero_for_one: bool,
+    /// The amount of liquidity owned by the order
+    pub liquidity: u128,
+    /// The amount of the sold token deposited for the order
+    pub amount: u64,
+    /// The token transfer fee for amount
+    pub transfer_fee: u64,
+}
+
+/// Emitted when an unfilled limit order is cancelled
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CancelLimitOrderEvent {
+    /// The pool the order was placed on
+    pub pool_state: Pubkey,
+    /// The order account
+    pub limit_order: Pubkey,
+    /// The owner of the order
+    pub owner: Pubkey,
+    /// The amount of liquidity removed
+    pub liquidity: u128,
+    /// The amount of token_0 returned to the owner
+    pub amount_0: u64,
+    /// The amount of token_1 returned to the owner
+    pub amount_1: u64,
+}
+
+/// Emitted when the proceeds of a filled limit order are claimed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ClaimLimitOrderEvent {
+    /// The pool the order was placed on
+    pub pool_state: Pubkey,
+    /// The order account
+    pub limit_order: Pubkey,
+    /// The owner of the order
+    pub owner: Pubkey,
+    /// The amount of liquidity claimed
+    pub liquidity: u128,
+    /// true if the order sold token_0, so the proceeds are in token_1
+    pub zero_for_one: bool,
+    /// The amount of the bought token paid to the owner
+    pub amount: u64,
+}
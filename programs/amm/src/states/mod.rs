@@ -1,19 +1,25 @@
 pub mod config;
+pub mod limit_order;
 pub mod operation_account;
 pub mod oracle;
 pub mod personal_position;
 pub mod pool;
+pub mod pool_fee_override;
 pub mod protocol_position;
 pub mod support_mint_associated;
 pub mod tick_array;
 pub mod tickarray_bitmap_extension;
+pub mod twamm;
 
 pub use config::*;
+pub use limit_order::*;
 pub use operation_account::*;
 pub use oracle::*;
 pub use personal_position::*;
 pub use pool::*;
+pub use pool_fee_override::*;
 pub use protocol_position::*;
 pub use support_mint_associated::*;
 pub use tick_array::*;
 pub use tickarray_bitmap_extension::*;
+pub use twamm::*;
@@ -9,6 +9,12 @@ pub const OBSERVATION_SEED: &str = "observation";
 // Number of ObservationState element
 pub const OBSERVATION_NUM: usize = 100;
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u32 = 15;
+/// Max number of the latest observation intervals used to measure the volatility
+pub const VOLATILITY_OBSERVATION_WINDOW: usize = 10;
+/// Observations older than this are ignored when measuring the volatility
+pub const VOLATILITY_LOOKBACK_SECONDS: u32 = 60 * 60;
+/// The TWAP window used by the swap price guard when the pool does not set one
+pub const DEFAULT_TWAP_WINDOW_SECONDS: u32 = 5 * 60;
 
 /// The element of observations in ObservationState
 #[zero_copy(unsafe)]
@@ -105,6 +111,91 @@ impl ObservationState {
             self.observation_index = next_observation_index;
         }
     }
+
+    /// Measures the recent volatility in ticks, which is the range of the current tick and the
+    /// time weighted average ticks of the latest observation intervals
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The ObservationState account to read from
+    /// * `tick_current` - The current tick of the pool
+    /// * `block_timestamp` - The current timestamp, the intervals end before the lookback are skipped
+    ///
+    pub fn get_volatility_ticks(&self, tick_current: i32, block_timestamp: u32) -> u32 {
+        let mut min_tick = tick_current;
+        let mut max_tick = tick_current;
+        if !self.initialized {
+            return 0;
+        }
+        let lookback_start = block_timestamp.saturating_sub(VOLATILITY_LOOKBACK_SECONDS);
+        let mut index = self.observation_index as usize;
+        for _ in 0..VOLATILITY_OBSERVATION_WINDOW {
+            let prev_index = if index == 0 {
+                OBSERVATION_NUM - 1
+            } else {
+                index - 1
+            };
+            let observation = self.observations[index];
+            let prev_observation = self.observations[prev_index];
+            // the ring buffer is not filled yet or the interval is out of the lookback
+            if prev_observation.block_timestamp == 0
+                || prev_observation.block_timestamp >= observation.block_timestamp
+                || observation.block_timestamp < lookback_start
+            {
+                break;
+            }
+            let delta_time = observation.block_timestamp - prev_observation.block_timestamp;
+            let average_tick = observation
+                .tick_cumulative
+                .wrapping_sub(prev_observation.tick_cumulative)
+                / i64::from(delta_time);
+            min_tick = min_tick.min(average_tick as i32);
+            max_tick = max_tick.max(average_tick as i32);
+            index = prev_index;
+        }
+        max_tick.abs_diff(min_tick)
+    }
+
+    /// Returns the time weighted average tick from the latest observation back over at least
+    /// `window_seconds`, or None if the recorded observations do not cover the window yet
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The ObservationState account to read from
+    /// * `window_seconds` - The minimum seconds the average covers
+    ///
+    pub fn get_twap_tick(&self, window_seconds: u32) -> Option<i32> {
+        if !self.initialized {
+            return None;
+        }
+        let latest_observation = self.observations[self.observation_index as usize];
+        let mut index = self.observation_index as usize;
+        for _ in 0..OBSERVATION_NUM - 1 {
+            let prev_index = if index == 0 {
+                OBSERVATION_NUM - 1
+            } else {
+                index - 1
+            };
+            let observation = self.observations[index];
+            let prev_observation = self.observations[prev_index];
+            // the ring buffer is not filled yet
+            if prev_observation.block_timestamp == 0
+                || prev_observation.block_timestamp >= observation.block_timestamp
+            {
+                return None;
+            }
+            let delta_time = latest_observation.block_timestamp - prev_observation.block_timestamp;
+            if delta_time >= window_seconds {
+                let average_tick = latest_observation
+                    .tick_cumulative
+                    .wrapping_sub(prev_observation.tick_cumulative)
+                    / i64::from(delta_time);
+                return Some(average_tick as i32);
+            }
+            index = prev_index;
+        }
+        None
+    }
 }
 
 /// Returns the block timestamp truncated to 32 bits, i.e. mod 2**32
@@ -235,3 +326,78 @@ pub mod oracle_layout_test {
         }
     }
 }
+
+#[cfg(test)]
+pub mod oracle_volatility_test {
+    use super::*;
+
+    #[test]
+    fn get_volatility_ticks_test() {
+        let mut observation_state = ObservationState::default();
+        assert_eq!(observation_state.get_volatility_ticks(10, 1000), 0);
+
+        observation_state.update(1000, 0);
+        // only one observation, no interval to measure
+        assert_eq!(observation_state.get_volatility_ticks(10, 1000), 0);
+
+        observation_state.update(1015, 100);
+        observation_state.update(1030, -50);
+        observation_state.update(1045, 20);
+        // average ticks of the intervals are 100, -50 and 20
+        assert_eq!(observation_state.get_volatility_ticks(10, 1045), 150);
+        assert_eq!(observation_state.get_volatility_ticks(200, 1045), 250);
+
+        // all the observations are out of the lookback
+        assert_eq!(
+            observation_state.get_volatility_ticks(10, 1045 + VOLATILITY_LOOKBACK_SECONDS + 1),
+            0
+        );
+    }
+
+    #[test]
+    fn get_volatility_ticks_wrap_around_test() {
+        let mut observation_state = ObservationState::default();
+        let mut block_timestamp = 1000;
+        observation_state.update(block_timestamp, 0);
+        for i in 0..OBSERVATION_NUM + VOLATILITY_OBSERVATION_WINDOW {
+            block_timestamp += OBSERVATION_UPDATE_DURATION_DEFAULT;
+            let tick = if i < OBSERVATION_NUM { 1000 } else { 0 };
+            observation_state.update(block_timestamp, tick);
+            if i == OBSERVATION_NUM {
+                // the window covers the intervals before the ring buffer wraps around
+                assert_eq!(
+                    observation_state.get_volatility_ticks(0, block_timestamp),
+                    1000
+                );
+            }
+        }
+        let observation_index = observation_state.observation_index;
+        assert_eq!(observation_index as usize, VOLATILITY_OBSERVATION_WINDOW);
+        // the older intervals out of the window are ignored
+        assert_eq!(
+            observation_state.get_volatility_ticks(0, block_timestamp),
+            0
+        );
+    }
+
+    #[test]
+    fn get_twap_tick_test() {
+        let mut observation_state = ObservationState::default();
+        assert_eq!(observation_state.get_twap_tick(30), None);
+
+        observation_state.update(1000, 0);
+        // only one observation, no interval to average
+        assert_eq!(observation_state.get_twap_tick(0), None);
+
+        observation_state.update(1015, 100);
+        observation_state.update(1030, -40);
+        observation_state.update(1045, 20);
+        assert_eq!(observation_state.get_twap_tick(15), Some(20));
+        // average ticks of the intervals are -40 and 20
+        assert_eq!(observation_state.get_twap_tick(30), Some(-10));
+        // the window ends between two observations, so the average extends to the older one
+        assert_eq!(observation_state.get_twap_tick(31), Some(26));
+        // the observations do not cover the window
+        assert_eq!(observation_state.get_twap_tick(46), None);
+    }
+}
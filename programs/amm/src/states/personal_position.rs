@@ -42,8 +42,12 @@ pub struct PersonalPositionState {
     pub reward_infos: [PositionRewardInfo; REWARD_NUM],
     // account update recent epoch
     pub recent_epoch: u64,
+    /// The liquidity can't be removed before this timestamp, fees and rewards can still be collected
+    pub unlock_timestamp: u64,
+    /// The account allowed to release the lock early, the default key if no one can
+    pub lock_authority: Pubkey,
     // Unused bytes for future upgrades.
-    pub padding: [u64; 7],
+    pub padding: [u64; 2],
 }
 
 impl PersonalPositionState {
@@ -58,6 +62,10 @@ impl PersonalPositionState {
         ]
     }
 
+    pub fn is_locked(&self, block_timestamp: u64) -> bool {
+        block_timestamp < self.unlock_timestamp
+    }
+
     pub fn update_rewards(
         &mut self,
         reward_growths_inside: [u128; REWARD_NUM],
@@ -232,6 +240,144 @@ pub struct CollectPersonalFeeEvent {
     pub amount_1: u64,
 }
 
+/// Emitted when rewards are collected for a position
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CollectPersonalRewardEvent {
+    /// The ID of the token for which rewards were collected
+    pub position_nft_mint: Pubkey,
+
+    /// The amount of each reward collected
+    pub reward_amounts: [u64; REWARD_NUM],
+}
+
+/// Emitted when the fees of a position are compounded into its liquidity
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CompoundPositionEvent {
+    /// The ID of the token for which the fees were compounded
+    pub position_nft_mint: Pubkey,
+
+    /// The amount by which liquidity for the position was increased
+    pub liquidity: u128,
+
+    /// The amount of token_0 owed to the position that was compounded
+    pub amount_0: u64,
+
+    /// The amount of token_1 owed to the position that was compounded
+    pub amount_1: u64,
+}
+
+/// Emitted when the liquidity of a position is moved to a new range
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RebalancePositionEvent {
+    /// The ID of the token for which the liquidity was moved
+    pub position_nft_mint: Pubkey,
+
+    /// The range and the liquidity of the position before the rebalance
+    pub tick_lower_index_before: i32,
+    pub tick_upper_index_before: i32,
+    pub liquidity_before: u128,
+
+    /// The range and the liquidity of the position after the rebalance
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub liquidity: u128,
+
+    /// The amounts of token_0 and token_1 withdrawn from the previous range
+    pub decrease_amount_0: u64,
+    pub decrease_amount_1: u64,
+
+    /// The direction and the amounts of the swap through the pool
+    pub zero_for_one: bool,
+    pub swap_amount_in: u64,
+    pub swap_amount_out: u64,
+
+    /// The amounts of token_0 and token_1 deposited into the new range
+    pub increase_amount_0: u64,
+    pub increase_amount_1: u64,
+
+    /// The amounts of token_0 and token_1 sent back to the position owner
+    pub refund_amount_0: u64,
+    pub refund_amount_1: u64,
+}
+
+/// Emitted when a single token is deposited into a position
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct IncreaseLiquidityZapEvent {
+    /// The ID of the token for which liquidity was increased
+    pub position_nft_mint: Pubkey,
+
+    /// The amount by which liquidity for the NFT position was increased
+    pub liquidity: u128,
+
+    /// Whether the deposited token is token_0
+    pub zero_for_one: bool,
+
+    /// The amount of the deposited token paid by the owner, and its transfer fee
+    pub amount_in: u64,
+    pub amount_in_transfer_fee: u64,
+
+    /// The amounts of the swap through the pool
+    pub swap_amount_in: u64,
+    pub swap_amount_out: u64,
+
+    /// The amounts of token_0 and token_1 added to the liquidity
+    pub amount_0: u64,
+    pub amount_1: u64,
+
+    /// The amounts of token_0 and token_1 sent back to the owner
+    pub refund_amount_0: u64,
+    pub refund_amount_1: u64,
+}
+
+/// Emitted when a position is withdrawn into a single token
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct DecreaseLiquidityZapOutEvent {
+    /// The ID of the token for which liquidity was decreased
+    pub position_nft_mint: Pubkey,
+
+    /// The amount by which liquidity for the position was decreased
+    pub liquidity: u128,
+
+    /// The amounts of token_0 and token_1 that were withdrawn for the liquidity decrease
+    pub decrease_amount_0: u64,
+    pub decrease_amount_1: u64,
+
+    /// The amounts of token_0 and token_1 fees collected
+    pub fee_amount_0: u64,
+    pub fee_amount_1: u64,
+
+    /// The direction and the amounts of the swap through the pool
+    pub zero_for_one: bool,
+    pub swap_amount_in: u64,
+    pub swap_amount_out: u64,
+
+    /// The amount of the single token sent to the recipient, and its transfer fee
+    pub amount_out: u64,
+    pub transfer_fee: u64,
+}
+
+/// Emitted when the lock of a position is set, extended or released
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PositionLockedEvent {
+    /// The ID of the token of the locked position
+    pub position_nft_mint: Pubkey,
+
+    /// The signer who updated the lock
+    pub authority: Pubkey,
+
+    /// The liquidity of the position is locked until this timestamp
+    pub unlock_timestamp: u64,
+
+    /// The account allowed to release the lock early
+    pub lock_authority: Pubkey,
+}
+
 /// Emitted when Reward are updated for a pool
 #[event]
 #[cfg_attr(feature = "client", derive(Debug))]
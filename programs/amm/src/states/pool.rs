@@ -21,8 +21,14 @@ pub const POOL_REWARD_VAULT_SEED: &str = "pool_reward_vault";
 pub const POOL_TICK_ARRAY_BITMAP_SEED: &str = "pool_tick_array_bitmap_extension";
 // Number of rewards Token
 pub const REWARD_NUM: usize = 3;
-/// Fee charged when creating a new pool. Set to zero to disable
-pub const POOL_CREATION_FEE_LAMPORTS: u64 = 0;
+/// The number of times the exponential launch fee halves its excess over the config fee during the launch window
+pub const LAUNCH_FEE_HALF_LIVES: u32 = 8;
+/// The longest a new pool can stay closed for swaps after it is created
+pub const MAX_OPEN_TIME_DELAY: u64 = 30 * 24 * 60 * 60;
+/// The longest launch window of a pool
+pub const MAX_LAUNCH_FEE_DURATION: u32 = 24 * 60 * 60;
+/// The highest flat launch fee, 100 times the default flat trade fee
+pub const MAX_LAUNCH_FEE_FLAT: u64 = 10_000_000;
 
 #[cfg(feature = "paramset")]
 pub mod reward_period_limit {
@@ -45,12 +51,56 @@ pub enum PoolStatusBitIndex {
     Swap,
 }
 
+/// The calls into the hook program of a pool
+/// bit0, 1: call `before_swap` before the swap
+/// bit1, 1: call `after_swap` after the swap
+/// bit2, 1: call `before_modify_position` before the liquidity of a position is increased or decreased
+/// bit3, 1: call `after_modify_position` after the liquidity of a position is increased or decreased
+#[derive(Clone, Copy)]
+pub enum HookPermissionBitIndex {
+    BeforeSwap,
+    AfterSwap,
+    BeforeModifyPosition,
+    AfterModifyPosition,
+}
+
+pub const HOOK_PERMISSIONS_MASK: u8 = 0b1111;
+/// The hook calls around a swap
+pub const SWAP_HOOK_PERMISSIONS: [HookPermissionBitIndex; 2] = [
+    HookPermissionBitIndex::BeforeSwap,
+    HookPermissionBitIndex::AfterSwap,
+];
+/// The hook calls around a liquidity change
+pub const MODIFY_POSITION_HOOK_PERMISSIONS: [HookPermissionBitIndex; 2] = [
+    HookPermissionBitIndex::BeforeModifyPosition,
+    HookPermissionBitIndex::AfterModifyPosition,
+];
+
 #[derive(PartialEq, Eq)]
 pub enum PoolStatusBitFlag {
     Enable,
     Disable,
 }
 
+/// Decides how the launch fee decays to the flat trade fee of the amm config
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchFeeDecayMode {
+    /// The excess over the config fee decreases linearly to zero at the end of the launch window
+    Linear,
+    /// The excess over the config fee halves `LAUNCH_FEE_HALF_LIVES` times during the launch window
+    Exponential,
+}
+
+impl LaunchFeeDecayMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        match mode {
+            0 => Ok(LaunchFeeDecayMode::Linear),
+            1 => Ok(LaunchFeeDecayMode::Exponential),
+            _ => err!(ErrorCode::InvalidLaunchFeeDecayMode),
+        }
+    }
+}
+
 /// The pool state
 ///
 /// PDA of `[POOL_SEED, config, token_mint_0, token_mint_1]`
@@ -115,8 +165,10 @@ pub struct PoolState {
     /// bit3, 1: disable collect reward, 0: normal
     /// bit4, 1: disable swap, 0: normal
     pub status: u8,
+    /// 1: the fees are overridden by the `PoolFeeOverride` account, which must be provided to swap
+    pub fee_override: u8,
     /// Leave blank for future use
-    pub padding: [u8; 7],
+    pub padding: [u8; 6],
 
     pub reward_infos: [RewardInfo; REWARD_NUM],
 
@@ -134,13 +186,61 @@ pub struct PoolState {
     pub fund_fees_token_1: u64,
 
     // The timestamp allowed for swap in the pool.
-    // Note: The open_time is disabled for now.
     pub open_time: u64,
     // account recent update epoch
     pub recent_epoch: u64,
 
+    /// The pool creator fees accrued and not yet collected
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
+
+    /// The flat trade fee charged right after open_time, decays to the config fee during the launch window
+    pub launch_fee_flat: u64,
+    /// The seconds of the launch window, the launch fee is disabled when it is 0
+    pub launch_fee_duration: u32,
+    /// How the launch fee decays, see `LaunchFeeDecayMode`
+    pub launch_fee_decay_mode: u8,
+    /// Set while a flash swap waits for its callback, the pool rejects swaps and liquidity changes until it is cleared
+    pub locked: u8,
+    pub padding5: [u8; 2],
+
+    /// The max ticks the post-swap tick may deviate from the oracle TWAP tick, the guard is disabled when it is 0
+    pub max_deviation_ticks: u32,
+    /// The seconds of the TWAP window the swap price is checked against, `DEFAULT_TWAP_WINDOW_SECONDS` when it is 0
+    pub twap_window_seconds: u32,
+
+    /// The flat fee charged for each token borrowed by a flash loan
+    pub flash_loan_fee_flat: u64,
+    /// The proportional flash loan fee, denominated in hundredths of a bip (10^-6)
+    pub flash_loan_fee_rate: u32,
+    /// How `flash_loan_fee_rate` is combined with `flash_loan_fee_flat`, see `TradeFeeMode`
+    pub flash_loan_fee_mode: u8,
+    pub padding6: [u8; 3],
+    /// The part of the liquidity in range owned by limit orders, it doesn't earn fees nor rewards
+    pub limit_order_liquidity: u128,
+    /// The program called before and after the swaps and liquidity changes allowed by `hook_permissions`
+    pub hook_program: Pubkey,
+    /// Bitwise representation of the calls into the hook program, see `HookPermissionBitIndex`
+    pub hook_permissions: u8,
+    pub padding7: [u8; 7],
+
+    /// The max ticks the price may move within a circuit breaker window, the breaker is disabled when it is 0
+    pub circuit_breaker_max_ticks: u32,
+    /// The seconds of the circuit breaker window
+    pub circuit_breaker_window_seconds: u32,
+    /// The seconds the swaps in the direction of the move are paused once the breaker trips
+    pub circuit_breaker_cooldown_seconds: u32,
+    /// The start time and the start tick of the current circuit breaker window
+    pub circuit_breaker_window_start_time: u32,
+    pub circuit_breaker_window_start_tick: i32,
+    /// The swaps in the paused direction are rejected until this time
+    pub circuit_breaker_paused_until: u32,
+    /// 0: not tripped, 1: zero_for_one swaps are paused, 2: one_for_zero swaps are paused
+    pub circuit_breaker_paused_direction: u8,
+    pub padding8: [u8; 7],
+
     // Unused bytes for future upgrades.
-    pub padding1: [u64; 24],
+    pub padding1: [u64; 6],
     pub padding2: [u64; 32],
 }
 
@@ -222,7 +322,8 @@ impl PoolState {
         self.swap_in_amount_token_1 = 0;
         self.swap_out_amount_token_0 = 0;
         self.status = 0;
-        self.padding = [0; 7];
+        self.fee_override = 0;
+        self.padding = [0; 6];
         self.tick_array_bitmap = [0; 16];
         self.total_fees_token_0 = 0;
         self.total_fees_claimed_token_0 = 0;
@@ -232,7 +333,32 @@ impl PoolState {
         self.fund_fees_token_1 = 0;
         self.open_time = open_time;
         self.recent_epoch = get_recent_epoch()?;
-        self.padding1 = [0; 24];
+        self.creator_fees_token_0 = 0;
+        self.creator_fees_token_1 = 0;
+        self.launch_fee_flat = 0;
+        self.launch_fee_duration = 0;
+        self.launch_fee_decay_mode = 0;
+        self.locked = 0;
+        self.padding5 = [0; 2];
+        self.max_deviation_ticks = 0;
+        self.twap_window_seconds = 0;
+        self.flash_loan_fee_flat = 0;
+        self.flash_loan_fee_rate = 0;
+        self.flash_loan_fee_mode = TradeFeeMode::FlatOnly as u8;
+        self.padding6 = [0; 3];
+        self.limit_order_liquidity = 0;
+        self.hook_program = Pubkey::default();
+        self.hook_permissions = 0;
+        self.padding7 = [0; 7];
+        self.circuit_breaker_max_ticks = 0;
+        self.circuit_breaker_window_seconds = 0;
+        self.circuit_breaker_cooldown_seconds = 0;
+        self.circuit_breaker_window_start_time = 0;
+        self.circuit_breaker_window_start_tick = 0;
+        self.circuit_breaker_paused_until = 0;
+        self.circuit_breaker_paused_direction = 0;
+        self.padding8 = [0; 7];
+        self.padding1 = [0; 6];
         self.padding2 = [0; 32];
         self.observation_key = observation_state_key;
 
@@ -352,7 +478,12 @@ impl PoolState {
             }
             let latest_update_timestamp = curr_timestamp.min(reward_info.end_time);
 
-            if self.liquidity != 0 {
+            // limit orders in range don't earn rewards
+            let reward_liquidity = self
+                .liquidity
+                .checked_sub(self.limit_order_liquidity)
+                .unwrap();
+            if reward_liquidity != 0 {
                 require_gte!(latest_update_timestamp, reward_info.last_update_time);
                 let time_delta = latest_update_timestamp
                     .checked_sub(reward_info.last_update_time)
@@ -361,7 +492,7 @@ impl PoolState {
                 let reward_growth_delta = U256::from(time_delta)
                     .mul_div_floor(
                         U256::from(reward_info.emissions_per_second_x64),
-                        U256::from(self.liquidity),
+                        U256::from(reward_liquidity),
                     )
                     .unwrap();
 
@@ -573,6 +704,197 @@ impl PoolState {
         self.status.bitand(status) == 0
     }
 
+    pub fn is_fee_overridden(&self) -> bool {
+        self.fee_override != 0
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked != 0
+    }
+
+    pub fn initialize_hook(&mut self, hook_program: Pubkey, hook_permissions: u8) -> Result<()> {
+        require!(
+            hook_permissions & !HOOK_PERMISSIONS_MASK == 0,
+            ErrorCode::InvalidHookPermissions
+        );
+        if hook_permissions != 0 {
+            require!(
+                hook_program != Pubkey::default() && hook_program != crate::id(),
+                ErrorCode::InvalidHookProgram
+            );
+        }
+        self.hook_program = hook_program;
+        self.hook_permissions = hook_permissions;
+        Ok(())
+    }
+
+    pub fn has_hook_permission(&self, bit: HookPermissionBitIndex) -> bool {
+        self.hook_permissions & (1 << (bit as u8)) != 0
+    }
+
+    /// Sets the circuit breaker and resets a tripped breaker, a new window starts at the current tick
+    pub fn initialize_circuit_breaker(
+        &mut self,
+        max_ticks: u32,
+        window_seconds: u32,
+        cooldown_seconds: u32,
+        block_timestamp: u32,
+    ) {
+        self.circuit_breaker_max_ticks = max_ticks;
+        self.circuit_breaker_window_seconds = window_seconds;
+        self.circuit_breaker_cooldown_seconds = cooldown_seconds;
+        self.circuit_breaker_window_start_time = block_timestamp;
+        self.circuit_breaker_window_start_tick = self.tick_current;
+        self.circuit_breaker_paused_until = 0;
+        self.circuit_breaker_paused_direction = 0;
+    }
+
+    /// Rejects the swap if the circuit breaker paused the swaps in its direction
+    pub fn check_circuit_breaker(&self, zero_for_one: bool, block_timestamp: u32) -> Result<()> {
+        let direction = if zero_for_one { 1 } else { 2 };
+        require!(
+            self.circuit_breaker_paused_direction != direction
+                || block_timestamp >= self.circuit_breaker_paused_until,
+            ErrorCode::CircuitBreakerTripped
+        );
+        Ok(())
+    }
+
+    /// Tracks the price move of a swap ending at `tick` in the circuit breaker window,
+    /// must be called before the pool tick is updated. Returns the start tick of the window if the breaker trips
+    pub fn update_circuit_breaker(&mut self, tick: i32, block_timestamp: u32) -> Option<i32> {
+        if self.circuit_breaker_max_ticks == 0 {
+            return None;
+        }
+        if block_timestamp.saturating_sub(self.circuit_breaker_window_start_time)
+            >= self.circuit_breaker_window_seconds
+        {
+            self.circuit_breaker_window_start_time = block_timestamp;
+            self.circuit_breaker_window_start_tick = self.tick_current;
+        }
+        let tick_start = self.circuit_breaker_window_start_tick;
+        let tick_move = i64::from(tick) - i64::from(tick_start);
+        if tick_move.unsigned_abs() <= u64::from(self.circuit_breaker_max_ticks) {
+            return None;
+        }
+        // pause the swaps moving the price further, the swaps moving it back are allowed
+        self.circuit_breaker_paused_direction = if tick_move < 0 { 1 } else { 2 };
+        self.circuit_breaker_paused_until =
+            block_timestamp.saturating_add(self.circuit_breaker_cooldown_seconds);
+        self.circuit_breaker_window_start_time = block_timestamp;
+        self.circuit_breaker_window_start_tick = tick;
+        Some(tick_start)
+    }
+
+    pub fn initialize_launch_fee(
+        &mut self,
+        launch_fee_flat: u64,
+        launch_fee_duration: u32,
+        launch_fee_decay_mode: u8,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        require_gte!(
+            MAX_OPEN_TIME_DELAY,
+            self.open_time.saturating_sub(block_timestamp),
+            ErrorCode::InvalidOpenTime
+        );
+        require!(
+            launch_fee_duration <= MAX_LAUNCH_FEE_DURATION
+                && launch_fee_flat <= MAX_LAUNCH_FEE_FLAT,
+            ErrorCode::InvalidLaunchFee
+        );
+        LaunchFeeDecayMode::from_u8(launch_fee_decay_mode)?;
+        self.launch_fee_flat = launch_fee_flat;
+        self.launch_fee_duration = launch_fee_duration;
+        self.launch_fee_decay_mode = launch_fee_decay_mode;
+        Ok(())
+    }
+
+    /// The flat trade fee of the pool at `block_timestamp`, which is the decaying launch fee
+    /// during the launch window and `config_fee_flat` afterwards
+    pub fn get_trade_fee_flat(&self, config_fee_flat: u64, block_timestamp: u64) -> u64 {
+        let duration = u64::from(self.launch_fee_duration);
+        if duration == 0 || self.launch_fee_flat <= config_fee_flat {
+            return config_fee_flat;
+        }
+        let elapsed = block_timestamp.saturating_sub(self.open_time);
+        if elapsed >= duration {
+            return config_fee_flat;
+        }
+        let excess = u128::from(self.launch_fee_flat - config_fee_flat);
+        let decayed_excess = match LaunchFeeDecayMode::from_u8(self.launch_fee_decay_mode) {
+            Ok(LaunchFeeDecayMode::Exponential) => {
+                // interpolate linearly between two adjacent halvings
+                let progress = u128::from(elapsed) * u128::from(LAUNCH_FEE_HALF_LIVES);
+                let half_lives = (progress / u128::from(duration)) as u32;
+                let remainder = progress % u128::from(duration);
+                let upper = excess >> half_lives;
+                let lower = excess >> (half_lives + 1);
+                upper - (upper - lower) * remainder / u128::from(duration)
+            }
+            _ => excess * u128::from(duration - elapsed) / u128::from(duration),
+        };
+        config_fee_flat + decayed_excess as u64
+    }
+
+    /// The fee charged for borrowing `amount` by a flash loan, the proportional part is rounded up
+    pub fn get_flash_loan_fee(&self, amount: u64) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        let rate_fee = || {
+            amount
+                .mul_div_ceil(
+                    self.flash_loan_fee_rate.into(),
+                    FEE_RATE_DENOMINATOR_VALUE.into(),
+                )
+                .ok_or(ErrorCode::CalculateOverflow)
+        };
+        let fee = match TradeFeeMode::from_u8(self.flash_loan_fee_mode)? {
+            TradeFeeMode::FlatOnly => self.flash_loan_fee_flat,
+            TradeFeeMode::Max => self.flash_loan_fee_flat.max(rate_fee()?),
+            TradeFeeMode::Sum => self
+                .flash_loan_fee_flat
+                .checked_add(rate_fee()?)
+                .ok_or(ErrorCode::CalculateOverflow)?,
+        };
+        Ok(fee)
+    }
+
+    /// The price deviation limit of a swap, a per-swap override can only tighten the pool limit.
+    /// Returns 0 when the guard is disabled
+    pub fn get_max_deviation_ticks(&self, swap_override: Option<u32>) -> u32 {
+        match swap_override.filter(|ticks| *ticks > 0) {
+            Some(ticks) if self.max_deviation_ticks > 0 => ticks.min(self.max_deviation_ticks),
+            Some(ticks) => ticks,
+            None => self.max_deviation_ticks,
+        }
+    }
+
+    /// The seconds of the TWAP window the swap price is checked against
+    pub fn get_twap_window_seconds(&self) -> u32 {
+        if self.twap_window_seconds == 0 {
+            DEFAULT_TWAP_WINDOW_SECONDS
+        } else {
+            self.twap_window_seconds
+        }
+    }
+
+    /// All the swap fees accumulated in token_0 or token_1, include the unclaimed protocol fee, fund fee and creator fee
+    pub fn get_accumulated_swap_fees(&self, is_token_0: bool) -> u64 {
+        if is_token_0 {
+            self.total_fees_token_0
+                + self.protocol_fees_token_0
+                + self.fund_fees_token_0
+                + self.creator_fees_token_0
+        } else {
+            self.total_fees_token_1
+                + self.protocol_fees_token_1
+                + self.fund_fees_token_1
+                + self.creator_fees_token_1
+        }
+    }
+
     pub fn is_overflow_default_tickarray_bitmap(&self, tick_indexs: Vec<i32>) -> bool {
         let (min_tick_array_start_index_boundary, max_tick_array_index_boundary) =
             self.tick_array_start_index_range();
@@ -706,6 +1028,22 @@ pub struct PoolCreatedEvent {
     pub token_vault_1: Pubkey,
 }
 
+/// Emitted when a swap moves the price too far within the circuit breaker window
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CircuitBreakerTrippedEvent {
+    /// The pool whose swaps are paused
+    pub pool_state: Pubkey,
+    /// The tick at the start of the window
+    pub tick_start: i32,
+    /// The tick after the swap tripping the breaker
+    pub tick: i32,
+    /// true if the zero_for_one swaps are paused, false if the one_for_zero swaps are paused
+    pub zero_for_one: bool,
+    /// The swaps in the paused direction are rejected until this time
+    pub paused_until: u32,
+}
+
 /// Emitted when the collected protocol fees are withdrawn by the factory owner
 #[event]
 #[cfg_attr(feature = "client", derive(Debug))]
@@ -726,6 +1064,26 @@ pub struct CollectProtocolFeeEvent {
     pub amount_1: u64,
 }
 
+/// Emitted when the creator fees are withdrawn by the pool creator
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CollectCreatorFeeEvent {
+    /// The pool whose creator fee is collected
+    pub pool_state: Pubkey,
+
+    /// The address that receives the collected token_0 creator fees
+    pub recipient_token_account_0: Pubkey,
+
+    /// The address that receives the collected token_1 creator fees
+    pub recipient_token_account_1: Pubkey,
+
+    /// The amount of token_0 creator fees that is withdrawn
+    pub amount_0: u64,
+
+    /// The amount of token_1 creator fees that is withdrawn
+    pub amount_1: u64,
+}
+
 /// Emitted by when a swap is performed for a pool
 #[event]
 #[cfg_attr(feature = "client", derive(Debug))]
@@ -767,6 +1125,58 @@ pub struct SwapEvent {
 
     /// The log base 1.0001 of price of the pool after the swap
     pub tick: i32,
+
+    /// The trade fee charged in input token, include the protocol fee, fund fee, creator fee and referral fee
+    pub fee_amount: u64,
+
+    /// The part of the specified amount filled by the swap, in input token if base input, otherwise in output token
+    pub filled_amount: u64,
+
+    /// The part of the specified amount left unfilled when the swap stops at the price limit or the liquidity runs out
+    pub unfilled_amount: u64,
+}
+
+/// Emitted when the referrer of a swap is paid
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ReferralFeeEvent {
+    /// The pool in which the swap was performed
+    pub pool_state: Pubkey,
+
+    /// The token account which received the referral fee
+    pub referral_token_account: Pubkey,
+
+    /// The mint of the referral fee, which is the input token of the swap
+    pub mint: Pubkey,
+
+    /// The referral share of the trade fee, in basis points
+    pub referral_fee_bps: u16,
+
+    /// The referral fee amount paid out of the pool vault
+    pub referral_fee_amount: u64,
+}
+
+/// Emitted when a flash loan is repaid
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct FlashLoanEvent {
+    /// The pool the tokens were borrowed from
+    pub pool_state: Pubkey,
+
+    /// The payer of the flash loan
+    pub sender: Pubkey,
+
+    /// The amount of token_0 sent out of the vault, token 2022 transfer fee included
+    pub amount_0: u64,
+
+    /// The amount of token_1 sent out of the vault, token 2022 transfer fee included
+    pub amount_1: u64,
+
+    /// The flash loan fee of token_0, include the protocol fee, fund fee and creator fee
+    pub fee_0: u64,
+
+    /// The flash loan fee of token_1, include the protocol fee, fund fee and creator fee
+    pub fee_1: u64,
 }
 
 /// Emitted pool liquidity change when increase and decrease liquidity
@@ -1020,6 +1430,213 @@ pub mod pool_test {
         }
     }
 
+    mod hook_permission_test {
+        use super::*;
+
+        #[test]
+        fn initialize_hook_test() {
+            let mut pool_state = PoolState::default();
+            let hook_program = Pubkey::new_unique();
+            pool_state.initialize_hook(hook_program, 0b0101).unwrap();
+            assert!(pool_state.has_hook_permission(HookPermissionBitIndex::BeforeSwap));
+            assert!(!pool_state.has_hook_permission(HookPermissionBitIndex::AfterSwap));
+            assert!(pool_state.has_hook_permission(HookPermissionBitIndex::BeforeModifyPosition));
+            assert!(!pool_state.has_hook_permission(HookPermissionBitIndex::AfterModifyPosition));
+
+            // unknown permission bits
+            assert!(pool_state.initialize_hook(hook_program, 0b1_0000).is_err());
+            // permissions without a hook program
+            assert!(pool_state
+                .initialize_hook(Pubkey::default(), 0b0001)
+                .is_err());
+            // no permissions, no hook
+            pool_state.initialize_hook(Pubkey::default(), 0).unwrap();
+            assert!(!pool_state.has_hook_permission(HookPermissionBitIndex::BeforeSwap));
+        }
+    }
+
+    mod circuit_breaker_test {
+        use super::*;
+
+        #[test]
+        fn trip_and_cooldown_test() {
+            let mut pool_state = PoolState::default();
+            pool_state.tick_current = 100;
+            pool_state.initialize_circuit_breaker(50, 600, 300, 1000);
+
+            // moves inside the limit don't trip the breaker
+            assert!(pool_state.update_circuit_breaker(130, 1010).is_none());
+            pool_state.tick_current = 130;
+            assert!(pool_state.update_circuit_breaker(60, 1020).is_none());
+            pool_state.tick_current = 60;
+
+            // the window keeps its start tick, 100 - 40 > 50
+            assert_eq!(pool_state.update_circuit_breaker(40, 1030), Some(100));
+            pool_state.tick_current = 40;
+            assert!(pool_state.check_circuit_breaker(true, 1100).is_err());
+            // moving the price back is allowed
+            assert!(pool_state.check_circuit_breaker(false, 1100).is_ok());
+            // the cooldown expired
+            assert!(pool_state.check_circuit_breaker(true, 1330).is_ok());
+
+            // an operator reset clears the pause
+            assert_eq!(pool_state.update_circuit_breaker(-20, 1040), Some(40));
+            pool_state.initialize_circuit_breaker(50, 600, 300, 1050);
+            assert!(pool_state.check_circuit_breaker(true, 1060).is_ok());
+        }
+
+        #[test]
+        fn window_rolls_test() {
+            let mut pool_state = PoolState::default();
+            pool_state.initialize_circuit_breaker(50, 600, 300, 1000);
+
+            assert!(pool_state.update_circuit_breaker(40, 1100).is_none());
+            pool_state.tick_current = 40;
+            // a new window starts at the tick before the swap
+            assert!(pool_state.update_circuit_breaker(80, 1700).is_none());
+            let window_start_tick = pool_state.circuit_breaker_window_start_tick;
+            assert_eq!(window_start_tick, 40);
+            pool_state.tick_current = 80;
+
+            // disabled breaker never trips
+            pool_state.initialize_circuit_breaker(0, 600, 300, 1800);
+            assert!(pool_state.update_circuit_breaker(-10000, 1810).is_none());
+        }
+    }
+
+    mod launch_fee_test {
+        use super::*;
+
+        fn build_launch_pool(launch_fee_decay_mode: LaunchFeeDecayMode) -> PoolState {
+            let mut pool_state = PoolState::default();
+            pool_state.open_time = 1000;
+            pool_state
+                .initialize_launch_fee(101000, 800, launch_fee_decay_mode as u8, 1000)
+                .unwrap();
+            pool_state
+        }
+
+        #[test]
+        fn linear_decay_test() {
+            let pool_state = build_launch_pool(LaunchFeeDecayMode::Linear);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1000), 101000);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1200), 76000);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1799), 1125);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1800), 1000);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 100000), 1000);
+        }
+
+        #[test]
+        fn exponential_decay_test() {
+            let pool_state = build_launch_pool(LaunchFeeDecayMode::Exponential);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1000), 101000);
+            // one half life
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1100), 51000);
+            // between the first and the second half life
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1150), 38500);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1799), 1394);
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1800), 1000);
+        }
+
+        #[test]
+        fn launch_fee_disabled_test() {
+            let mut pool_state = build_launch_pool(LaunchFeeDecayMode::Linear);
+            // the launch fee never goes below the config fee
+            assert_eq!(pool_state.get_trade_fee_flat(200000, 1000), 200000);
+
+            pool_state.launch_fee_duration = 0;
+            assert_eq!(pool_state.get_trade_fee_flat(1000, 1000), 1000);
+
+            assert!(pool_state
+                .initialize_launch_fee(101000, 800, 2, 1000)
+                .is_err());
+        }
+
+        #[test]
+        fn launch_fee_limits_test() {
+            let mut pool_state = PoolState::default();
+            let block_timestamp = 1000;
+            pool_state.open_time = block_timestamp + MAX_OPEN_TIME_DELAY;
+            pool_state
+                .initialize_launch_fee(
+                    MAX_LAUNCH_FEE_FLAT,
+                    MAX_LAUNCH_FEE_DURATION,
+                    0,
+                    block_timestamp,
+                )
+                .unwrap();
+            // an open time in the past is allowed
+            pool_state.open_time = 0;
+            pool_state
+                .initialize_launch_fee(0, 0, 0, block_timestamp)
+                .unwrap();
+
+            pool_state.open_time = block_timestamp + MAX_OPEN_TIME_DELAY + 1;
+            assert_eq!(
+                pool_state
+                    .initialize_launch_fee(0, 0, 0, block_timestamp)
+                    .unwrap_err(),
+                ErrorCode::InvalidOpenTime.into()
+            );
+            pool_state.open_time = u64::MAX;
+            assert_eq!(
+                pool_state
+                    .initialize_launch_fee(0, 0, 0, block_timestamp)
+                    .unwrap_err(),
+                ErrorCode::InvalidOpenTime.into()
+            );
+
+            pool_state.open_time = block_timestamp;
+            assert_eq!(
+                pool_state
+                    .initialize_launch_fee(MAX_LAUNCH_FEE_FLAT + 1, 800, 0, block_timestamp)
+                    .unwrap_err(),
+                ErrorCode::InvalidLaunchFee.into()
+            );
+            assert_eq!(
+                pool_state
+                    .initialize_launch_fee(101000, MAX_LAUNCH_FEE_DURATION + 1, 0, block_timestamp)
+                    .unwrap_err(),
+                ErrorCode::InvalidLaunchFee.into()
+            );
+            assert_eq!(
+                pool_state
+                    .initialize_launch_fee(101000, u32::MAX, 0, block_timestamp)
+                    .unwrap_err(),
+                ErrorCode::InvalidLaunchFee.into()
+            );
+        }
+    }
+
+    mod flash_loan_fee_test {
+        use super::*;
+
+        #[test]
+        fn flash_loan_fee_mode_test() {
+            let mut pool_state = PoolState {
+                flash_loan_fee_flat: 1000,
+                flash_loan_fee_rate: 3000,
+                ..Default::default()
+            };
+            // nothing borrowed, nothing charged
+            assert_eq!(pool_state.get_flash_loan_fee(0).unwrap(), 0);
+
+            pool_state.flash_loan_fee_mode = TradeFeeMode::FlatOnly as u8;
+            assert_eq!(pool_state.get_flash_loan_fee(1_000_000).unwrap(), 1000);
+
+            pool_state.flash_loan_fee_mode = TradeFeeMode::Max as u8;
+            assert_eq!(pool_state.get_flash_loan_fee(100_000).unwrap(), 1000);
+            // the proportional fee is rounded up
+            assert_eq!(pool_state.get_flash_loan_fee(1_000_001).unwrap(), 3001);
+
+            pool_state.flash_loan_fee_mode = TradeFeeMode::Sum as u8;
+            assert_eq!(pool_state.get_flash_loan_fee(1_000_000).unwrap(), 4000);
+
+            pool_state.flash_loan_fee_mode = 3;
+            assert!(pool_state.get_flash_loan_fee(1_000_000).is_err());
+        }
+    }
+
     mod update_reward_infos_test {
         use super::*;
         use anchor_lang::prelude::Pubkey;
@@ -1612,7 +2229,8 @@ pub mod pool_test {
             let swap_in_amount_token_1: u128 = 0x11223344556677008899aabbccddeeff;
             let swap_out_amount_token_0: u128 = 0x11223344556677880099aabbccddeeff;
             let status: u8 = 0x1b;
-            let padding: [u8; 7] = [0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18];
+            let fee_override: u8 = 0x12;
+            let padding: [u8; 6] = [0x13, 0x14, 0x15, 0x16, 0x17, 0x18];
             // RewardInfo
             let reward_state: u8 = 0x1c;
             let open_time: u64 = 0x123456789abc0def;
@@ -1683,10 +2301,35 @@ pub mod pool_test {
             let fund_fees_token_1: u64 = 0x1230456789abcdef;
             let pool_open_time: u64 = 0x1203456789abcdef;
             let recent_epoch: u64 = 0x1023456789abcdef;
-            let mut padding1: [u64; 24] = [0u64; 24];
-            let mut padding1_data = [0u8; 8 * 24];
+            let creator_fees_token_0: u64 = 0x0123456789abcdef;
+            let creator_fees_token_1: u64 = 0x0123456789abcdfe;
+            let launch_fee_flat: u64 = 0x0123456789abdcef;
+            let launch_fee_duration: u32 = 0x01234567;
+            let launch_fee_decay_mode: u8 = 0x01;
+            let locked: u8 = 0x01;
+            let padding5: [u8; 2] = [0x22, 0x23];
+            let max_deviation_ticks: u32 = 0x01234576;
+            let twap_window_seconds: u32 = 0x01234657;
+            let flash_loan_fee_flat: u64 = 0x0123456789abcedf;
+            let flash_loan_fee_rate: u32 = 0x01234675;
+            let flash_loan_fee_mode: u8 = 0x02;
+            let padding6: [u8; 3] = [0x24, 0x25, 0x26];
+            let limit_order_liquidity: u128 = 0x11223344556677889900aabbccddefef;
+            let hook_program = Pubkey::new_unique();
+            let hook_permissions: u8 = 0x0b;
+            let padding7: [u8; 7] = [0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x2d];
+            let circuit_breaker_max_ticks: u32 = 0x01235467;
+            let circuit_breaker_window_seconds: u32 = 0x01243567;
+            let circuit_breaker_cooldown_seconds: u32 = 0x01324567;
+            let circuit_breaker_window_start_time: u32 = 0x02134567;
+            let circuit_breaker_window_start_tick: i32 = -0x01234567;
+            let circuit_breaker_paused_until: u32 = 0x01234756;
+            let circuit_breaker_paused_direction: u8 = 0x02;
+            let padding8: [u8; 7] = [0x2e, 0x2f, 0x30, 0x31, 0x32, 0x33, 0x34];
+            let mut padding1: [u64; 6] = [0u64; 6];
+            let mut padding1_data = [0u8; 8 * 6];
             let mut offset = 0;
-            for i in 0..24 {
+            for i in 0..6 {
                 padding1[i] = u64::MAX - i as u64;
                 padding1_data[offset..offset + 8].copy_from_slice(&padding1[i].to_le_bytes());
                 offset += 8;
@@ -1694,9 +2337,9 @@ pub mod pool_test {
             let mut padding2: [u64; 32] = [0u64; 32];
             let mut padding2_data = [0u8; 8 * 32];
             let mut offset = 0;
-            for i in 24..(24 + 32) {
-                padding2[i - 24] = u64::MAX - i as u64;
-                padding2_data[offset..offset + 8].copy_from_slice(&padding2[i - 24].to_le_bytes());
+            for i in 6..(6 + 32) {
+                padding2[i - 6] = u64::MAX - i as u64;
+                padding2_data[offset..offset + 8].copy_from_slice(&padding2[i - 6].to_le_bytes());
                 offset += 8;
             }
             // serialize original data
@@ -1754,8 +2397,10 @@ pub mod pool_test {
             offset += 16;
             pool_data[offset..offset + 1].copy_from_slice(&status.to_le_bytes());
             offset += 1;
-            pool_data[offset..offset + 7].copy_from_slice(&padding);
-            offset += 7;
+            pool_data[offset..offset + 1].copy_from_slice(&fee_override.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 6].copy_from_slice(&padding);
+            offset += 6;
             pool_data[offset..offset + RewardInfo::LEN * REWARD_NUM]
                 .copy_from_slice(&reward_info_datas);
             offset += RewardInfo::LEN * REWARD_NUM;
@@ -1779,8 +2424,64 @@ pub mod pool_test {
             offset += 8;
             pool_data[offset..offset + 8].copy_from_slice(&recent_epoch.to_le_bytes());
             offset += 8;
-            pool_data[offset..offset + 8 * 24].copy_from_slice(&padding1_data);
-            offset += 8 * 24;
+            pool_data[offset..offset + 8].copy_from_slice(&creator_fees_token_0.to_le_bytes());
+            offset += 8;
+            pool_data[offset..offset + 8].copy_from_slice(&creator_fees_token_1.to_le_bytes());
+            offset += 8;
+            pool_data[offset..offset + 8].copy_from_slice(&launch_fee_flat.to_le_bytes());
+            offset += 8;
+            pool_data[offset..offset + 4].copy_from_slice(&launch_fee_duration.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 1].copy_from_slice(&launch_fee_decay_mode.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 1].copy_from_slice(&locked.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 2].copy_from_slice(&padding5);
+            offset += 2;
+            pool_data[offset..offset + 4].copy_from_slice(&max_deviation_ticks.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4].copy_from_slice(&twap_window_seconds.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 8].copy_from_slice(&flash_loan_fee_flat.to_le_bytes());
+            offset += 8;
+            pool_data[offset..offset + 4].copy_from_slice(&flash_loan_fee_rate.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 1].copy_from_slice(&flash_loan_fee_mode.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 3].copy_from_slice(&padding6);
+            offset += 3;
+            pool_data[offset..offset + 16].copy_from_slice(&limit_order_liquidity.to_le_bytes());
+            offset += 16;
+            pool_data[offset..offset + 32].copy_from_slice(&hook_program.to_bytes());
+            offset += 32;
+            pool_data[offset..offset + 1].copy_from_slice(&hook_permissions.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 7].copy_from_slice(&padding7);
+            offset += 7;
+            pool_data[offset..offset + 4].copy_from_slice(&circuit_breaker_max_ticks.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4]
+                .copy_from_slice(&circuit_breaker_window_seconds.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4]
+                .copy_from_slice(&circuit_breaker_cooldown_seconds.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4]
+                .copy_from_slice(&circuit_breaker_window_start_time.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4]
+                .copy_from_slice(&circuit_breaker_window_start_tick.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 4]
+                .copy_from_slice(&circuit_breaker_paused_until.to_le_bytes());
+            offset += 4;
+            pool_data[offset..offset + 1]
+                .copy_from_slice(&circuit_breaker_paused_direction.to_le_bytes());
+            offset += 1;
+            pool_data[offset..offset + 7].copy_from_slice(&padding8);
+            offset += 7;
+            pool_data[offset..offset + 8 * 6].copy_from_slice(&padding1_data);
+            offset += 8 * 6;
             pool_data[offset..offset + 8 * 32].copy_from_slice(&padding2_data);
             offset += 8 * 32;
 
@@ -1843,6 +2544,8 @@ pub mod pool_test {
             assert_eq!(unpack_swap_out_amount_token_0, swap_out_amount_token_0);
             let unpack_status = unpack_data.status;
             assert_eq!(unpack_status, status);
+            let unpack_fee_override = unpack_data.fee_override;
+            assert_eq!(unpack_fee_override, fee_override);
             let unpack_padding = unpack_data.padding;
             assert_eq!(unpack_padding, padding);
 
@@ -1893,6 +2596,78 @@ pub mod pool_test {
             assert_eq!(unpack_open_time, pool_open_time);
             let unpack_recent_epoch = unpack_data.recent_epoch;
             assert_eq!(unpack_recent_epoch, recent_epoch);
+            let unpack_creator_fees_token_0 = unpack_data.creator_fees_token_0;
+            assert_eq!(unpack_creator_fees_token_0, creator_fees_token_0);
+            let unpack_creator_fees_token_1 = unpack_data.creator_fees_token_1;
+            assert_eq!(unpack_creator_fees_token_1, creator_fees_token_1);
+            let unpack_launch_fee_flat = unpack_data.launch_fee_flat;
+            assert_eq!(unpack_launch_fee_flat, launch_fee_flat);
+            let unpack_launch_fee_duration = unpack_data.launch_fee_duration;
+            assert_eq!(unpack_launch_fee_duration, launch_fee_duration);
+            let unpack_launch_fee_decay_mode = unpack_data.launch_fee_decay_mode;
+            assert_eq!(unpack_launch_fee_decay_mode, launch_fee_decay_mode);
+            let unpack_locked = unpack_data.locked;
+            assert_eq!(unpack_locked, locked);
+            let unpack_padding5 = unpack_data.padding5;
+            assert_eq!(unpack_padding5, padding5);
+            let unpack_max_deviation_ticks = unpack_data.max_deviation_ticks;
+            assert_eq!(unpack_max_deviation_ticks, max_deviation_ticks);
+            let unpack_twap_window_seconds = unpack_data.twap_window_seconds;
+            assert_eq!(unpack_twap_window_seconds, twap_window_seconds);
+            let unpack_flash_loan_fee_flat = unpack_data.flash_loan_fee_flat;
+            assert_eq!(unpack_flash_loan_fee_flat, flash_loan_fee_flat);
+            let unpack_flash_loan_fee_rate = unpack_data.flash_loan_fee_rate;
+            assert_eq!(unpack_flash_loan_fee_rate, flash_loan_fee_rate);
+            let unpack_flash_loan_fee_mode = unpack_data.flash_loan_fee_mode;
+            assert_eq!(unpack_flash_loan_fee_mode, flash_loan_fee_mode);
+            let unpack_padding6 = unpack_data.padding6;
+            assert_eq!(unpack_padding6, padding6);
+            let unpack_limit_order_liquidity = unpack_data.limit_order_liquidity;
+            assert_eq!(unpack_limit_order_liquidity, limit_order_liquidity);
+            let unpack_hook_program = unpack_data.hook_program;
+            assert_eq!(unpack_hook_program, hook_program);
+            let unpack_hook_permissions = unpack_data.hook_permissions;
+            assert_eq!(unpack_hook_permissions, hook_permissions);
+            let unpack_padding7 = unpack_data.padding7;
+            assert_eq!(unpack_padding7, padding7);
+            let unpack_circuit_breaker_max_ticks = unpack_data.circuit_breaker_max_ticks;
+            assert_eq!(unpack_circuit_breaker_max_ticks, circuit_breaker_max_ticks);
+            let unpack_circuit_breaker_window_seconds = unpack_data.circuit_breaker_window_seconds;
+            assert_eq!(
+                unpack_circuit_breaker_window_seconds,
+                circuit_breaker_window_seconds
+            );
+            let unpack_circuit_breaker_cooldown_seconds =
+                unpack_data.circuit_breaker_cooldown_seconds;
+            assert_eq!(
+                unpack_circuit_breaker_cooldown_seconds,
+                circuit_breaker_cooldown_seconds
+            );
+            let unpack_circuit_breaker_window_start_time =
+                unpack_data.circuit_breaker_window_start_time;
+            assert_eq!(
+                unpack_circuit_breaker_window_start_time,
+                circuit_breaker_window_start_time
+            );
+            let unpack_circuit_breaker_window_start_tick =
+                unpack_data.circuit_breaker_window_start_tick;
+            assert_eq!(
+                unpack_circuit_breaker_window_start_tick,
+                circuit_breaker_window_start_tick
+            );
+            let unpack_circuit_breaker_paused_until = unpack_data.circuit_breaker_paused_until;
+            assert_eq!(
+                unpack_circuit_breaker_paused_until,
+                circuit_breaker_paused_until
+            );
+            let unpack_circuit_breaker_paused_direction =
+                unpack_data.circuit_breaker_paused_direction;
+            assert_eq!(
+                unpack_circuit_breaker_paused_direction,
+                circuit_breaker_paused_direction
+            );
+            let unpack_padding8 = unpack_data.padding8;
+            assert_eq!(unpack_padding8, padding8);
             let unpack_padding1 = unpack_data.padding1;
             assert_eq!(unpack_padding1, padding1);
             let unpack_padding2 = unpack_data.padding2;
@@ -0,0 +1,179 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+pub const POOL_FEE_OVERRIDE_SEED: &str = "pool_fee_override";
+
+pub enum PoolFeeOverrideBitIndex {
+    TradeFeeFlat,
+    ProtocolFeeRate,
+    FundFeeRate,
+}
+
+/// Overrides the fees of the amm config for a single pool
+///
+/// PDA of `[POOL_FEE_OVERRIDE_SEED, pool]`
+///
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolFeeOverride {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool whose fees are overridden
+    pub pool_id: Pubkey,
+    /// Bitwise representation of the overridden fees
+    /// bit0, 1: override trade_fee_flat
+    /// bit1, 1: override protocol_fee_rate
+    /// bit2, 1: override fund_fee_rate
+    pub override_flags: u8,
+    /// Flat trade fee charged on each swap
+    pub trade_fee_flat: u64,
+    /// The protocol fee, denominated in hundredths of a bip (10^-6)
+    pub protocol_fee_rate: u32,
+    /// The fund fee, denominated in hundredths of a bip (10^-6)
+    pub fund_fee_rate: u32,
+    pub padding: [u64; 8],
+}
+
+impl PoolFeeOverride {
+    pub const LEN: usize = 8 + 1 + 32 + 1 + 8 + 4 + 4 + 64;
+
+    pub fn key(pool_id: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[POOL_FEE_OVERRIDE_SEED.as_bytes(), pool_id.as_ref()],
+            &crate::id(),
+        )
+        .0
+    }
+
+    pub fn is_overridden(&self, bit: PoolFeeOverrideBitIndex) -> bool {
+        self.override_flags & (1 << (bit as u8)) != 0
+    }
+
+    /// Returns a copy of `amm_config` with the overridden fees replaced
+    pub fn apply(&self, amm_config: &AmmConfig) -> AmmConfig {
+        let mut effective_amm_config = amm_config.clone();
+        if self.is_overridden(PoolFeeOverrideBitIndex::TradeFeeFlat) {
+            effective_amm_config.trade_fee_flat = self.trade_fee_flat;
+        }
+        if self.is_overridden(PoolFeeOverrideBitIndex::ProtocolFeeRate) {
+            effective_amm_config.protocol_fee_rate = self.protocol_fee_rate;
+        }
+        if self.is_overridden(PoolFeeOverrideBitIndex::FundFeeRate) {
+            effective_amm_config.fund_fee_rate = self.fund_fee_rate;
+        }
+        effective_amm_config
+    }
+
+    /// Checks the fee shares carved out of the trade fee don't exceed it once the overridden fees are applied,
+    /// the rates of the amm config can change after the override is set
+    pub fn check_fee_rates(&self, amm_config: &AmmConfig) -> Result<()> {
+        let effective_amm_config = self.apply(amm_config);
+        require_gte!(
+            u64::from(FEE_RATE_DENOMINATOR_VALUE),
+            u64::from(effective_amm_config.protocol_fee_rate)
+                + u64::from(effective_amm_config.fund_fee_rate)
+                + u64::from(effective_amm_config.creator_fee_rate),
+            ErrorCode::InvalidFeeRates
+        );
+        Ok(())
+    }
+}
+
+/// Returns the fees which the swaps of the pool should use,
+/// the override account must be provided if the pool has one
+pub fn get_effective_amm_config(
+    amm_config: &AmmConfig,
+    pool_state: &PoolState,
+    pool_fee_override: Option<PoolFeeOverride>,
+) -> Result<AmmConfig> {
+    if !pool_state.is_fee_overridden() {
+        return Ok(amm_config.clone());
+    }
+    let pool_fee_override = pool_fee_override.ok_or(ErrorCode::MissingPoolFeeOverrideAccount)?;
+    require_keys_eq!(pool_fee_override.pool_id, pool_state.key());
+    pool_fee_override.check_fee_rates(amm_config)?;
+    Ok(pool_fee_override.apply(amm_config))
+}
+
+/// Emitted when the fee override of a pool is set
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolFeeOverrideChangeEvent {
+    pub pool_state: Pubkey,
+    pub override_flags: u8,
+    pub trade_fee_flat: u64,
+    pub protocol_fee_rate: u32,
+    pub fund_fee_rate: u32,
+}
+
+#[cfg(test)]
+mod pool_fee_override_test {
+    use super::*;
+    use crate::states::pool_test::build_pool;
+
+    #[test]
+    fn apply_override_test() {
+        let amm_config = AmmConfig {
+            trade_fee_flat: 1000,
+            protocol_fee_rate: 120000,
+            fund_fee_rate: 40000,
+            ..Default::default()
+        };
+        let mut pool_fee_override = PoolFeeOverride {
+            trade_fee_flat: 10,
+            protocol_fee_rate: 0,
+            fund_fee_rate: 80000,
+            ..Default::default()
+        };
+
+        let effective_amm_config = pool_fee_override.apply(&amm_config);
+        assert_eq!(effective_amm_config.trade_fee_flat, 1000);
+        assert_eq!(effective_amm_config.protocol_fee_rate, 120000);
+        assert_eq!(effective_amm_config.fund_fee_rate, 40000);
+
+        pool_fee_override.override_flags = 1 << (PoolFeeOverrideBitIndex::TradeFeeFlat as u8)
+            | 1 << (PoolFeeOverrideBitIndex::FundFeeRate as u8);
+        let effective_amm_config = pool_fee_override.apply(&amm_config);
+        assert_eq!(effective_amm_config.trade_fee_flat, 10);
+        assert_eq!(effective_amm_config.protocol_fee_rate, 120000);
+        assert_eq!(effective_amm_config.fund_fee_rate, 80000);
+    }
+
+    #[test]
+    fn effective_fee_rates_exceed_trade_fee_test() {
+        let mut amm_config = AmmConfig {
+            protocol_fee_rate: 120000,
+            fund_fee_rate: 40000,
+            ..Default::default()
+        };
+        let pool_state = build_pool(0, 10, 1 << 64, 0).take();
+        assert!(!pool_state.is_fee_overridden());
+        let pool_state = PoolState {
+            fee_override: 1,
+            ..pool_state
+        };
+        let pool_fee_override = PoolFeeOverride {
+            pool_id: pool_state.key(),
+            override_flags: 1 << (PoolFeeOverrideBitIndex::FundFeeRate as u8),
+            fund_fee_rate: 800000,
+            ..Default::default()
+        };
+        let effective_amm_config =
+            get_effective_amm_config(&amm_config, &pool_state, Some(pool_fee_override.clone()))
+                .unwrap();
+        assert_eq!(effective_amm_config.fund_fee_rate, 800000);
+
+        // the protocol fee rate of the config is raised after the override is set
+        amm_config.protocol_fee_rate = 300000;
+        assert_eq!(
+            pool_fee_override.check_fee_rates(&amm_config).unwrap_err(),
+            ErrorCode::InvalidFeeRates.into()
+        );
+        assert_eq!(
+            get_effective_amm_config(&amm_config, &pool_state, Some(pool_fee_override))
+                .unwrap_err(),
+            ErrorCode::InvalidFeeRates.into()
+        );
+    }
+}
@@ -5,8 +5,10 @@ use crate::pool::{RewardInfo, REWARD_NUM};
 use crate::util::*;
 use crate::Result;
 use anchor_lang::{prelude::*, system_program};
+use std::cell::{Ref, RefMut};
 #[cfg(feature = "enable-log")]
 use std::convert::identity;
+use std::ops::Deref;
 
 pub const TICK_ARRAY_SEED: &str = "tick_array";
 pub const TICK_ARRAY_SIZE_USIZE: usize = 60;
@@ -156,12 +158,12 @@ impl TickArrayState {
     }
 
     /// Base on swap directioin, return the first initialized tick in the tick array.
-    pub fn first_initialized_tick(&mut self, zero_for_one: bool) -> Result<&mut TickState> {
+    pub fn first_initialized_tick(&self, zero_for_one: bool) -> Result<&TickState> {
         if zero_for_one {
             let mut i = TICK_ARRAY_SIZE - 1;
             while i >= 0 {
                 if self.ticks[i as usize].is_initialized() {
-                    return Ok(self.ticks.get_mut(i as usize).unwrap());
+                    return Ok(&self.ticks[i as usize]);
                 }
                 i = i - 1;
             }
@@ -169,7 +171,7 @@ impl TickArrayState {
             let mut i = 0;
             while i < TICK_ARRAY_SIZE_USIZE {
                 if self.ticks[i].is_initialized() {
-                    return Ok(self.ticks.get_mut(i).unwrap());
+                    return Ok(&self.ticks[i]);
                 }
                 i = i + 1;
             }
@@ -181,11 +183,11 @@ impl TickArrayState {
     /// and current_tick_index % tick_spacing maybe not equal zero.
     /// If price move to left tick <= current_tick_index, or to right tick > current_tick_index
     pub fn next_initialized_tick(
-        &mut self,
+        &self,
         current_tick_index: i32,
         tick_spacing: u16,
         zero_for_one: bool,
-    ) -> Result<Option<&mut TickState>> {
+    ) -> Result<Option<&TickState>> {
         let current_tick_array_start_index =
             TickArrayState::get_array_start_index(current_tick_index, tick_spacing);
         if current_tick_array_start_index != self.start_tick_index {
@@ -197,7 +199,7 @@ impl TickArrayState {
         if zero_for_one {
             while offset_in_array >= 0 {
                 if self.ticks[offset_in_array as usize].is_initialized() {
-                    return Ok(self.ticks.get_mut(offset_in_array as usize));
+                    return Ok(self.ticks.get(offset_in_array as usize));
                 }
                 offset_in_array = offset_in_array - 1;
             }
@@ -205,7 +207,7 @@ impl TickArrayState {
             offset_in_array = offset_in_array + 1;
             while offset_in_array < TICK_ARRAY_SIZE {
                 if self.ticks[offset_in_array as usize].is_initialized() {
-                    return Ok(self.ticks.get_mut(offset_in_array as usize));
+                    return Ok(self.ticks.get(offset_in_array as usize));
                 }
                 offset_in_array = offset_in_array + 1;
             }
@@ -248,6 +250,174 @@ impl TickArrayState {
     pub fn tick_count(tick_spacing: u16) -> i32 {
         TICK_ARRAY_SIZE * i32::from(tick_spacing)
     }
+
+    /// The offsets of both ticks of the limit order range starting at `tick_lower_index`,
+    /// None if the range isn't inside this tick array, in which case it can't hold any limit order
+    fn get_limit_order_range_offsets(
+        &self,
+        tick_lower_index: i32,
+        tick_spacing: u16,
+    ) -> Option<(usize, usize)> {
+        let tick_upper_index = tick_lower_index + i32::from(tick_spacing);
+        if tick_lower_index < self.start_tick_index
+            || tick_upper_index >= self.start_tick_index + TickArrayState::tick_count(tick_spacing)
+        {
+            return None;
+        }
+        let offset_lower =
+            ((tick_lower_index - self.start_tick_index) / i32::from(tick_spacing)) as usize;
+        Some((offset_lower, offset_lower + 1))
+    }
+
+    /// The lower ticks of the limit order ranges the price enters and leaves when crossing `tick_index` in the swap direction
+    fn get_limit_order_ranges_crossed(
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> (i32, i32) {
+        if zero_for_one {
+            (tick_index - i32::from(tick_spacing), tick_index)
+        } else {
+            (tick_index, tick_index - i32::from(tick_spacing))
+        }
+    }
+
+    /// The limit order liquidity of the range starting at `tick_lower_index`, zero if the range isn't inside this tick array
+    fn get_limit_order_liquidity(&self, tick_lower_index: i32, tick_spacing: u16) -> u128 {
+        match self.get_limit_order_range_offsets(tick_lower_index, tick_spacing) {
+            Some((offset_lower, _)) => {
+                let tick_lower = &self.ticks[offset_lower];
+                tick_lower
+                    .limit_order_liquidity_0
+                    .checked_add(tick_lower.limit_order_liquidity_1)
+                    .unwrap()
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the limit order liquidity of the range the price enters and of the range the price leaves
+    /// when crossing `tick_index` in the swap direction, without filling any order
+    pub fn get_limit_order_liquidity_crossed(
+        &self,
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> (u128, u128) {
+        let (entering_tick_lower, leaving_tick_lower) =
+            TickArrayState::get_limit_order_ranges_crossed(tick_index, tick_spacing, zero_for_one);
+        (
+            self.get_limit_order_liquidity(entering_tick_lower, tick_spacing),
+            self.get_limit_order_liquidity(leaving_tick_lower, tick_spacing),
+        )
+    }
+
+    /// Runs the limit order transition of crossing `tick_index` in the swap direction.
+    /// The unfilled orders of the range the price leaves are filled if the price leaves it at their far end,
+    /// their liquidity is taken out of the liquidity_net of both range ticks so that it's never added back.
+    /// Returns the limit order liquidity of the range the price enters and of the range the price leaves
+    pub fn cross_limit_orders(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Result<(u128, u128)> {
+        let (liquidity_entering, liquidity_leaving) =
+            self.get_limit_order_liquidity_crossed(tick_index, tick_spacing, zero_for_one);
+        let (_, leaving_tick_lower) =
+            TickArrayState::get_limit_order_ranges_crossed(tick_index, tick_spacing, zero_for_one);
+        if let Some((offset_lower, offset_upper)) =
+            self.get_limit_order_range_offsets(leaving_tick_lower, tick_spacing)
+        {
+            let tick_lower = &mut self.ticks[offset_lower];
+            // orders selling token_0 are filled at the upper tick, orders selling token_1 at the lower tick
+            let liquidity_filled = if zero_for_one {
+                let liquidity_filled = tick_lower.limit_order_liquidity_1;
+                tick_lower.limit_order_liquidity_1 = 0;
+                liquidity_filled
+            } else {
+                let liquidity_filled = tick_lower.limit_order_liquidity_0;
+                tick_lower.limit_order_liquidity_0 = 0;
+                liquidity_filled
+            };
+            if liquidity_filled > 0 {
+                let liquidity_filled = i128::try_from(liquidity_filled).unwrap();
+                tick_lower.limit_order_fill_count =
+                    tick_lower.limit_order_fill_count.checked_add(1).unwrap();
+                tick_lower.liquidity_net = tick_lower
+                    .liquidity_net
+                    .checked_sub(liquidity_filled)
+                    .unwrap();
+                let tick_upper = &mut self.ticks[offset_upper];
+                tick_upper.liquidity_net = tick_upper
+                    .liquidity_net
+                    .checked_add(liquidity_filled)
+                    .unwrap();
+            }
+        }
+        Ok((liquidity_entering, liquidity_leaving))
+    }
+}
+
+/// The tick array accounts a swap runs against.
+/// The ticks crossed are written back to the accounts borrowed mutably, the accounts borrowed
+/// immutably are only read, which is enough to quote a swap as the swap never reads back a tick it has crossed
+pub trait SwapTickArray: Deref<Target = TickArrayState> {
+    /// Writes the tick crossed back to the tick array
+    fn update_tick_state(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        tick_state: TickState,
+    ) -> Result<()>;
+
+    /// Runs the limit order transition of crossing `tick_index`, see `TickArrayState::cross_limit_orders`
+    fn cross_limit_orders(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Result<(u128, u128)>;
+}
+
+impl SwapTickArray for RefMut<'_, TickArrayState> {
+    fn update_tick_state(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        tick_state: TickState,
+    ) -> Result<()> {
+        TickArrayState::update_tick_state(self, tick_index, tick_spacing, tick_state)
+    }
+
+    fn cross_limit_orders(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Result<(u128, u128)> {
+        TickArrayState::cross_limit_orders(self, tick_index, tick_spacing, zero_for_one)
+    }
+}
+
+impl SwapTickArray for Ref<'_, TickArrayState> {
+    fn update_tick_state(
+        &mut self,
+        _tick_index: i32,
+        _tick_spacing: u16,
+        _tick_state: TickState,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn cross_limit_orders(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Result<(u128, u128)> {
+        Ok(self.get_limit_order_liquidity_crossed(tick_index, tick_spacing, zero_for_one))
+    }
 }
 
 impl Default for TickArrayState {
@@ -281,8 +451,15 @@ pub struct TickState {
 
     // Reward growth per unit of liquidity like fee, array of Q64.64
     pub reward_growths_outside_x64: [u128; REWARD_NUM],
+
+    /// The unfilled liquidity of the limit orders selling token_0 on the range from this tick to the next tick
+    pub limit_order_liquidity_0: u128,
+    /// The unfilled liquidity of the limit orders selling token_1 on the range from this tick to the next tick
+    pub limit_order_liquidity_1: u128,
+    /// The number of times the limit orders on the range from this tick to the next tick were filled
+    pub limit_order_fill_count: u32,
     // Unused bytes for future upgrades.
-    pub padding: [u32; 13],
+    pub padding: [u32; 4],
 }
 
 impl TickState {
@@ -379,6 +556,20 @@ impl TickState {
         self.liquidity_gross != 0
     }
 
+    /// Removes the liquidity of a claimed limit order, whose liquidity_net was already removed when it was filled.
+    /// Returns true if the tick was flipped from initialized to uninitialized
+    pub fn remove_filled_limit_order(&mut self, liquidity: u128) -> Result<bool> {
+        self.liquidity_gross = self
+            .liquidity_gross
+            .checked_sub(liquidity)
+            .ok_or(ErrorCode::LiquiditySubValueErr)?;
+        let flipped = self.liquidity_gross == 0;
+        if flipped {
+            self.clear();
+        }
+        Ok(flipped)
+    }
+
     /// Common checks for a valid tick input.
     /// A tick is valid if it lies within tick boundaries
     pub fn check_is_out_of_boundary(tick: i32) -> bool {
@@ -1307,6 +1498,87 @@ pub mod tick_array_test {
             assert_eq!(reward_frowth_inside_delta, 500);
         }
     }
+
+    mod cross_limit_orders_test {
+        use super::*;
+        use std::convert::identity;
+
+        fn build_limit_order_tick(
+            tick: i32,
+            liquidity_net: i128,
+            limit_order_liquidity_0: u128,
+            limit_order_liquidity_1: u128,
+        ) -> TickState {
+            TickState {
+                tick,
+                liquidity_gross: 1,
+                liquidity_net,
+                limit_order_liquidity_0,
+                limit_order_liquidity_1,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn fill_orders_on_leaving_range_test() {
+            // an order selling token_0 on [10, 20] and one selling token_1 on [20, 30]
+            let tick_array = &mut build_tick_array_with_tick_states(
+                Pubkey::default(),
+                0,
+                10,
+                vec![
+                    build_limit_order_tick(10, 100, 100, 0),
+                    build_limit_order_tick(20, -100 + 50, 0, 50),
+                    build_limit_order_tick(30, -50, 0, 0),
+                ],
+            );
+            let mut tick_array = tick_array.borrow_mut();
+
+            // the price moves up into [10, 20], nothing is filled
+            let (entering, leaving) = tick_array.cross_limit_orders(10, 10, false).unwrap();
+            assert_eq!((entering, leaving), (100, 0));
+            assert_eq!(identity(tick_array.ticks[1].limit_order_fill_count), 0);
+
+            // the price moves up out of [10, 20] into [20, 30], the token_0 order is filled
+            let (entering, leaving) = tick_array.cross_limit_orders(20, 10, false).unwrap();
+            assert_eq!((entering, leaving), (50, 100));
+            assert_eq!(identity(tick_array.ticks[1].limit_order_liquidity_0), 0);
+            assert_eq!(identity(tick_array.ticks[1].limit_order_fill_count), 1);
+            assert_eq!(identity(tick_array.ticks[1].liquidity_net), 0);
+            assert_eq!(identity(tick_array.ticks[2].liquidity_net), 50);
+            // the token_1 order isn't filled by leaving its range at the upper tick
+            let (entering, leaving) = tick_array.cross_limit_orders(30, 10, false).unwrap();
+            assert_eq!((entering, leaving), (0, 50));
+            assert_eq!(identity(tick_array.ticks[2].limit_order_liquidity_1), 50);
+            assert_eq!(identity(tick_array.ticks[2].limit_order_fill_count), 0);
+
+            // the price moves back down through [20, 30], the token_1 order is filled at the lower tick
+            let (entering, leaving) = tick_array.cross_limit_orders(30, 10, true).unwrap();
+            assert_eq!((entering, leaving), (50, 0));
+            let (entering, leaving) = tick_array.cross_limit_orders(20, 10, true).unwrap();
+            assert_eq!((entering, leaving), (0, 50));
+            assert_eq!(identity(tick_array.ticks[2].limit_order_liquidity_1), 0);
+            assert_eq!(identity(tick_array.ticks[2].limit_order_fill_count), 1);
+            assert_eq!(identity(tick_array.ticks[2].liquidity_net), 0);
+            assert_eq!(identity(tick_array.ticks[3].liquidity_net), 0);
+        }
+
+        #[test]
+        fn range_outside_tick_array_test() {
+            let tick_array = &mut build_tick_array_with_tick_states(
+                Pubkey::default(),
+                0,
+                10,
+                vec![build_limit_order_tick(590, 100, 100, 0)],
+            );
+            let mut tick_array = tick_array.borrow_mut();
+            // [590, 600] spans two tick arrays, so it can't hold limit orders
+            let (entering, leaving) = tick_array.cross_limit_orders(590, 10, false).unwrap();
+            assert_eq!((entering, leaving), (0, 0));
+            assert_eq!(identity(tick_array.ticks[59].limit_order_liquidity_0), 100);
+        }
+    }
+
     mod tick_array_layout_test {
         use super::*;
         use anchor_lang::Discriminator;
@@ -1333,10 +1605,13 @@ pub mod tick_array_test {
                 0x11223344556600778899aabbccddeeff,
                 0x11223344556677008899aabbccddeeff,
             ];
-            let mut tick_padding: [u32; 13] = [0u32; 13];
-            let mut tick_padding_data = [0u8; 4 * 13];
+            let limit_order_liquidity_0: u128 = 0x11223344556677880099aabbccddeeff;
+            let limit_order_liquidity_1: u128 = 0x11223344556677889900aabbccddeeff;
+            let limit_order_fill_count: u32 = 0x12345687;
+            let mut tick_padding: [u32; 4] = [0u32; 4];
+            let mut tick_padding_data = [0u8; 4 * 4];
             let mut offset = 0;
-            for i in 0..13 {
+            for i in 0..4 {
                 tick_padding[i] = u32::MAX - 3 * i as u32;
                 tick_padding_data[offset..offset + 4]
                     .copy_from_slice(&tick_padding[i].to_le_bytes());
@@ -1360,8 +1635,14 @@ pub mod tick_array_test {
                     .copy_from_slice(&reward_growths_outside_x64[i].to_le_bytes());
                 offset += 16;
             }
-            tick_data[offset..offset + 4 * 13].copy_from_slice(&tick_padding_data);
-            offset += 4 * 13;
+            tick_data[offset..offset + 16].copy_from_slice(&limit_order_liquidity_0.to_le_bytes());
+            offset += 16;
+            tick_data[offset..offset + 16].copy_from_slice(&limit_order_liquidity_1.to_le_bytes());
+            offset += 16;
+            tick_data[offset..offset + 4].copy_from_slice(&limit_order_fill_count.to_le_bytes());
+            offset += 4;
+            tick_data[offset..offset + 4 * 4].copy_from_slice(&tick_padding_data);
+            offset += 4 * 4;
             assert_eq!(offset, tick_data.len());
             assert_eq!(tick_data.len(), core::mem::size_of::<TickState>());
 
@@ -1419,6 +1700,12 @@ pub mod tick_array_test {
                     unpack_reward_growths_outside_x64,
                     reward_growths_outside_x64
                 );
+                let unpack_limit_order_liquidity_0 = tick_item.limit_order_liquidity_0;
+                assert_eq!(unpack_limit_order_liquidity_0, limit_order_liquidity_0);
+                let unpack_limit_order_liquidity_1 = tick_item.limit_order_liquidity_1;
+                assert_eq!(unpack_limit_order_liquidity_1, limit_order_liquidity_1);
+                let unpack_limit_order_fill_count = tick_item.limit_order_fill_count;
+                assert_eq!(unpack_limit_order_fill_count, limit_order_fill_count);
                 let unpack_tick_padding = tick_item.padding;
                 assert_eq!(unpack_tick_padding, tick_padding);
             }
@@ -0,0 +1,551 @@
+use crate::error::ErrorCode;
+use crate::libraries::{big_num::U256, fixed_point_64, full_math::MulDiv};
+use anchor_lang::prelude::*;
+
+pub const TWAMM_ORDER_POOL_SEED: &str = "twamm_order_pool";
+pub const TWAMM_ORDER_SEED: &str = "twamm_order";
+/// Orders expire on multiples of the interval
+pub const TWAMM_INTERVAL_SECONDS: u64 = 3600;
+/// The number of expiry buckets, an order can't last longer than `TWAMM_EXPIRY_NUM - 1` intervals
+pub const TWAMM_EXPIRY_NUM: usize = 168;
+/// The keeper fee taken from the amount sold by each execution, denominated in hundredths of a bip (10^-6)
+pub const TWAMM_KEEPER_FEE_RATE: u32 = 500;
+/// How far in ticks an execution can move the price from the TWAP, if the pool has no price deviation limit
+pub const TWAMM_MAX_DEVIATION_TICKS: u32 = 100;
+
+/// The sell rate of the orders expiring at the same time
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct TwammExpiry {
+    /// The expiry time of the orders in the bucket
+    pub expiry_time: u64,
+    /// The sell rate removed from the order pool at the expiry time, Q64.64 tokens per second
+    pub sell_rate_ending_x64: u128,
+    /// The earnings factor of the order pool at the expiry time, once the expiry is executed
+    pub earnings_factor_x64: u128,
+    /// The unsold factor of the order pool at the expiry time, once the expiry is executed
+    pub unsold_factor_x64: u128,
+    /// The number of orders expiring at the expiry time that aren't withdrawn yet
+    pub order_count: u32,
+    pub padding: u32,
+}
+
+impl TwammExpiry {
+    pub const LEN: usize = 8 + 16 + 16 + 16 + 4 + 4;
+}
+
+/// The long term orders selling one token of a pool, executed against the pool by `execute_twamm`
+///
+/// PDA of `[TWAMM_ORDER_POOL_SEED, pool, zero_for_one]`
+///
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct TwammOrderPool {
+    /// The pool the orders are executed against
+    pub pool_id: Pubkey,
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// 1 if the orders sell token_0 for token_1, 0 if they sell token_1 for token_0
+    pub zero_for_one: u8,
+    /// The time the orders were executed up to
+    pub last_execution_time: u64,
+    /// The sum of the sell rates of the active orders, Q64.64 tokens per second
+    pub sell_rate_x64: u128,
+    /// The amount of token bought per unit of sell rate, Q64.64, only differences are meaningful
+    pub earnings_factor_x64: u128,
+    /// The amount accrued for selling but not sold yet, Q64.64, below one token between executions
+    pub unsold_amount_x64: u128,
+    /// The amount of token the executions couldn't sell per unit of sell rate, Q64.64,
+    /// refunded to the orders. Only differences are meaningful
+    pub unsold_factor_x64: u128,
+    /// The order expiries, indexed by the expiry interval
+    pub expiries: [TwammExpiry; TWAMM_EXPIRY_NUM],
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 6],
+}
+
+impl Default for TwammOrderPool {
+    #[inline]
+    fn default() -> TwammOrderPool {
+        TwammOrderPool {
+            pool_id: Pubkey::default(),
+            bump: 0,
+            zero_for_one: 0,
+            last_execution_time: 0,
+            sell_rate_x64: 0,
+            earnings_factor_x64: 0,
+            unsold_amount_x64: 0,
+            unsold_factor_x64: 0,
+            expiries: [TwammExpiry::default(); TWAMM_EXPIRY_NUM],
+            padding: [0; 6],
+        }
+    }
+}
+
+impl TwammOrderPool {
+    pub const LEN: usize =
+        8 + 32 + 1 + 1 + 8 + 16 + 16 + 16 + 16 + TwammExpiry::LEN * TWAMM_EXPIRY_NUM + 48;
+
+    pub fn key(pool_id: Pubkey, zero_for_one: bool) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                TWAMM_ORDER_POOL_SEED.as_bytes(),
+                pool_id.as_ref(),
+                &[zero_for_one as u8],
+            ],
+            &crate::id(),
+        )
+        .0
+    }
+
+    pub fn initialize(
+        &mut self,
+        bump: u8,
+        pool_id: Pubkey,
+        zero_for_one: bool,
+        block_timestamp: u64,
+    ) {
+        self.bump = bump;
+        self.pool_id = pool_id;
+        self.zero_for_one = zero_for_one as u8;
+        self.last_execution_time = block_timestamp;
+    }
+
+    pub fn is_zero_for_one(&self) -> bool {
+        self.zero_for_one == 1
+    }
+
+    fn expiry_index(expiry_time: u64) -> usize {
+        ((expiry_time / TWAMM_INTERVAL_SECONDS) % TWAMM_EXPIRY_NUM as u64) as usize
+    }
+
+    /// The expiry time of an order lasting `intervals` after the current interval
+    pub fn get_expiry_time(block_timestamp: u64, intervals: u16) -> Result<u64> {
+        require!(
+            intervals > 0 && usize::from(intervals) < TWAMM_EXPIRY_NUM,
+            ErrorCode::InvalidTwammOrderDuration
+        );
+        Ok(
+            (block_timestamp / TWAMM_INTERVAL_SECONDS + u64::from(intervals))
+                * TWAMM_INTERVAL_SECONDS,
+        )
+    }
+
+    pub fn get_expiry(&self, expiry_time: u64) -> &TwammExpiry {
+        &self.expiries[TwammOrderPool::expiry_index(expiry_time)]
+    }
+
+    /// Adds an order selling `sell_rate_x64` from the last execution until `expiry_time`
+    pub fn add_order(&mut self, sell_rate_x64: u128, expiry_time: u64) -> Result<()> {
+        let expiry = &mut self.expiries[TwammOrderPool::expiry_index(expiry_time)];
+        if expiry.expiry_time != expiry_time {
+            // the bucket is reused once all the orders of its previous expiry are withdrawn
+            require_eq!(expiry.order_count, 0, ErrorCode::TwammExpiryInUse);
+            *expiry = TwammExpiry {
+                expiry_time,
+                ..Default::default()
+            };
+        }
+        expiry.sell_rate_ending_x64 = expiry
+            .sell_rate_ending_x64
+            .checked_add(sell_rate_x64)
+            .unwrap();
+        expiry.order_count = expiry.order_count.checked_add(1).unwrap();
+        self.sell_rate_x64 = self.sell_rate_x64.checked_add(sell_rate_x64).unwrap();
+        Ok(())
+    }
+
+    /// Removes an order, returns the earnings factor and the unsold factor the order stopped at
+    pub fn remove_order(&mut self, sell_rate_x64: u128, expiry_time: u64) -> (u128, u128) {
+        let expired = expiry_time <= self.last_execution_time;
+        let earnings_factor_x64 = self.earnings_factor_x64;
+        let unsold_factor_x64 = self.unsold_factor_x64;
+        let expiry = &mut self.expiries[TwammOrderPool::expiry_index(expiry_time)];
+        expiry.order_count = expiry.order_count.checked_sub(1).unwrap();
+        if expired {
+            return (expiry.earnings_factor_x64, expiry.unsold_factor_x64);
+        }
+        // the order is still active, stop selling it now
+        expiry.sell_rate_ending_x64 = expiry
+            .sell_rate_ending_x64
+            .checked_sub(sell_rate_x64)
+            .unwrap();
+        self.sell_rate_x64 = self.sell_rate_x64.checked_sub(sell_rate_x64).unwrap();
+        (earnings_factor_x64, unsold_factor_x64)
+    }
+
+    /// The end time of the next execution segment, the orders are executed up to the next expiry
+    /// at which the sell rate changes or up to the block time
+    pub fn next_segment_end(&self, block_timestamp: u64) -> u64 {
+        // all the orders expire within `TWAMM_EXPIRY_NUM` intervals of the last execution
+        let last_expiry_time = block_timestamp
+            .min(self.last_execution_time + TWAMM_EXPIRY_NUM as u64 * TWAMM_INTERVAL_SECONDS);
+        let mut expiry_time =
+            (self.last_execution_time / TWAMM_INTERVAL_SECONDS + 1) * TWAMM_INTERVAL_SECONDS;
+        while expiry_time <= last_expiry_time {
+            let expiry = self.get_expiry(expiry_time);
+            if expiry.expiry_time == expiry_time && expiry.sell_rate_ending_x64 > 0 {
+                return expiry_time;
+            }
+            expiry_time += TWAMM_INTERVAL_SECONDS;
+        }
+        block_timestamp
+    }
+
+    /// Accrues the sell rate up to `segment_end`, returns the amount to sell
+    pub fn accrue_sell_amount(&mut self, segment_end: u64) -> u64 {
+        let duration = segment_end.checked_sub(self.last_execution_time).unwrap();
+        self.unsold_amount_x64 = self
+            .unsold_amount_x64
+            .checked_add(
+                self.sell_rate_x64
+                    .checked_mul(u128::from(duration))
+                    .unwrap(),
+            )
+            .unwrap();
+        (self.unsold_amount_x64 >> fixed_point_64::RESOLUTION) as u64
+    }
+
+    /// The factor increase sharing `amount` between the orders by their sell rates
+    fn get_factor_delta_x64(&self, amount: u64) -> Result<u128> {
+        let factor_delta_x64 = U256::from(amount)
+            .mul_div_floor(
+                U256::from(fixed_point_64::Q64) * U256::from(fixed_point_64::Q64),
+                U256::from(self.sell_rate_x64),
+            )
+            .unwrap();
+        require!(
+            factor_delta_x64 <= U256::from(u128::MAX),
+            ErrorCode::CalculateOverflow
+        );
+        Ok(factor_delta_x64.as_u128())
+    }
+
+    /// Settles the execution segment ending at `segment_end`, where `amount_sold` was taken out
+    /// of the accrued amount and `amount_out` bought for the orders.
+    /// The whole tokens the segment left unsold are refunded to the orders it sold for
+    pub fn settle_segment(
+        &mut self,
+        segment_end: u64,
+        amount_sold: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        self.unsold_amount_x64 = self
+            .unsold_amount_x64
+            .checked_sub(u128::from(amount_sold) << fixed_point_64::RESOLUTION)
+            .unwrap();
+        if amount_out > 0 {
+            self.earnings_factor_x64 = self
+                .earnings_factor_x64
+                .wrapping_add(self.get_factor_delta_x64(amount_out)?);
+        }
+        // the amount the pool couldn't take is refunded, it isn't sold for the orders active later
+        let amount_unsold = (self.unsold_amount_x64 >> fixed_point_64::RESOLUTION) as u64;
+        if amount_unsold > 0 {
+            self.unsold_factor_x64 = self
+                .unsold_factor_x64
+                .wrapping_add(self.get_factor_delta_x64(amount_unsold)?);
+            self.unsold_amount_x64 -= u128::from(amount_unsold) << fixed_point_64::RESOLUTION;
+        }
+        let expiry_crossed = segment_end > self.last_execution_time;
+        self.last_execution_time = segment_end;
+
+        let earnings_factor_x64 = self.earnings_factor_x64;
+        let unsold_factor_x64 = self.unsold_factor_x64;
+        let expiry = &mut self.expiries[TwammOrderPool::expiry_index(segment_end)];
+        if expiry_crossed && expiry.expiry_time == segment_end {
+            expiry.earnings_factor_x64 = earnings_factor_x64;
+            expiry.unsold_factor_x64 = unsold_factor_x64;
+            self.sell_rate_x64 = self
+                .sell_rate_x64
+                .checked_sub(expiry.sell_rate_ending_x64)
+                .unwrap();
+            if self.sell_rate_x64 == 0 {
+                // nothing is left to sell the dust under one token for
+                self.unsold_amount_x64 = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A long term order selling an amount evenly until its expiry
+///
+/// PDA of `[TWAMM_ORDER_SEED, order_pool, owner, order_nonce]`
+///
+#[account]
+#[derive(Default, Debug)]
+pub struct TwammOrderState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The order pool the order sells through
+    pub order_pool: Pubkey,
+    /// The owner of the order
+    pub owner: Pubkey,
+    /// The sell rate of the order, Q64.64 tokens per second
+    pub sell_rate_x64: u128,
+    /// The earnings factor of the order pool when the order was placed
+    pub earnings_factor_last_x64: u128,
+    /// The time the order stops selling
+    pub expiry_time: u64,
+    /// The unsold factor of the order pool when the order was placed
+    pub unsold_factor_last_x64: u128,
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 6],
+}
+
+impl TwammOrderState {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 16 + 16 + 8 + 16 + 48;
+
+    /// The amount of the order between the factor when it was placed and `factor_x64`
+    fn get_amount_by_factor(&self, factor_x64: u128, factor_last_x64: u128) -> u64 {
+        U256::from(self.sell_rate_x64)
+            .mul_div_floor(
+                U256::from(factor_x64.wrapping_sub(factor_last_x64)),
+                U256::from(fixed_point_64::Q64) * U256::from(fixed_point_64::Q64),
+            )
+            .unwrap()
+            .as_u64()
+    }
+
+    /// The amount bought for the order between two earnings factors
+    pub fn get_earnings(&self, earnings_factor_x64: u128) -> u64 {
+        self.get_amount_by_factor(earnings_factor_x64, self.earnings_factor_last_x64)
+    }
+
+    /// The amount of the order the executions couldn't sell between two unsold factors
+    pub fn get_unsold_refund(&self, unsold_factor_x64: u128) -> u64 {
+        self.get_amount_by_factor(unsold_factor_x64, self.unsold_factor_last_x64)
+    }
+
+    /// The amount the order hasn't sold yet when it's executed up to `last_execution_time`
+    pub fn get_unsold_amount(&self, last_execution_time: u64) -> u64 {
+        let duration = self.expiry_time.saturating_sub(last_execution_time);
+        ((U256::from(self.sell_rate_x64) * U256::from(duration)) >> fixed_point_64::RESOLUTION)
+            .as_u64()
+    }
+}
+
+/// Emitted when a TWAMM order is placed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PlaceTwammOrderEvent {
+    pub pool_state: Pubkey,
+    pub order_pool: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    /// true if the order sells token_0, false if it sells token_1
+    pub zero_for_one: bool,
+    /// The amount of the sold token deposited for the order
+    pub amount: u64,
+    /// The token transfer fee for amount
+    pub transfer_fee: u64,
+    /// The sell rate of the order, Q64.64 tokens per second
+    pub sell_rate_x64: u128,
+    /// The time the order stops selling
+    pub expiry_time: u64,
+}
+
+/// Emitted when a TWAMM order is withdrawn
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct WithdrawTwammOrderEvent {
+    pub pool_state: Pubkey,
+    pub order_pool: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    /// The amount of the sold token refunded to the owner
+    pub unsold_amount: u64,
+    /// The amount of the bought token paid to the owner
+    pub earnings: u64,
+}
+
+/// Emitted when the orders of an order pool are executed against the pool
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ExecuteTwammEvent {
+    pub pool_state: Pubkey,
+    pub order_pool: Pubkey,
+    pub keeper: Pubkey,
+    /// true if the orders sell token_0, false if they sell token_1
+    pub zero_for_one: bool,
+    /// The amount of the sold token swapped against the pool
+    pub amount_in: u64,
+    /// The amount of the bought token credited to the orders
+    pub amount_out: u64,
+    /// The amount of the sold token paid to the keeper
+    pub keeper_fee: u64,
+    /// The time the orders were executed up to
+    pub execution_time: u64,
+}
+
+#[cfg(test)]
+mod twamm_test {
+    use super::*;
+    use std::convert::identity;
+
+    const START_TIME: u64 = 100 * TWAMM_INTERVAL_SECONDS + 600;
+
+    fn build_order_pool() -> TwammOrderPool {
+        let mut order_pool = TwammOrderPool::default();
+        order_pool.initialize(255, Pubkey::default(), true, START_TIME);
+        order_pool
+    }
+
+    #[test]
+    fn expiry_time_test() {
+        assert_eq!(
+            TwammOrderPool::get_expiry_time(START_TIME, 2).unwrap(),
+            102 * TWAMM_INTERVAL_SECONDS
+        );
+        assert!(TwammOrderPool::get_expiry_time(START_TIME, 0).is_err());
+        assert!(TwammOrderPool::get_expiry_time(START_TIME, TWAMM_EXPIRY_NUM as u16).is_err());
+    }
+
+    #[test]
+    fn execute_until_expiry_test() {
+        let mut order_pool = build_order_pool();
+        let expiry_time = TwammOrderPool::get_expiry_time(START_TIME, 1).unwrap();
+        // sell 3000 token over the 3000 seconds left until the expiry
+        let order = TwammOrderState {
+            sell_rate_x64: 1 << 64,
+            expiry_time,
+            ..Default::default()
+        };
+        order_pool
+            .add_order(order.sell_rate_x64, expiry_time)
+            .unwrap();
+
+        // the execution stops at the expiry
+        let now = expiry_time + 100;
+        let segment_end = order_pool.next_segment_end(now);
+        assert_eq!(segment_end, expiry_time);
+        let amount_to_sell = order_pool.accrue_sell_amount(segment_end);
+        assert_eq!(amount_to_sell, 3000);
+        order_pool
+            .settle_segment(segment_end, amount_to_sell, 6000)
+            .unwrap();
+        assert_eq!(identity(order_pool.sell_rate_x64), 0);
+        assert_eq!(order.get_unsold_amount(order_pool.last_execution_time), 0);
+
+        // nothing is left to sell after the expiry
+        let segment_end = order_pool.next_segment_end(now);
+        assert_eq!(segment_end, now);
+        assert_eq!(order_pool.accrue_sell_amount(segment_end), 0);
+        order_pool.settle_segment(segment_end, 0, 0).unwrap();
+
+        let (earnings_factor_x64, _) = order_pool.remove_order(order.sell_rate_x64, expiry_time);
+        assert_eq!(order.get_earnings(earnings_factor_x64), 6000);
+        assert_eq!(identity(order_pool.get_expiry(expiry_time).order_count), 0);
+    }
+
+    #[test]
+    fn withdraw_active_order_test() {
+        let mut order_pool = build_order_pool();
+        let expiry_time = TwammOrderPool::get_expiry_time(START_TIME, 1).unwrap();
+        let order_0 = TwammOrderState {
+            sell_rate_x64: 1 << 64,
+            expiry_time,
+            ..Default::default()
+        };
+        let order_1 = TwammOrderState {
+            sell_rate_x64: 3 << 64,
+            expiry_time,
+            ..Default::default()
+        };
+        order_pool
+            .add_order(order_0.sell_rate_x64, expiry_time)
+            .unwrap();
+        order_pool
+            .add_order(order_1.sell_rate_x64, expiry_time)
+            .unwrap();
+
+        // execute the first 1000 seconds, the earnings are split by the sell rates
+        let segment_end = order_pool.next_segment_end(START_TIME + 1000);
+        let amount_to_sell = order_pool.accrue_sell_amount(segment_end);
+        assert_eq!(amount_to_sell, 4000);
+        order_pool
+            .settle_segment(segment_end, amount_to_sell, 8000)
+            .unwrap();
+
+        let (earnings_factor_x64, _) = order_pool.remove_order(order_0.sell_rate_x64, expiry_time);
+        assert_eq!(order_0.get_earnings(earnings_factor_x64), 2000);
+        assert_eq!(
+            order_0.get_unsold_amount(order_pool.last_execution_time),
+            2000
+        );
+        assert_eq!(identity(order_pool.sell_rate_x64), 3 << 64);
+        assert_eq!(identity(order_pool.get_expiry(expiry_time).order_count), 1);
+
+        // the bucket can't be reused by another expiry while an order is left in it
+        let next_expiry_time = expiry_time + TWAMM_EXPIRY_NUM as u64 * TWAMM_INTERVAL_SECONDS;
+        assert!(order_pool.add_order(1 << 64, next_expiry_time).is_err());
+    }
+
+    #[test]
+    fn refund_segment_left_unsold_test() {
+        let mut order_pool = build_order_pool();
+        let expiry_time = TwammOrderPool::get_expiry_time(START_TIME, 1).unwrap();
+        let order_0 = TwammOrderState {
+            sell_rate_x64: 1 << 64,
+            expiry_time,
+            ..Default::default()
+        };
+        let order_1 = TwammOrderState {
+            sell_rate_x64: 3 << 64,
+            expiry_time,
+            ..Default::default()
+        };
+        order_pool
+            .add_order(order_0.sell_rate_x64, expiry_time)
+            .unwrap();
+        order_pool
+            .add_order(order_1.sell_rate_x64, expiry_time)
+            .unwrap();
+
+        // the price limit lets the pool take 1000 of the 4000 accrued in the first 1000 seconds
+        let segment_end = order_pool.next_segment_end(START_TIME + 1000);
+        let amount_to_sell = order_pool.accrue_sell_amount(segment_end);
+        assert_eq!(amount_to_sell, 4000);
+        order_pool.settle_segment(segment_end, 1000, 2000).unwrap();
+        // the amount left unsold isn't carried over to the next segment
+        assert_eq!(identity(order_pool.unsold_amount_x64), 0);
+
+        // an order placed after the segment doesn't get any of it
+        let order_2 = TwammOrderState {
+            sell_rate_x64: 2 << 64,
+            expiry_time,
+            unsold_factor_last_x64: order_pool.unsold_factor_x64,
+            earnings_factor_last_x64: order_pool.earnings_factor_x64,
+            ..Default::default()
+        };
+        order_pool
+            .add_order(order_2.sell_rate_x64, expiry_time)
+            .unwrap();
+
+        // the rest of the orders is price limited out entirely, up to the expiry
+        let segment_end = order_pool.next_segment_end(expiry_time + 100);
+        assert_eq!(segment_end, expiry_time);
+        let amount_to_sell = order_pool.accrue_sell_amount(segment_end);
+        assert_eq!(amount_to_sell, 12000);
+        order_pool.settle_segment(segment_end, 0, 0).unwrap();
+        assert_eq!(identity(order_pool.sell_rate_x64), 0);
+
+        // the expired orders get back what wasn't sold for them, split by the sell rates
+        let refund = |order: &TwammOrderState, order_pool: &mut TwammOrderPool| {
+            let (earnings_factor_x64, unsold_factor_x64) =
+                order_pool.remove_order(order.sell_rate_x64, expiry_time);
+            (
+                order.get_unsold_amount(order_pool.last_execution_time)
+                    + order.get_unsold_refund(unsold_factor_x64),
+                order.get_earnings(earnings_factor_x64),
+            )
+        };
+        // a quarter of each order's first 1000 seconds was sold
+        assert_eq!(refund(&order_0, &mut order_pool), (2750, 500));
+        assert_eq!(refund(&order_1, &mut order_pool), (8250, 1500));
+        // 4000 accrued over the last 2000 seconds
+        assert_eq!(refund(&order_2, &mut order_pool), (4000, 0));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+
+/// The instruction discriminators of the hook interface, the anchor sighashes of the hook methods
+pub const BEFORE_SWAP_HOOK_DISCRIMINATOR: [u8; 8] = [227, 59, 240, 68, 164, 9, 29, 254];
+pub const AFTER_SWAP_HOOK_DISCRIMINATOR: [u8; 8] = [235, 215, 232, 183, 152, 109, 5, 35];
+pub const BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR: [u8; 8] = [172, 17, 157, 29, 42, 53, 99, 78];
+pub const AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR: [u8; 8] = [101, 75, 111, 77, 24, 43, 133, 189];
+
+/// The instruction data of `before_swap`, following `BEFORE_SWAP_HOOK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct BeforeSwapHook {
+    pub pool_state: Pubkey,
+    /// The user performing the swap
+    pub sender: Pubkey,
+    pub zero_for_one: bool,
+    pub is_base_input: bool,
+    /// The amount of the swap, the input amount if is_base_input, otherwise the output amount
+    pub amount_specified: u64,
+    pub sqrt_price_limit_x64: u128,
+}
+
+/// The instruction data of `after_swap`, following `AFTER_SWAP_HOOK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct AfterSwapHook {
+    pub pool_state: Pubkey,
+    /// The user performing the swap
+    pub sender: Pubkey,
+    pub zero_for_one: bool,
+    pub is_base_input: bool,
+    /// The amounts of token_0 and token_1 swapped, token 2022 transfer fee excluded
+    pub amount_0: u64,
+    pub amount_1: u64,
+    /// The sqrt price and the tick of the pool after the swap
+    pub sqrt_price_x64: u128,
+    pub tick: i32,
+}
+
+/// The instruction data of `before_modify_position`, following `BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct BeforeModifyPositionHook {
+    pub pool_state: Pubkey,
+    /// The owner of the position
+    pub owner: Pubkey,
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    /// The liquidity added if positive or removed if negative,
+    /// it's 0 when the added liquidity is calculated from the token amounts
+    pub liquidity_delta: i128,
+}
+
+/// The instruction data of `after_modify_position`, following `AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct AfterModifyPositionHook {
+    pub pool_state: Pubkey,
+    /// The owner of the position
+    pub owner: Pubkey,
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    /// The liquidity added if positive or removed if negative
+    pub liquidity_delta: i128,
+    /// The amounts of token_0 and token_1 deposited or withdrawn, fees excluded
+    pub amount_0: u64,
+    pub amount_1: u64,
+}
+
+/// Splits the hook accounts off the remaining accounts if the pool calls the hook for any of the
+/// `permissions` of the instruction, otherwise no hook accounts are expected.
+/// The hook accounts trail the remaining accounts, they start with the hook program of the pool
+/// and are followed by the accounts passed to the hook in order
+pub fn split_hook_accounts<'c, 'info>(
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    permissions: &[HookPermissionBitIndex],
+) -> Result<(&'c [AccountInfo<'info>], &'c [AccountInfo<'info>])> {
+    let pool_state = pool_state_loader.load()?;
+    split_accounts_at_hook_program(&pool_state, remaining_accounts, permissions)
+}
+
+fn split_accounts_at_hook_program<'c, T: Key>(
+    pool_state: &PoolState,
+    remaining_accounts: &'c [T],
+    permissions: &[HookPermissionBitIndex],
+) -> Result<(&'c [T], &'c [T])> {
+    if !permissions
+        .iter()
+        .any(|permission| pool_state.has_hook_permission(*permission))
+    {
+        return Ok((remaining_accounts, &[]));
+    }
+    let hook_program = pool_state.hook_program;
+    let hook_program_index = remaining_accounts
+        .iter()
+        .position(|account| account.key() == hook_program)
+        .ok_or(ErrorCode::InvalidHookProgram)?;
+    Ok(remaining_accounts.split_at(hook_program_index))
+}
+
+/// Calls the hook program of the pool if the pool has the permission,
+/// the pool is locked during the call so the hook can't swap or change the liquidity of the pool
+pub fn invoke_hook<'info, T: AnchorSerialize>(
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    hook_accounts: &[AccountInfo<'info>],
+    permission: HookPermissionBitIndex,
+    discriminator: [u8; 8],
+    params: &T,
+) -> Result<()> {
+    let hook_program_key = {
+        let pool_state = pool_state_loader.load()?;
+        if !pool_state.has_hook_permission(permission) {
+            return Ok(());
+        }
+        require!(!pool_state.is_locked(), ErrorCode::LOK);
+        pool_state.hook_program
+    };
+    let (hook_program, accounts) = hook_accounts
+        .split_first()
+        .ok_or(ErrorCode::InvalidHookProgram)?;
+    require!(
+        hook_program.key() == hook_program_key && hook_program.executable,
+        ErrorCode::InvalidHookProgram
+    );
+
+    let mut data = discriminator.to_vec();
+    params.serialize(&mut data)?;
+    pool_state_loader.load_mut()?.locked = 1;
+    invoke(
+        &Instruction {
+            program_id: hook_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|account_info| {
+                    if account_info.is_writable {
+                        AccountMeta::new(account_info.key(), account_info.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(account_info.key(), account_info.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        },
+        hook_accounts,
+    )?;
+    pool_state_loader.load_mut()?.locked = 0;
+    Ok(())
+}
+
+#[cfg(test)]
+mod hook_test {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+
+    #[test]
+    fn hook_discriminator_test() {
+        assert_eq!(
+            BEFORE_SWAP_HOOK_DISCRIMINATOR,
+            hash(b"global:before_swap").to_bytes()[..8]
+        );
+        assert_eq!(
+            AFTER_SWAP_HOOK_DISCRIMINATOR,
+            hash(b"global:after_swap").to_bytes()[..8]
+        );
+        assert_eq!(
+            BEFORE_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+            hash(b"global:before_modify_position").to_bytes()[..8]
+        );
+        assert_eq!(
+            AFTER_MODIFY_POSITION_HOOK_DISCRIMINATOR,
+            hash(b"global:after_modify_position").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn split_hook_accounts_test() {
+        let hook_program = Pubkey::new_unique();
+        let tick_array = Pubkey::new_unique();
+        let hook_account = Pubkey::new_unique();
+        let mut pool_state = PoolState::default();
+        // the pool only calls the hook before swaps
+        pool_state.initialize_hook(hook_program, 0b0001).unwrap();
+
+        let remaining_accounts = vec![tick_array, hook_program, hook_account];
+        let (accounts, hook_accounts) = split_accounts_at_hook_program(
+            &pool_state,
+            &remaining_accounts,
+            &SWAP_HOOK_PERMISSIONS,
+        )
+        .unwrap();
+        assert_eq!(accounts, &[tick_array]);
+        assert_eq!(hook_accounts, &[hook_program, hook_account]);
+
+        // a liquidity change doesn't call the hook, so the hook program isn't needed
+        let remaining_accounts = vec![tick_array];
+        let (accounts, hook_accounts) = split_accounts_at_hook_program(
+            &pool_state,
+            &remaining_accounts,
+            &MODIFY_POSITION_HOOK_PERMISSIONS,
+        )
+        .unwrap();
+        assert_eq!(accounts, &[tick_array]);
+        assert!(hook_accounts.is_empty());
+
+        // a swap without the hook program fails
+        assert_eq!(
+            split_accounts_at_hook_program(
+                &pool_state,
+                &remaining_accounts,
+                &SWAP_HOOK_PERMISSIONS
+            )
+            .unwrap_err(),
+            ErrorCode::InvalidHookProgram.into()
+        );
+
+        // nothing is split off for a pool without a hook
+        pool_state.initialize_hook(Pubkey::default(), 0).unwrap();
+        let remaining_accounts = vec![tick_array, hook_program];
+        let (accounts, hook_accounts) = split_accounts_at_hook_program(
+            &pool_state,
+            &remaining_accounts,
+            &SWAP_HOOK_PERMISSIONS,
+        )
+        .unwrap();
+        assert_eq!(accounts, &[tick_array, hook_program]);
+        assert!(hook_accounts.is_empty());
+    }
+}
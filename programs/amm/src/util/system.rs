@@ -55,6 +55,26 @@ pub fn create_or_allocate_account<'a>(
     Ok(())
 }
 
+/// Rejects the transaction if it lands after the `deadline` unix timestamp
+pub fn check_deadline(deadline: Option<u64>) -> Result<()> {
+    match deadline {
+        Some(deadline) => check_deadline_at(
+            deadline,
+            u64::try_from(Clock::get()?.unix_timestamp).unwrap(),
+        ),
+        None => Ok(()),
+    }
+}
+
+fn check_deadline_at(deadline: u64, current_timestamp: u64) -> Result<()> {
+    require_gte!(
+        deadline,
+        current_timestamp,
+        crate::error::ErrorCode::TransactionTooOld
+    );
+    Ok(())
+}
+
 #[cfg(not(any(test, feature = "client")))]
 pub fn get_recent_epoch() -> Result<u64> {
     Ok(Clock::get()?.epoch)
@@ -69,3 +89,23 @@ pub fn get_recent_epoch() -> Result<u64> {
         .as_secs()
         / (2 * 24 * 3600))
 }
+
+#[cfg(test)]
+mod system_test {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn check_deadline_test() {
+        // no deadline never reads the clock
+        assert!(check_deadline(None).is_ok());
+
+        assert!(check_deadline_at(1000, 999).is_ok());
+        // the deadline itself is still accepted
+        assert!(check_deadline_at(1000, 1000).is_ok());
+        assert_eq!(
+            check_deadline_at(1000, 1001).unwrap_err(),
+            ErrorCode::TransactionTooOld.into()
+        );
+    }
+}